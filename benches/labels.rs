@@ -0,0 +1,96 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use prometheus_rs::{escape_label_value, write_label_set, BorrowedLabel, Label};
+use std::fmt::Write;
+
+const PATHS: &[&str] = &[
+    "/",
+    "/index.html",
+    "/api/v1/users",
+    "/api/v1/users/1234/settings",
+    "/static/css/main.css",
+];
+
+fn owned_labels(c: &mut Criterion) {
+    let paths: Vec<String> = PATHS.iter().map(|&path| path.to_owned()).collect();
+
+    c.bench_function("encode owned labels", |b| {
+        let mut buf = String::with_capacity(1024);
+
+        b.iter(|| {
+            buf.clear();
+
+            for path in paths.iter() {
+                let labels = [
+                    Label::new("method", "GET").unwrap(),
+                    Label::new("path", path.clone()).unwrap(),
+                ];
+                write_label_set(&mut buf, labels.iter().map(Label::as_borrowed)).unwrap();
+            }
+
+            black_box(&buf);
+        })
+    });
+}
+
+fn borrowed_labels(c: &mut Criterion) {
+    let paths: Vec<String> = PATHS.iter().map(|&path| path.to_owned()).collect();
+
+    c.bench_function("encode borrowed labels", |b| {
+        let mut buf = String::with_capacity(1024);
+
+        b.iter(|| {
+            buf.clear();
+
+            for path in paths.iter() {
+                let labels = [
+                    BorrowedLabel::new("method", "GET").unwrap(),
+                    BorrowedLabel::new("path", path).unwrap(),
+                ];
+                write_label_set(&mut buf, labels.iter().copied()).unwrap();
+            }
+
+            black_box(&buf);
+        })
+    });
+}
+
+fn debug_label_values(c: &mut Criterion) {
+    c.bench_function("escape label values with debug", |b| {
+        let mut buf = String::with_capacity(1024);
+
+        b.iter(|| {
+            buf.clear();
+
+            for path in PATHS.iter() {
+                write!(buf, "{:?}", path).unwrap();
+            }
+
+            black_box(&buf);
+        })
+    });
+}
+
+fn escaped_label_values(c: &mut Criterion) {
+    c.bench_function("escape label values", |b| {
+        let mut buf = String::with_capacity(1024);
+
+        b.iter(|| {
+            buf.clear();
+
+            for path in PATHS.iter() {
+                escape_label_value(&mut buf, path);
+            }
+
+            black_box(&buf);
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    owned_labels,
+    borrowed_labels,
+    debug_label_values,
+    escaped_label_values
+);
+criterion_main!(benches);
@@ -1,14 +1,95 @@
 use std::{
     fmt::{self, Write},
-    ops,
-    sync::atomic::{self, AtomicI64, AtomicU64, Ordering},
+    hint, ops,
+    sync::atomic::{AtomicI32, AtomicI64, AtomicU32, AtomicU64, Ordering},
 };
 
+/// How collectors write their sample values while being encoded, passed to every
+/// [`Collectable`] encoding method. Label values like histogram buckets aren't affected
+///
+/// [`Collectable`]: crate::Collectable
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    float_precision: Option<usize>,
+}
+
+impl FormatOptions {
+    /// Options that write every value exactly
+    pub const fn new() -> Self {
+        Self {
+            float_precision: None,
+        }
+    }
+
+    /// Write float sample values with `precision` significant digits, or exactly if it's
+    /// `None`, see [`RegistryBuilder::float_precision`]
+    ///
+    /// [`RegistryBuilder::float_precision`]: crate::RegistryBuilder#method.float_precision
+    pub const fn with_float_precision(mut self, precision: Option<usize>) -> Self {
+        self.float_precision = precision;
+        self
+    }
+
+    pub const fn float_precision(&self) -> Option<usize> {
+        self.float_precision
+    }
+
+    /// Write a sample value, rounding finite floats to the float precision. `NaN`, `+Inf` and
+    /// `-Inf` are written as is
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::FormatOptions;
+    ///
+    /// let options = FormatOptions::new().with_float_precision(Some(3));
+    ///
+    /// let mut buf = String::new();
+    /// options.write_value(&mut buf, 2.0 / 3.0).unwrap();
+    /// buf.push(' ');
+    /// options.write_value(&mut buf, 123456u64).unwrap();
+    /// assert_eq!(buf, "0.667 123456");
+    /// ```
+    pub fn write_value<T: Num>(&self, buf: &mut String, value: T) -> fmt::Result {
+        match value.into() {
+            MetricValue::U64(int) => AtomicU64::format(int, buf, false),
+            MetricValue::I64(int) => AtomicI64::format(int, buf, false),
+            MetricValue::F64(float) => match self.float_precision {
+                Some(digits) if float.is_finite() => {
+                    AtomicF64::format(round_significant(float, digits), buf, false)
+                }
+                _ => AtomicF64::format(float, buf, false),
+            },
+        }
+    }
+}
+
+/// Round `float` to `digits` significant digits, at least one digit is always kept
+fn round_significant(float: f64, digits: usize) -> f64 {
+    format!("{:.*e}", digits.max(1) - 1, float)
+        .parse()
+        .unwrap_or(float)
+}
+
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct AtomicF64(AtomicU64);
 
 impl AtomicF64 {
+    /// The `NaN` Prometheus uses internally to mark a series as stale, with the bit pattern
+    /// `0x7ff0000000000002`. Stores and loads keep its exact bits, but it's written as `NaN` in
+    /// the text format since text has no way of telling `NaN`s apart
+    pub const STALE_MARKER: f64 = f64::from_bits(0x7ff0_0000_0000_0002);
+
+    /// Returns `true` if `val` has the exact bit pattern of [`AtomicF64::STALE_MARKER`], which
+    /// can't be checked with `==` like every other `NaN`
+    ///
+    /// [`AtomicF64::STALE_MARKER`]: crate::AtomicF64#associatedconstant.STALE_MARKER
+    #[inline]
+    pub fn is_stale_marker(val: f64) -> bool {
+        val.to_bits() == Self::STALE_MARKER.to_bits()
+    }
+
     #[inline]
     pub const fn zeroed() -> Self {
         Self(AtomicU64::new(0))
@@ -25,11 +106,15 @@ impl AtomicF64 {
             let current = self.0.load(order);
             let new = f64::from_bits(current) + val;
 
-            if self.0.compare_and_swap(current, f64::to_bits(new), order) == current {
+            if self
+                .0
+                .compare_exchange_weak(current, f64::to_bits(new), order, Ordering::Relaxed)
+                .is_ok()
+            {
                 break new;
             }
 
-            atomic::spin_loop_hint();
+            hint::spin_loop();
         }
     }
 
@@ -39,60 +124,238 @@ impl AtomicF64 {
             let current = self.0.load(order);
             let new = f64::from_bits(current) - val;
 
-            if self.0.compare_and_swap(current, f64::to_bits(new), order) == current {
+            if self
+                .0
+                .compare_exchange_weak(current, f64::to_bits(new), order, Ordering::Relaxed)
+                .is_ok()
+            {
                 break new;
             }
 
-            atomic::spin_loop_hint();
+            hint::spin_loop();
         }
     }
 
     #[inline]
     pub fn store(&self, val: f64, order: Ordering) {
-        loop {
-            let current = self.0.load(order);
+        self.0.store(f64::to_bits(val), order);
+    }
 
-            if self.0.compare_and_swap(current, f64::to_bits(val), order) == current {
-                break;
-            }
+    /// Stores `val`, returning the previous value
+    #[inline]
+    pub fn swap(&self, val: f64, order: Ordering) -> f64 {
+        f64::from_bits(self.0.swap(f64::to_bits(val), order))
+    }
 
-            atomic::spin_loop_hint();
-        }
+    /// Stores `new` if the current value is `current`, returning `Ok` with the previous value
+    /// on success and `Err` with the actual value on failure
+    ///
+    /// Values are compared by their bit patterns rather than with `==`, so `0.0` and `-0.0`
+    /// are considered different and a `NaN` can match another `NaN` with the same bits
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::AtomicF64;
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let float = AtomicF64::zeroed();
+    /// assert_eq!(float.compare_exchange(0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst), Ok(0.0));
+    /// assert_eq!(float.compare_exchange(0.0, 2.0, Ordering::SeqCst, Ordering::SeqCst), Err(1.0));
+    ///
+    /// // `-0.0` has a different bit pattern than `0.0`
+    /// float.store(0.0, Ordering::SeqCst);
+    /// assert!(float.compare_exchange(-0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst).is_err());
+    /// ```
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: f64,
+        new: f64,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<f64, f64> {
+        self.0
+            .compare_exchange(f64::to_bits(current), f64::to_bits(new), success, failure)
+            .map(f64::from_bits)
+            .map_err(f64::from_bits)
     }
 
     #[inline]
     pub fn load(&self, order: Ordering) -> f64 {
         f64::from_bits(self.0.load(order))
     }
+
+    /// Fetches the value and applies `f` to it, storing the result if `f` returns `Some`.
+    /// Mirrors [`AtomicU64::fetch_update`], returning `Ok` with the previous value if `f`
+    /// returned `Some` and `Err` with the previous value otherwise
+    ///
+    /// [`AtomicU64::fetch_update`]: https://doc.rust-lang.org/std/sync/atomic/struct.AtomicU64.html#method.fetch_update
+    #[inline]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<f64, f64>
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        self.0
+            .fetch_update(set_order, fetch_order, |bits| {
+                f(f64::from_bits(bits)).map(f64::to_bits)
+            })
+            .map(f64::from_bits)
+            .map_err(f64::from_bits)
+    }
+}
+
+/// The current value of a metric, letting generic code read samples without knowing which
+/// atomic type a metric uses
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum MetricValue {
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
+impl MetricValue {
+    /// Get the value as an `f64`, integers that are too large to be represented exactly are rounded
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::U64(int) => int as f64,
+            Self::I64(int) => int as f64,
+            Self::F64(float) => float,
+        }
+    }
+
+    /// Compares two values like `==`, except floats are compared by their bits so that `NaN`,
+    /// like a stale marker, equals itself
+    pub(crate) fn bitwise_eq(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::F64(a), Self::F64(b)) => a.to_bits() == b.to_bits(),
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl From<u64> for MetricValue {
+    fn from(int: u64) -> Self {
+        Self::U64(int)
+    }
+}
+
+impl From<u32> for MetricValue {
+    fn from(int: u32) -> Self {
+        Self::U64(int.into())
+    }
+}
+
+impl From<i32> for MetricValue {
+    fn from(int: i32) -> Self {
+        Self::I64(int.into())
+    }
+}
+
+impl From<i64> for MetricValue {
+    fn from(int: i64) -> Self {
+        Self::I64(int)
+    }
+}
+
+impl From<f64> for MetricValue {
+    fn from(float: f64) -> Self {
+        Self::F64(float)
+    }
 }
 
 pub trait Num:
-    Copy + ops::Add + ops::AddAssign + ops::Sub + Default + PartialEq + PartialOrd + fmt::Debug
+    Copy
+    + ops::Add
+    + ops::AddAssign
+    + ops::Sub
+    + ops::Mul<Output = Self>
+    + Default
+    + PartialEq
+    + PartialOrd
+    + fmt::Debug
+    + Into<MetricValue>
 {
+    /// The largest possible histogram bucket boundary, which is exposed as `+Inf`
+    const UPPER_BOUND: Self;
+
+    /// Convert `int` into the type, saturating at the type's largest value
     fn from_u64(int: u64) -> Self;
+
+    /// Convert `float` into the type, returning `None` if it's `NaN` or outside of the type's
+    /// range. Integer types truncate any fractional part
+    fn from_f64(float: f64) -> Option<Self>;
+
+    /// Add `rhs`, saturating at the type's bounds instead of overflowing
+    fn saturating_add(self, rhs: Self) -> Self;
+
+    /// Subtract `rhs`, saturating at the type's bounds instead of underflowing
+    fn saturating_sub(self, rhs: Self) -> Self;
+
+    /// Multiply by `rhs`, saturating at the type's bounds instead of overflowing
+    fn saturating_mul(self, rhs: Self) -> Self;
 }
 
 pub trait AtomicNum {
     type Type: Num;
 
     fn new() -> Self;
-    fn inc(&self);
-    fn inc_by(&self, inc: Self::Type);
-    fn dec(&self);
-    fn dec_by(&self, dec: Self::Type);
+    fn inc(&self) -> Self::Type;
+    fn inc_by(&self, inc: Self::Type) -> Self::Type;
+    fn saturating_inc_by(&self, inc: Self::Type) -> Self::Type;
+    fn saturating_dec_by(&self, dec: Self::Type) -> Self::Type;
+    fn dec(&self) -> Self::Type;
+    fn dec_by(&self, dec: Self::Type) -> Self::Type;
     fn set(&self, val: Self::Type);
+    fn update_max(&self, val: Self::Type) -> Self::Type;
+    fn update_min(&self, val: Self::Type) -> Self::Type;
     fn get(&self) -> Self::Type;
     fn clear(&self);
+    fn take(&self) -> Self::Type;
     fn format(int: Self::Type, f: &mut String, quotes: bool) -> fmt::Result;
 }
 
+// `added` and `subtracted` turn what `fetch_add` and `fetch_sub` return into the updated value,
+// the standard atomics return the previous value while `AtomicF64` returns the updated one
 macro_rules! impl_atomic {
-    ($($atomic:ty := $new:expr => $ty:ty = $fmt:expr,)*) => {
+    ($($atomic:ty := $new:expr => $ty:ty = $fmt:expr; added = $added:expr; subtracted = $subtracted:expr; saturating = $sat:expr; saturating_sub = $sat_sub:expr; saturating_mul = $sat_mul:expr; upper_bound = $upper:expr; from_f64 = $from_f64:expr,)*) => {
         $(
             impl Num for $ty {
+                const UPPER_BOUND: Self = $upper;
+
                 #[inline(always)]
                 fn from_u64(int: u64) -> Self {
-                    int as $ty
+                    // Every type's maximum converts to a `u64` exactly or, for `f64`, saturates
+                    int.min(<$ty>::MAX as u64) as $ty
+                }
+
+                #[inline(always)]
+                fn from_f64(float: f64) -> Option<Self> {
+                    let from_f64: fn(f64) -> Option<Self> = $from_f64;
+                    from_f64(float)
+                }
+
+                #[inline(always)]
+                fn saturating_add(self, rhs: Self) -> Self {
+                    let saturating_add: fn(Self, Self) -> Self = $sat;
+                    saturating_add(self, rhs)
+                }
+
+                #[inline(always)]
+                fn saturating_sub(self, rhs: Self) -> Self {
+                    let saturating_sub: fn(Self, Self) -> Self = $sat_sub;
+                    saturating_sub(self, rhs)
+                }
+
+                #[inline(always)]
+                fn saturating_mul(self, rhs: Self) -> Self {
+                    let saturating_mul: fn(Self, Self) -> Self = $sat_mul;
+                    saturating_mul(self, rhs)
                 }
             }
 
@@ -104,24 +367,56 @@ macro_rules! impl_atomic {
                     $new
                 }
 
-                /// Increment the value by 1
-                fn inc(&self) {
-                    self.fetch_add(1 as _, Ordering::SeqCst);
+                /// Increment the value by 1, returning the updated value
+                fn inc(&self) -> Self::Type {
+                    self.inc_by(1 as _)
                 }
 
-                /// Increment the value by `inc`
-                fn inc_by(&self, inc: Self::Type) {
-                    self.fetch_add(inc, Ordering::SeqCst);
+                /// Increment the value by `inc`, returning the updated value
+                fn inc_by(&self, inc: Self::Type) -> Self::Type {
+                    let added: fn(Self::Type, Self::Type) -> Self::Type = $added;
+                    added(self.fetch_add(inc, Ordering::SeqCst), inc)
                 }
 
-                /// Decrement the value by 1
-                fn dec(&self) {
-                    self.fetch_sub(1 as _, Ordering::SeqCst);
+                /// Increment the value by `inc`, saturating at the type's bounds instead of
+                /// overflowing. Returns the updated value
+                fn saturating_inc_by(&self, inc: Self::Type) -> Self::Type {
+                    let saturating_add: fn(Self::Type, Self::Type) -> Self::Type = $sat;
+
+                    // The closure always returns `Some`, so the update can't fail
+                    let previous = self
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                            Some(saturating_add(current, inc))
+                        })
+                        .unwrap_or_else(|current| current);
+
+                    saturating_add(previous, inc)
                 }
 
-                /// Decrement the value by `dec`
-                fn dec_by(&self, dec: Self::Type) {
-                    self.fetch_sub(dec, Ordering::SeqCst);
+                /// Decrement the value by `dec`, saturating at the type's bounds instead of
+                /// underflowing. Unsigned values stop at zero. Returns the updated value
+                fn saturating_dec_by(&self, dec: Self::Type) -> Self::Type {
+                    let saturating_sub: fn(Self::Type, Self::Type) -> Self::Type = $sat_sub;
+
+                    // The closure always returns `Some`, so the update can't fail
+                    let previous = self
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                            Some(saturating_sub(current, dec))
+                        })
+                        .unwrap_or_else(|current| current);
+
+                    saturating_sub(previous, dec)
+                }
+
+                /// Decrement the value by 1, returning the updated value
+                fn dec(&self) -> Self::Type {
+                    self.dec_by(1 as _)
+                }
+
+                /// Decrement the value by `dec`, returning the updated value
+                fn dec_by(&self, dec: Self::Type) -> Self::Type {
+                    let subtracted: fn(Self::Type, Self::Type) -> Self::Type = $subtracted;
+                    subtracted(self.fetch_sub(dec, Ordering::SeqCst), dec)
                 }
 
                 /// Set the value to `val`
@@ -129,6 +424,34 @@ macro_rules! impl_atomic {
                     self.store(val, Ordering::SeqCst);
                 }
 
+                /// Set the value to `val` if it's greater than the current value, returning the
+                /// updated value
+                fn update_max(&self, val: Self::Type) -> Self::Type {
+                    let updated = self.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                        if val > current {
+                            Some(val)
+                        } else {
+                            None
+                        }
+                    });
+
+                    updated.map_or_else(|current| current, |_| val)
+                }
+
+                /// Set the value to `val` if it's less than the current value, returning the
+                /// updated value
+                fn update_min(&self, val: Self::Type) -> Self::Type {
+                    let updated = self.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                        if val < current {
+                            Some(val)
+                        } else {
+                            None
+                        }
+                    });
+
+                    updated.map_or_else(|current| current, |_| val)
+                }
+
                 /// Get the current value
                 fn get(&self) -> Self::Type {
                     self.load(Ordering::SeqCst)
@@ -139,6 +462,11 @@ macro_rules! impl_atomic {
                     self.store(0 as _, Ordering::SeqCst);
                 }
 
+                /// Reset the value to 0, returning the previous value
+                fn take(&self) -> Self::Type {
+                    self.swap(0 as _, Ordering::SeqCst)
+                }
+
                 fn format(int: Self::Type, f: &mut String, quotes: bool) -> fmt::Result {
                     let fmt: fn(&mut String, Self::Type, bool) -> fmt::Result = $fmt;
                     fmt(f, int, quotes)
@@ -156,6 +484,13 @@ impl_atomic! {
         } else {
             write!(f, "{:?}", int)
         }
+    }; added = u64::wrapping_add; subtracted = u64::wrapping_sub; saturating = u64::saturating_add; saturating_sub = u64::saturating_sub; saturating_mul = u64::saturating_mul; upper_bound = u64::MAX; from_f64 = |float| {
+        // `MAX as f64` can round up to the next power of two, which is out of range
+        if float >= u64::MIN as f64 && float < u64::MAX as f64 + 1.0 {
+            Some(float as u64)
+        } else {
+            None
+        }
     },
 
     AtomicI64 := AtomicI64::new(0) => i64 = |f, int, quotes| {
@@ -164,25 +499,102 @@ impl_atomic! {
         } else {
             write!(f, "{:?}", int)
         }
+    }; added = i64::wrapping_add; subtracted = i64::wrapping_sub; saturating = i64::saturating_add; saturating_sub = i64::saturating_sub; saturating_mul = i64::saturating_mul; upper_bound = i64::MAX; from_f64 = |float| {
+        if float >= i64::MIN as f64 && float < i64::MAX as f64 + 1.0 {
+            Some(float as i64)
+        } else {
+            None
+        }
+    },
+
+    AtomicU32 := AtomicU32::new(0) => u32 = |f, int, quotes| {
+        if quotes {
+            write!(f, "\"{:?}\"", int)
+        } else {
+            write!(f, "{:?}", int)
+        }
+    }; added = u32::wrapping_add; subtracted = u32::wrapping_sub; saturating = u32::saturating_add; saturating_sub = u32::saturating_sub; saturating_mul = u32::saturating_mul; upper_bound = u32::MAX; from_f64 = |float| {
+        if float >= u32::MIN as f64 && float < u32::MAX as f64 + 1.0 {
+            Some(float as u32)
+        } else {
+            None
+        }
+    },
+
+    AtomicI32 := AtomicI32::new(0) => i32 = |f, int, quotes| {
+        if quotes {
+            write!(f, "\"{:?}\"", int)
+        } else {
+            write!(f, "{:?}", int)
+        }
+    }; added = i32::wrapping_add; subtracted = i32::wrapping_sub; saturating = i32::saturating_add; saturating_sub = i32::saturating_sub; saturating_mul = i32::saturating_mul; upper_bound = i32::MAX; from_f64 = |float| {
+        if float >= i32::MIN as f64 && float < i32::MAX as f64 + 1.0 {
+            Some(float as i32)
+        } else {
+            None
+        }
     },
 
     AtomicF64 := AtomicF64::zeroed() => f64 = |f, int, quotes| {
+        // `-0.0` is numerically equal to `0.0`, so both are written as `0.0` to keep consumers
+        // from treating them as distinct values (like two separate `le` buckets)
+        let int = if int == 0.0 { 0.0 } else { int };
+
         if quotes {
             match int {
                 int if int.is_infinite() && int.is_sign_positive() => write!(f, "\"+Inf\""),
                 int if int.is_infinite() && int.is_sign_negative() => write!(f, "\"-Inf\""),
-                int if int.is_nan()  => write!(f, "\"Nan\""),
+                int if int.is_nan()  => write!(f, "\"NaN\""),
                 int => write!(f, "\"{:?}\"", int),
             }
         } else {
             match int {
                 int if int.is_infinite() && int.is_sign_positive() => write!(f, "+Inf"),
                 int if int.is_infinite() && int.is_sign_negative() => write!(f, "-Inf"),
-                int if int.is_nan()  => write!(f, "Nan"),
+                int if int.is_nan()  => write!(f, "NaN"),
                 int => write!(f, "{:?}", int),
             }
         }
-    },
+    }; added = |updated, _| updated; subtracted = |updated, _| updated; saturating = |current, inc| {
+        let sum = current + inc;
+
+        // Only clamp when finite values overflowed, explicit infinities are left untouched
+        if sum.is_infinite() && current.is_finite() && inc.is_finite() {
+            if sum.is_sign_positive() {
+                f64::MAX
+            } else {
+                f64::MIN
+            }
+        } else {
+            sum
+        }
+    }; saturating_sub = |current, dec| {
+        let difference = current - dec;
+
+        // Only clamp when finite values overflowed, explicit infinities are left untouched
+        if difference.is_infinite() && current.is_finite() && dec.is_finite() {
+            if difference.is_sign_positive() {
+                f64::MAX
+            } else {
+                f64::MIN
+            }
+        } else {
+            difference
+        }
+    }; saturating_mul = |current, rhs| {
+        let product = current * rhs;
+
+        // Only clamp when finite values overflowed, explicit infinities are left untouched
+        if product.is_infinite() && current.is_finite() && rhs.is_finite() {
+            if product.is_sign_positive() {
+                f64::MAX
+            } else {
+                f64::MIN
+            }
+        } else {
+            product
+        }
+    }; upper_bound = f64::INFINITY; from_f64 = |float| if float.is_nan() { None } else { Some(float) },
 }
 
 #[cfg(test)]
@@ -243,4 +655,143 @@ mod tests {
         FLOAT.store(-1000.034512, Ordering::SeqCst);
         assert_eq!(FLOAT.load(Ordering::SeqCst), -1000.034512);
     }
+
+    #[test]
+    fn updates_return_updated_value() {
+        let int = AtomicU64::new(0);
+        assert_eq!(AtomicNum::inc(&int), 1);
+        assert_eq!(AtomicNum::inc_by(&int, 4), 5);
+        assert_eq!(AtomicNum::dec_by(&int, 2), 3);
+        assert_eq!(AtomicNum::dec(&int), 2);
+        assert_eq!(int.saturating_dec_by(5), 0);
+        assert_eq!(int.saturating_inc_by(u64::MAX), u64::MAX);
+        assert_eq!(int.update_max(5), u64::MAX);
+        assert_eq!(int.update_min(5), 5);
+
+        let float = AtomicF64::zeroed();
+        assert_eq!(AtomicNum::inc_by(&float, 1.5), 1.5);
+        assert_eq!(AtomicNum::dec_by(&float, 0.5), 1.0);
+        assert_eq!(float.update_max(0.5), 1.0);
+        assert_eq!(float.update_min(0.5), 0.5);
+    }
+
+    #[test]
+    fn store_load_round_trip() {
+        let float = AtomicF64::zeroed();
+
+        for &val in &[1.5, -0.0, f64::MAX, f64::MIN_POSITIVE, f64::INFINITY] {
+            float.store(val, Ordering::Release);
+            assert_eq!(float.load(Ordering::Acquire).to_bits(), val.to_bits());
+        }
+    }
+
+    #[test]
+    fn swapping() {
+        let float = AtomicF64::zeroed();
+
+        assert_eq!(float.swap(10.5, Ordering::SeqCst), 0.0);
+        assert_eq!(float.swap(-3.25, Ordering::SeqCst), 10.5);
+        assert_eq!(float.load(Ordering::SeqCst), -3.25);
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn threaded_stores() {
+        use std::{sync::Arc, thread};
+
+        let float = Arc::new(AtomicF64::zeroed());
+        let threads: Vec<_> = (1..=8)
+            .map(|i| {
+                let float = float.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        float.store(i as f64, Ordering::Release);
+                        float.fetch_add(0.0, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Stores are never torn, so the value is always exactly one of the stored values
+        let value = float.load(Ordering::Acquire);
+        assert!((1..=8).any(|i| i as f64 == value));
+    }
+
+    #[test]
+    fn compare_exchanging() {
+        let float = AtomicF64::from_bits(f64::to_bits(1.0));
+
+        assert_eq!(
+            float.compare_exchange(1.0, 2.0, Ordering::SeqCst, Ordering::SeqCst),
+            Ok(1.0)
+        );
+        assert_eq!(float.load(Ordering::SeqCst), 2.0);
+
+        assert_eq!(
+            float.compare_exchange(1.0, 3.0, Ordering::SeqCst, Ordering::SeqCst),
+            Err(2.0)
+        );
+        assert_eq!(float.load(Ordering::SeqCst), 2.0);
+    }
+
+    #[test]
+    fn compare_exchange_zero_signs() {
+        let float = AtomicF64::zeroed();
+
+        // `0.0 == -0.0`, but their bits differ so the exchange fails
+        let err = float
+            .compare_exchange(-0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst)
+            .unwrap_err();
+        assert!(err.is_sign_positive());
+        assert_eq!(float.load(Ordering::SeqCst), 0.0);
+
+        float.store(-0.0, Ordering::SeqCst);
+        assert!(float
+            .compare_exchange(0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err());
+        assert_eq!(
+            float
+                .compare_exchange(-0.0, 1.0, Ordering::SeqCst, Ordering::SeqCst)
+                .map(f64::to_bits),
+            Ok(f64::to_bits(-0.0))
+        );
+        assert_eq!(float.load(Ordering::SeqCst), 1.0);
+    }
+
+    #[test]
+    fn special_float_tokens() {
+        let format = |float, quotes| {
+            let mut buf = String::new();
+            <AtomicF64 as AtomicNum>::format(float, &mut buf, quotes).unwrap();
+            buf
+        };
+
+        assert_eq!(format(f64::NAN, false), "NaN");
+        assert_eq!(format(f64::NAN, true), "\"NaN\"");
+        assert_eq!(format(f64::INFINITY, false), "+Inf");
+        assert_eq!(format(f64::INFINITY, true), "\"+Inf\"");
+        assert_eq!(format(f64::NEG_INFINITY, false), "-Inf");
+        assert_eq!(format(f64::NEG_INFINITY, true), "\"-Inf\"");
+        assert_eq!(format(-0.0, false), "0.0");
+        assert_eq!(format(-0.0, true), "\"0.0\"");
+    }
+
+    #[test]
+    fn stale_marker() {
+        static FLOAT: AtomicF64 = AtomicF64::zeroed();
+
+        FLOAT.store(AtomicF64::STALE_MARKER, Ordering::SeqCst);
+        let loaded = FLOAT.load(Ordering::SeqCst);
+        assert_eq!(loaded.to_bits(), 0x7ff0_0000_0000_0002);
+        assert!(AtomicF64::is_stale_marker(loaded));
+        assert!(!AtomicF64::is_stale_marker(f64::NAN));
+
+        let mut buf = String::new();
+        AtomicF64::format(loaded, &mut buf, false).unwrap();
+        assert_eq!(buf, "NaN");
+    }
 }
@@ -0,0 +1,316 @@
+use crate::{
+    atomics::{FormatOptions, MetricValue},
+    error::Result,
+    label::{write_labels, Label},
+    registry::{estimate_text_size, Collectable, Descriptor},
+};
+use std::{borrow::Cow, fmt, fmt::Write, sync::Arc};
+#[cfg(feature = "callback-timeout")]
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// A gauge whose value is read from a closure at scrape time
+///
+/// Useful for pull-based values like free disk space or the length of a queue, where
+/// eagerly updating an atomic would be wasteful
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{CallbackGauge, RegistryBuilder};
+///
+/// let gauge = CallbackGauge::new("answer", "The answer to everything", || 42.0).unwrap();
+/// assert_eq!(gauge.get(), 42.0);
+///
+/// let registry = RegistryBuilder::new().register(Box::new(gauge)).build().unwrap();
+/// assert!(registry.collect_to_string().unwrap().contains("answer 42.0"));
+/// ```
+pub struct CallbackGauge {
+    callback: Arc<dyn Fn() -> f64 + Send + Sync>,
+    descriptor: Descriptor,
+    #[cfg(feature = "callback-timeout")]
+    timeout: Option<Timeout>,
+}
+
+impl CallbackGauge {
+    /// Create a new `CallbackGauge` that calls `callback` every time it's collected
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the given name doesn't follow the [prometheus metric name specification]
+    ///
+    /// [`PromError`]: crate::PromError
+    /// [prometheus metric name specification]: https://prometheus.io/docs/concepts/data_model/#metric-names-and-labels
+    pub fn new<F>(
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        callback: F,
+    ) -> Result<Self>
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        Ok(Self {
+            callback: Arc::new(callback),
+            descriptor: Descriptor::new(name, help, Vec::new())?,
+            #[cfg(feature = "callback-timeout")]
+            timeout: None,
+        })
+    }
+
+    /// Give up on the callback if it takes longer than `timeout`, reporting the last value it
+    /// returned instead (or `NaN` if it never returned in time). Every timeout is counted in
+    /// the registry's `collector_scrape_errors_total`
+    ///
+    /// The callback is run on its own thread, which keeps running after a timeout. While it's
+    /// still running later scrapes report the stale value instead of spawning more threads
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::CallbackGauge;
+    /// use std::{thread, time::Duration};
+    ///
+    /// let gauge = CallbackGauge::new("slow_query", "A slow database query", || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     1.0
+    /// })
+    /// .unwrap()
+    /// .with_timeout(Duration::from_millis(10));
+    ///
+    /// assert!(gauge.get().is_nan());
+    /// assert_eq!(gauge.timeouts(), 1);
+    /// ```
+    #[cfg(feature = "callback-timeout")]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout::new(timeout));
+        self
+    }
+
+    /// The number of times the callback didn't return within its timeout
+    #[cfg(feature = "callback-timeout")]
+    pub fn timeouts(&self) -> u64 {
+        self.timeout
+            .as_ref()
+            .map_or(0, |timeout| timeout.timeouts.load(Ordering::Relaxed))
+    }
+
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Self {
+        self.descriptor.labels = labels.into();
+        self
+    }
+
+    /// Get the current value of the gauge by calling its callback
+    pub fn get(&self) -> f64 {
+        #[cfg(feature = "callback-timeout")]
+        {
+            if let Some(timeout) = self.timeout.as_ref() {
+                return timeout.call(&self.callback);
+            }
+        }
+
+        (self.callback)()
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
+}
+
+impl Collectable for CallbackGauge {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write!(buf, "{}", self.name())?;
+        if !self.labels().is_empty() {
+            write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
+        }
+        write!(buf, " ")?;
+
+        options.write_value(buf, self.get())?;
+        writeln!(buf)?;
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), self.get().into());
+    }
+
+    #[cfg(feature = "callback-timeout")]
+    fn scrape_errors(&self) -> u64 {
+        self.timeouts()
+    }
+
+    /// Estimated without calling the callback, which may be expensive
+    fn text_size_hint(&self) -> usize {
+        estimate_text_size(&self.descriptor, self.metric_type(), |_| {})
+    }
+}
+
+/// The state used to run a [`CallbackGauge`]'s callback with a timeout
+///
+/// [`CallbackGauge`]: crate::CallbackGauge
+#[cfg(feature = "callback-timeout")]
+#[derive(Debug)]
+struct Timeout {
+    duration: Duration,
+    /// The bits of the last value returned within the timeout
+    last_value: Arc<AtomicU64>,
+    /// Set while a callback thread is running
+    running: Arc<AtomicBool>,
+    timeouts: AtomicU64,
+}
+
+#[cfg(feature = "callback-timeout")]
+impl Timeout {
+    fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            last_value: Arc::new(AtomicU64::new(f64::NAN.to_bits())),
+            running: Arc::new(AtomicBool::new(false)),
+            timeouts: AtomicU64::new(0),
+        }
+    }
+
+    fn call(&self, callback: &Arc<dyn Fn() -> f64 + Send + Sync>) -> f64 {
+        let stale = || f64::from_bits(self.last_value.load(Ordering::Acquire));
+
+        // A previous call is still stuck, don't pile more threads onto it
+        if self.running.swap(true, Ordering::AcqRel) {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+            return stale();
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let (callback, last_value, running) = (
+            Arc::clone(callback),
+            Arc::clone(&self.last_value),
+            Arc::clone(&self.running),
+        );
+
+        thread::spawn(move || {
+            let value = callback();
+            last_value.store(value.to_bits(), Ordering::Release);
+            running.store(false, Ordering::Release);
+
+            // The receiver is gone if the call already timed out
+            let _ = sender.send(value);
+        });
+
+        match receiver.recv_timeout(self.duration) {
+            Ok(value) => value,
+            Err(_) => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                stale()
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CallbackGauge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackGauge")
+            .field("descriptor", &self.descriptor)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::RegistryBuilder;
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn read_at_scrape_time() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let gauge = {
+            let calls = calls.clone();
+            CallbackGauge::new("callback_gauge", "Counts its own scrapes", move || {
+                calls.fetch_add(1, Ordering::SeqCst) as f64
+            })
+            .unwrap()
+            .with_labels(vec![Label::new("source", "closure").unwrap()])
+        };
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(gauge))
+            .build()
+            .unwrap();
+
+        let first = registry.collect_to_string().unwrap();
+        let second = registry.collect_to_string().unwrap();
+
+        assert_ne!(first, second);
+        assert!(first.contains("# TYPE callback_gauge gauge\n"));
+        assert!(first.contains("callback_gauge{source=\"closure\"} 0.0\n"));
+        assert!(second.contains("callback_gauge{source=\"closure\"} 1.0\n"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "callback-timeout")]
+    fn slow_callback_times_out() {
+        use std::{thread, time::Instant};
+
+        let slow = CallbackGauge::new("slow_gauge", "Takes its time", || {
+            thread::sleep(Duration::from_millis(500));
+            1.0
+        })
+        .unwrap()
+        .with_timeout(Duration::from_millis(20));
+        let fast = CallbackGauge::new("fast_gauge", "Returns right away", || 2.0)
+            .unwrap()
+            .with_timeout(Duration::from_secs(10));
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(slow))
+            .register(Box::new(fast))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let first = registry.collect_to_string().unwrap();
+        let second = registry.collect_to_string().unwrap();
+        assert!(start.elapsed() < Duration::from_millis(400));
+
+        assert!(first.contains("slow_gauge NaN\n"), "{}", first);
+        assert!(first.contains("fast_gauge 2.0\n"), "{}", first);
+        assert!(first.contains("# TYPE collector_scrape_errors_total counter\n"));
+        assert!(first.contains("collector_scrape_errors_total{collector=\"slow_gauge\"} 1\n"));
+        assert!(!first.contains("collector=\"fast_gauge\""));
+
+        // The stuck thread is reused instead of spawning another one
+        assert!(second.contains("collector_scrape_errors_total{collector=\"slow_gauge\"} 2\n"));
+
+        // Once the slow callback finishes its value is reported as the stale value
+        thread::sleep(Duration::from_millis(600));
+        let third = registry.collect_to_string().unwrap();
+        assert!(third.contains("slow_gauge 1.0\n"), "{}", third);
+    }
+}
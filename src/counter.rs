@@ -30,15 +30,23 @@
 //! [`IntCounter`]: crate::counter::IntCounter
 
 use crate::{
-    atomics::{AtomicF64, AtomicNum},
-    error::Result,
-    label::Label,
+    atomics::{AtomicF64, AtomicNum, FormatOptions, MetricValue, Num},
+    encoder::counter_family,
+    error::{PromError, PromErrorKind, Result},
+    label::{write_labels, DebugLabels, Label},
     registry::{Collectable, Descriptor},
 };
 use std::{
     borrow::Cow,
-    fmt::Write,
-    sync::atomic::{AtomicI64, AtomicU64},
+    cell::Cell,
+    collections::VecDeque,
+    fmt::{self, Write},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant, SystemTime},
 };
 
 /// A [`Counter`] that stores a `u64`, see [`Counter`] for more information
@@ -92,6 +100,122 @@ pub type IntCounter = Counter<AtomicI64>;
 /// [`Counter`]: crate::Counter
 pub type FloatCounter = Counter<AtomicF64>;
 
+/// A builder for [`Counter`]s, validation of the name and labels is deferred until [`build`] is called
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{counter::CounterBuilder, Counter, Label};
+/// use std::sync::atomic::AtomicU64;
+///
+/// let counter: Counter<AtomicU64> = CounterBuilder::new()
+///     .name("requests")
+///     .help("Total requests")
+///     .label(Label::new("method", "GET").unwrap())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(counter.name(), "requests");
+/// ```
+///
+/// [`Counter`]: crate::Counter
+/// [`build`]: crate::counter::CounterBuilder#method.build
+#[derive(Debug)]
+pub struct CounterBuilder<Atomic: AtomicNum = AtomicU64> {
+    name: Option<Cow<'static, str>>,
+    help: Option<Cow<'static, str>>,
+    labels: Option<Vec<Label>>,
+    unit: Option<Cow<'static, str>>,
+    atomic: PhantomData<fn() -> Atomic>,
+}
+
+impl<Atomic: AtomicNum> CounterBuilder<Atomic> {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            help: None,
+            labels: None,
+            unit: None,
+            atomic: PhantomData,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn help(mut self, help: impl Into<Cow<'static, str>>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Self {
+        self.labels = Some(labels.into());
+        self
+    }
+
+    pub fn label(mut self, label: Label) -> Self {
+        if let Some(ref mut labels) = self.labels {
+            labels.push(label);
+        } else {
+            self.labels = Some(vec![label]);
+        }
+
+        self
+    }
+
+    /// Set the counter's OpenMetrics unit, see [`Counter::with_unit`]
+    ///
+    /// [`Counter::with_unit`]: crate::Counter#method.with_unit
+    pub fn unit(mut self, unit: impl Into<Cow<'static, str>>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Build the counter
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if no name or help were given or if the name doesn't follow the
+    /// [prometheus metric name specification]
+    ///
+    /// [`PromError`]: crate::PromError
+    /// [prometheus metric name specification]: https://prometheus.io/docs/concepts/data_model/#metric-names-and-labels
+    pub fn build(self) -> Result<Counter<Atomic>> {
+        let name = self.name.ok_or_else(|| {
+            PromError::new(
+                "Counters must have a name, but you didn't give one",
+                PromErrorKind::MissingName,
+            )
+        })?;
+        let help = self.help.ok_or_else(|| {
+            PromError::new(
+                "Counters must have a help, but you didn't give one",
+                PromErrorKind::MissingHelp,
+            )
+        })?;
+
+        let mut descriptor = Descriptor::new(name, help, self.labels.unwrap_or_default())?;
+        if let Some(unit) = self.unit {
+            descriptor.set_unit(unit, true)?;
+        }
+
+        Ok(Counter {
+            value: Atomic::new(),
+            descriptor,
+            last_reset: AtomicU64::new(0),
+            created: SystemTime::now(),
+        })
+    }
+}
+
+impl<Atomic: AtomicNum> Default for CounterBuilder<Atomic> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// A monotonically increasing counter. When in doubt of what type to choose, default to [`std::sync::atomic::AtomicU64`].
 ///
 /// Multiple continence types are provided, [`UintCounter`], [`FloatCounter`] and [`IntCounter`]
@@ -132,12 +256,25 @@ pub type FloatCounter = Counter<AtomicF64>;
 /// [`IntCounter`]: crate::counter::IntCounter
 /// [docs]: https://prometheus.io/docs/concepts/metric_types/#counter
 /// [gauge]: crate::Gauge
-#[derive(Debug)]
 pub struct Counter<Atomic: AtomicNum = AtomicU64> {
     /// The inner atomically manipulated value
     value: Atomic,
     /// The prometheus description data, like the counter name, help and labels
     descriptor: Descriptor,
+    /// Nanoseconds since the unix epoch of when the counter was last reset, zero if it never was
+    last_reset: AtomicU64,
+    /// When the counter was created, written as its OpenMetrics `_created` sample until it's reset
+    created: SystemTime,
+}
+
+impl<Atomic: AtomicNum> fmt::Debug for Counter<Atomic> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Counter")
+            .field("name", &self.name())
+            .field("labels", &DebugLabels(self.labels()))
+            .field("value", &self.get())
+            .finish()
+    }
 }
 
 impl<Atomic: AtomicNum> Counter<Atomic> {
@@ -173,9 +310,58 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
         Ok(Self {
             value: Atomic::new(),
             descriptor: Descriptor::new(name, help, Vec::new())?,
+            last_reset: AtomicU64::new(0),
+            created: SystemTime::now(),
+        })
+    }
+
+    /// Create a counter that continues from a value and created timestamp persisted by a
+    /// previous run, so that Prometheus doesn't see the restart as a counter reset. The
+    /// [`OpenMetricsEncoder`] writes `created_at` as the counter's `_created` sample
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Counter;
+    /// use std::{sync::atomic::AtomicU64, time::{Duration, SystemTime}};
+    ///
+    /// let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    /// let counter: Counter<AtomicU64> =
+    ///     Counter::restore("requests", "Total requests", 1024, created_at).unwrap();
+    ///
+    /// assert_eq!(counter.get(), 1024);
+    /// assert_eq!(counter.created(), created_at);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] under the same conditions as [`Counter::new`]
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    /// [`PromError`]: crate::PromError
+    /// [`Counter::new`]: crate::Counter#method.new
+    pub fn restore(
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        value: Atomic::Type,
+        created_at: SystemTime,
+    ) -> Result<Self> {
+        let counter = Self::new(name, help)?;
+        counter.value.set(value);
+
+        Ok(Self {
+            created: created_at,
+            ..counter
         })
     }
 
+    /// When the counter was created, or the time it was restored with from [`Counter::restore`]
+    ///
+    /// [`Counter::restore`]: crate::Counter#method.restore
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
     /// Set the labels of the current counter
     ///
     /// # Examples
@@ -195,6 +381,47 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
         self
     }
 
+    /// Set the counter's unit, which the [`OpenMetricsEncoder`] emits as a `# UNIT` line
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Counter;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let counter: Counter<AtomicU64> = Counter::new("sent_bytes", "Bytes sent")
+    ///     .unwrap()
+    ///     .with_unit("bytes")
+    ///     .unwrap();
+    ///
+    /// // OpenMetrics checks the unit against the name without its `_total` suffix
+    /// let total: Counter<AtomicU64> = Counter::new("received_bytes_total", "Bytes received")
+    ///     .unwrap()
+    ///     .with_unit("bytes")
+    ///     .unwrap();
+    ///
+    /// assert!(Counter::<AtomicU64>::new("sent", "Bytes sent").unwrap().with_unit("bytes").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the unit is invalid or the counter's name doesn't end with
+    /// `_{unit}` or `_{unit}_total`
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    /// [`PromError`]: crate::PromError
+    pub fn with_unit(mut self, unit: impl Into<Cow<'static, str>>) -> Result<Self> {
+        self.descriptor.set_unit(unit.into(), true)?;
+        Ok(self)
+    }
+
+    /// Write the counter's help as-is instead of escaping backslashes and newlines, for help text
+    /// that's already escaped. The caller is responsible for the help being valid in the exposition format
+    pub fn with_raw_help(mut self) -> Self {
+        self.descriptor.raw_help = true;
+        self
+    }
+
     /// Increment the current counter by 1
     ///
     /// # Examples
@@ -227,6 +454,47 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
         self.value.inc_by(inc);
     }
 
+    /// Increment the current counter by a `u64`, converting it to the counter's type so that
+    /// integer counts can be added to any kind of counter without casting. Counts that don't fit
+    /// in a smaller integer type saturate at the type's maximum value instead of being truncated
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{counter::FloatCounter, Counter};
+    /// use std::sync::atomic::AtomicU32;
+    ///
+    /// let sent = FloatCounter::new("sent_bytes", "Bytes sent").unwrap();
+    /// sent.inc_by_u64(1024);
+    /// assert_eq!(sent.get(), 1024.0);
+    ///
+    /// // `u32::MAX + 1` would be truncated to zero by an `as` cast
+    /// let small: Counter<AtomicU32> = Counter::new("small_count", "A small counter").unwrap();
+    /// small.inc_by_u64(u64::from(u32::MAX) + 1);
+    /// assert_eq!(small.get(), u32::MAX);
+    /// ```
+    pub fn inc_by_u64(&self, inc: u64) {
+        self.inc_by(Atomic::Type::from_u64(inc));
+    }
+
+    /// Increment the current counter by `inc`, saturating at the type's maximum value instead
+    /// of wrapping around. For float counters the value saturates at `f64::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Counter;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let counter: Counter<AtomicU64> = Counter::new("count_dracula", "I am Count von Count!").unwrap();
+    /// counter.set(u64::MAX - 1);
+    /// counter.saturating_inc_by(100);
+    /// assert_eq!(counter.get(), u64::MAX);
+    /// ```
+    pub fn saturating_inc_by(&self, inc: Atomic::Type) {
+        self.value.saturating_inc_by(inc);
+    }
+
     /// Get the value of the current counter
     ///
     /// # Examples
@@ -258,7 +526,54 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
     /// assert_eq!(counter.get(), 0);
     /// ```
     pub fn clear(&self) {
-        self.value.clear()
+        self.value.clear();
+        self.record_reset();
+    }
+
+    /// Reset the current counter's value to 0, returning the value it had before the reset
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Counter;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let counter: Counter<AtomicU64> = Counter::new("count_dracula", "I am Count von Count!").unwrap();
+    /// counter.inc_by(100);
+    /// assert_eq!(counter.fetch_and_reset(), 100);
+    /// assert_eq!(counter.get(), 0);
+    /// ```
+    pub fn fetch_and_reset(&self) -> Atomic::Type {
+        let value = self.value.take();
+        self.record_reset();
+
+        value
+    }
+
+    /// Get when the counter was last reset by [`Counter::clear`] or [`Counter::fetch_and_reset`],
+    /// `None` if it never was. Useful for correlating `rate()` anomalies with resets, the
+    /// timestamp isn't exported
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Counter;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let counter: Counter<AtomicU64> = Counter::new("count_dracula", "I am Count von Count!").unwrap();
+    /// assert!(counter.last_reset().is_none());
+    ///
+    /// counter.clear();
+    /// assert!(counter.last_reset().is_some());
+    /// ```
+    ///
+    /// [`Counter::clear`]: crate::Counter#method.clear
+    /// [`Counter::fetch_and_reset`]: crate::Counter#method.fetch_and_reset
+    pub fn last_reset(&self) -> Option<SystemTime> {
+        match self.last_reset.load(Ordering::SeqCst) {
+            0 => None,
+            nanos => Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos)),
+        }
     }
 
     /// Set the current counter's value to `val`
@@ -289,7 +604,7 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
     /// assert_eq!(counter.name(), "count_dracula");
     /// ```
     pub fn name(&self) -> &str {
-        &self.descriptor.name()
+        self.descriptor.name()
     }
 
     /// Get the current counter's help
@@ -304,7 +619,7 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
     /// assert_eq!(counter.help(), "I am Count von Count!");
     /// ```
     pub fn help(&self) -> &str {
-        &self.descriptor.help()
+        self.descriptor.help()
     }
 
     /// Get the labels of the current counter
@@ -322,49 +637,279 @@ impl<Atomic: AtomicNum> Counter<Atomic> {
     /// assert_eq!(counter.labels(), &[Label::new("your_label", "The label's value").unwrap()]);
     /// ```
     pub fn labels(&self) -> &[Label] {
-        &self.descriptor.labels()
+        self.descriptor.labels()
+    }
+
+    fn record_reset(&self) {
+        let nanos = SystemTime::UNIX_EPOCH
+            .elapsed()
+            .expect("Impossible to fail, `UNIX_EPOCH` will never be sooner than the current system time")
+            .as_nanos();
+
+        // Zero is reserved for counters that were never reset
+        self.last_reset
+            .store((nanos as u64).max(1), Ordering::SeqCst);
+    }
+
+    /// Create a [`LocalCounter`] that counts without atomics and adds its count to this
+    /// counter when flushed
+    ///
+    /// [`LocalCounter`]: crate::counter::LocalCounter
+    pub fn local(&self) -> LocalCounter<'_, Atomic> {
+        LocalCounter::new(self)
+    }
+}
+
+/// A single-threaded counter backed by a [`Cell`] instead of an atomic, its count is added to
+/// the [`Counter`] it was created from when it's flushed
+///
+/// `LocalCounter` isn't `Sync`, so it can't be shared between threads
+///
+/// ```rust,compile_fail
+/// use prometheus_rs::Counter;
+/// use std::sync::atomic::AtomicU64;
+///
+/// fn assert_sync<T: Sync>(_: T) {}
+///
+/// let counter: Counter<AtomicU64> = Counter::new("requests", "Total requests").unwrap();
+/// assert_sync(counter.local());
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::Counter;
+/// use std::sync::atomic::AtomicU64;
+///
+/// let counter: Counter<AtomicU64> = Counter::new("requests", "Total requests").unwrap();
+///
+/// let local = counter.local();
+/// local.inc();
+/// local.inc_by(9);
+/// assert_eq!(local.get(), 10);
+/// assert_eq!(counter.get(), 0);
+///
+/// local.flush();
+/// assert_eq!(counter.get(), 10);
+/// ```
+///
+/// [`Cell`]: std::cell::Cell
+/// [`Counter`]: crate::Counter
+#[derive(Debug)]
+pub struct LocalCounter<'a, Atomic: AtomicNum> {
+    counter: &'a Counter<Atomic>,
+    value: Cell<Atomic::Type>,
+}
+
+impl<'a, Atomic: AtomicNum> LocalCounter<'a, Atomic> {
+    pub(crate) fn new(counter: &'a Counter<Atomic>) -> Self {
+        Self {
+            counter,
+            value: Cell::new(Atomic::Type::default()),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(Atomic::Type::from_u64(1));
+    }
+
+    /// Increment the local count by `inc`, saturating at the type's maximum value instead of
+    /// overflowing
+    pub fn inc_by(&self, inc: Atomic::Type) {
+        self.value.set(self.value.get().saturating_add(inc));
+    }
+
+    /// Get the count that hasn't been flushed yet
+    pub fn get(&self) -> Atomic::Type {
+        self.value.get()
+    }
+
+    /// Discard the count that hasn't been flushed yet
+    pub fn clear(&self) {
+        self.value.set(Atomic::Type::default());
+    }
+
+    /// Add the local count to the shared counter and reset the local count. The shared counter
+    /// saturates like the local count does
+    pub fn flush(&self) {
+        let value = self.value.replace(Atomic::Type::default());
+        if value != Atomic::Type::default() {
+            self.counter.saturating_inc_by(value);
+        }
+    }
+}
+
+/// A [`Counter`] that also keeps its recent increments, so that it can report its own
+/// per-second rate over a trailing window for decisions made in-process, like local alerting.
+/// The rate isn't exported, only the wrapped counter is
+///
+/// Increments are grouped into slots of a sixteenth of the window so that the buffer stays
+/// small no matter how often the counter is incremented. The rate is the sum of the increments
+/// within the window divided by the whole window, so it reads low until the counter has been
+/// running for a full window
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{counter::RatedCounter, Counter};
+/// use std::time::Duration;
+///
+/// let requests: RatedCounter = RatedCounter::new(
+///     Counter::new("requests", "Total requests").unwrap(),
+///     Duration::from_secs(10),
+/// );
+/// requests.inc_by(50);
+///
+/// assert_eq!(requests.get(), 50);
+/// assert_eq!(requests.rate(), 5.0);
+/// ```
+///
+/// [`Counter`]: crate::Counter
+#[derive(Debug)]
+pub struct RatedCounter<Atomic: AtomicNum = AtomicU64> {
+    counter: Counter<Atomic>,
+    increments: Mutex<VecDeque<(Instant, f64)>>,
+    window: Duration,
+}
+
+impl<Atomic: AtomicNum> RatedCounter<Atomic> {
+    pub fn new(counter: Counter<Atomic>, window: Duration) -> Self {
+        Self {
+            counter,
+            increments: Mutex::new(VecDeque::new()),
+            window,
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(Atomic::Type::from_u64(1));
+    }
+
+    pub fn inc_by(&self, inc: Atomic::Type) {
+        self.inc_by_at(inc, Instant::now());
+    }
+
+    pub fn get(&self) -> Atomic::Type {
+        self.counter.get()
+    }
+
+    /// Get the number of increments per second within the window
+    pub fn rate(&self) -> f64 {
+        self.rate_at(Instant::now())
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Get the wrapped counter, e.g. for registering it
+    pub fn counter(&self) -> &Counter<Atomic> {
+        &self.counter
+    }
+
+    pub(crate) fn inc_by_at(&self, inc: Atomic::Type, now: Instant) {
+        self.counter.inc_by(inc);
+
+        let inc: MetricValue = inc.into();
+        let mut increments = self.evicted(now);
+        match increments.back_mut() {
+            Some((slot, total)) if now.saturating_duration_since(*slot) < self.window / 16 => {
+                *total += inc.as_f64();
+            }
+            _ => increments.push_back((now, inc.as_f64())),
+        }
+    }
+
+    pub(crate) fn rate_at(&self, now: Instant) -> f64 {
+        let window = self.window.as_secs_f64();
+        if window == 0.0 {
+            return 0.0;
+        }
+
+        self.evicted(now).iter().map(|(_, inc)| inc).sum::<f64>() / window
+    }
+
+    /// Locks the increments, removing any that fall outside of the window ending at `now`
+    fn evicted(&self, now: Instant) -> MutexGuard<'_, VecDeque<(Instant, f64)>> {
+        let mut increments = self.increments.lock().unwrap();
+        while let Some((time, _)) = increments.front() {
+            if now.saturating_duration_since(*time) > self.window {
+                increments.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        increments
     }
 }
 
 impl<Atomic: AtomicNum> Collectable for &Counter<Atomic> {
-    /// Encodes a `Counter` into the following format
+    /// Encodes a `Counter`'s sample into the following format
     ///
     /// ```text
-    /// # HELP {{ name }} {{ help }}
-    /// # TYPE {{ name }} counter
     /// {{ name }}{ labels } {{ value }}
     /// ```
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()> {
-        writeln!(buf, "# HELP {} {}", self.name(), self.help())?;
-        writeln!(buf, "# TYPE {} counter", self.name())?;
-
-        write!(buf, "{}", self.name())?;
-        if !self.labels().is_empty() {
-            write!(buf, "{{")?;
-
-            let (last, labels) = self
-                .labels()
-                .split_last()
-                .expect("There is at least 1 label");
-            for label in labels {
-                write!(buf, "{}={:?},", label.name(), label.value())?;
-            }
-            write!(buf, "{}={:?}", last.name(), last.value())?;
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write_sample_name(buf, self.name(), "", self.labels())?;
+        options.write_value(buf, self.get())?;
+        writeln!(buf)?;
 
-            write!(buf, "}} ")?;
-        } else {
-            write!(buf, " ")?;
-        }
+        Ok(())
+    }
+
+    /// Encodes the counter's `<family>_total` sample followed by its `<family>_created` sample,
+    /// the unix timestamp in seconds of when the counter was created or last reset
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        let family = counter_family(self.name());
+
+        write_sample_name(buf, family, "_total", self.labels())?;
+        options.write_value(buf, self.get())?;
+        writeln!(buf)?;
+
+        write_sample_name(buf, family, "_created", self.labels())?;
 
-        Atomic::format(self.get(), buf, false)?;
+        let created = self
+            .last_reset()
+            .unwrap_or(self.created)
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        AtomicF64::format(created.as_secs_f64(), buf, false)?;
         writeln!(buf)?;
 
         Ok(())
     }
 
+    fn metric_type(&self) -> &'static str {
+        "counter"
+    }
+
     fn descriptor(&self) -> &Descriptor {
         &self.descriptor
     }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), self.get().into());
+    }
+}
+
+/// Writes the name of one of a counter's samples followed by its labels and the space before
+/// its value, `{{ name }}{{ suffix }}{ labels } `
+pub(crate) fn write_sample_name(
+    buf: &mut String,
+    name: &str,
+    suffix: &str,
+    labels: &[Label],
+) -> Result<()> {
+    write!(buf, "{}{}", name, suffix)?;
+    if !labels.is_empty() {
+        write!(buf, "{{")?;
+        write_labels(buf, labels, false)?;
+        write!(buf, "}}")?;
+    }
+    write!(buf, " ")?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -375,6 +920,58 @@ mod tests {
     use std::sync::atomic::{AtomicI64, AtomicU64};
     use std::thread;
 
+    #[test]
+    fn last_reset() {
+        let counter: Counter<AtomicU64> = Counter::new("resets", "Counts things").unwrap();
+        assert!(counter.last_reset().is_none());
+
+        let before = SystemTime::now();
+        counter.inc_by(10);
+        counter.clear();
+
+        let cleared = counter.last_reset().unwrap();
+        assert!(cleared >= before - Duration::from_millis(1));
+        assert!(cleared <= SystemTime::now());
+
+        counter.inc_by(5);
+        assert_eq!(counter.fetch_and_reset(), 5);
+        assert_eq!(counter.get(), 0);
+        assert!(counter.last_reset().unwrap() >= cleared);
+
+        // Incrementing or setting the counter doesn't count as a reset
+        let reset = counter.last_reset();
+        counter.inc();
+        counter.set(0);
+        assert_eq!(counter.last_reset(), reset);
+    }
+
+    #[test]
+    fn local_counter() {
+        let counter: Counter<AtomicU64> = Counter::new("local_uint", "Counts things").unwrap();
+
+        let local = counter.local();
+        local.inc();
+        local.inc_by(4);
+        assert_eq!(local.get(), 5);
+        assert_eq!(counter.get(), 0);
+
+        local.flush();
+        assert_eq!(local.get(), 0);
+        assert_eq!(counter.get(), 5);
+
+        local.inc_by(3);
+        local.clear();
+        local.flush();
+        assert_eq!(counter.get(), 5);
+
+        // Overflowing counts saturate instead of panicking
+        local.inc_by(u64::MAX);
+        local.inc();
+        assert_eq!(local.get(), u64::MAX);
+        local.flush();
+        assert_eq!(counter.get(), u64::MAX);
+    }
+
     #[test]
     fn uint_counter() {
         let uint: Counter<AtomicU64> = Counter::new("some_uint", "Counts things").unwrap();
@@ -490,6 +1087,101 @@ mod tests {
         assert_eq!(int.get(), 999);
     }
 
+    #[test]
+    fn builder() {
+        let built: Counter<AtomicU64> = CounterBuilder::new()
+            .name("some_uint")
+            .help("Counts things")
+            .with_labels(vec![Label::new("first", "a").unwrap()])
+            .label(Label::new("second", "b").unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(built.name(), "some_uint");
+        assert_eq!(built.help(), "Counts things");
+        assert_eq!(
+            built.labels(),
+            &[
+                Label::new("first", "a").unwrap(),
+                Label::new("second", "b").unwrap()
+            ]
+        );
+
+        let missing_name = CounterBuilder::<AtomicU64>::new()
+            .help("Counts things")
+            .build()
+            .unwrap_err();
+        assert_eq!(missing_name.kind(), PromErrorKind::MissingName);
+
+        let invalid_name = CounterBuilder::<AtomicF64>::new()
+            .name("not a name")
+            .help("Counts things")
+            .build()
+            .unwrap_err();
+        assert_eq!(invalid_name.kind(), PromErrorKind::InvalidMetricName);
+    }
+
+    #[test]
+    fn u32_counter() {
+        use crate::{registry::RegistryBuilder, MetricValue};
+        use std::sync::atomic::AtomicU32;
+
+        static SMALL: Lazy<Counter<AtomicU32>> = Lazy::new(|| {
+            Counter::new("some_u32", "Counts things")
+                .unwrap()
+                .with_labels(vec![Label::new("width", "32").unwrap()])
+        });
+
+        SMALL.inc_by(u32::MAX - 1);
+        SMALL.saturating_inc_by(10);
+        assert_eq!(SMALL.get(), u32::MAX);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*SMALL))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP some_u32 Counts things\n\
+             # TYPE some_u32 counter\n\
+             some_u32{width=\"32\"} 4294967295\n",
+        );
+        assert_eq!(
+            (&*SMALL).current_values(),
+            vec![(
+                "",
+                vec![Label::new("width", "32").unwrap()],
+                MetricValue::U64(u32::MAX.into())
+            )]
+        );
+    }
+
+    #[test]
+    fn saturating_inc_by() {
+        let uint: Counter<AtomicU64> = Counter::new("some_uint", "Counts things").unwrap();
+        uint.set(u64::MAX - 5);
+        uint.saturating_inc_by(3);
+        assert_eq!(uint.get(), u64::MAX - 2);
+        uint.saturating_inc_by(10);
+        assert_eq!(uint.get(), u64::MAX);
+        uint.saturating_inc_by(1);
+        assert_eq!(uint.get(), u64::MAX);
+
+        let int: Counter<AtomicI64> = Counter::new("some_int", "Counts things").unwrap();
+        int.set(i64::MAX - 5);
+        int.saturating_inc_by(10);
+        assert_eq!(int.get(), i64::MAX);
+
+        let float: Counter<AtomicF64> = Counter::new("some_float", "Counts things").unwrap();
+        float.set(f64::MAX);
+        float.saturating_inc_by(f64::MAX);
+        assert_eq!(float.get(), f64::MAX);
+        float.set(10.0);
+        float.saturating_inc_by(5.0);
+        assert_eq!(float.get(), 15.0);
+    }
+
     #[test]
     #[cfg(not(miri))]
     fn int_threaded() {
@@ -509,4 +1201,110 @@ mod tests {
 
         assert_eq!(INT.get(), 5);
     }
+
+    #[test]
+    fn inc_by_u64() {
+        let bytes = FloatCounter::new("bytes", "Bytes").unwrap();
+        bytes.inc_by(0.5);
+        bytes.inc_by_u64(4096);
+        assert_eq!(bytes.get(), 4096.5);
+
+        let ints = IntCounter::new("ints", "Ints").unwrap();
+        ints.inc_by_u64(20);
+        ints.inc_by_u64(22);
+        assert_eq!(ints.get(), 42);
+    }
+
+    #[test]
+    fn debug_output() {
+        let counter: Counter = Counter::new("debug_counter", "Counts things").unwrap();
+        counter.inc_by(3);
+
+        let debug = format!("{:?}", counter);
+        assert_eq!(
+            debug,
+            r#"Counter { name: "debug_counter", labels: {}, value: 3 }"#
+        );
+    }
+
+    #[test]
+    fn restore() {
+        use crate::{encoder::Encoder, registry::RegistryBuilder, OpenMetricsEncoder};
+
+        static RESTORED: Lazy<Counter<AtomicU64>> = Lazy::new(|| {
+            let created_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_600_000_000_500);
+            Counter::restore("restored_requests", "Total requests", 1024, created_at)
+                .unwrap()
+                .with_labels(vec![Label::new("method", "GET").unwrap()])
+        });
+
+        RESTORED.inc();
+        assert_eq!(RESTORED.get(), 1025);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*RESTORED))
+            .build()
+            .unwrap();
+
+        let mut encoded = Vec::new();
+        OpenMetricsEncoder::new()
+            .encode(&registry.collect(), &mut encoded)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(encoded).unwrap(),
+            "# HELP restored_requests Total requests\n\
+             # TYPE restored_requests counter\n\
+             restored_requests_total{method=\"GET\"} 1025\n\
+             restored_requests_created{method=\"GET\"} 1600000000.5\n\
+             # EOF\n",
+        );
+
+        // The text format has no `_created` samples
+        assert!(!registry.collect_to_string().unwrap().contains("_created"));
+    }
+
+    #[test]
+    fn for_each_sample() {
+        let counter: Counter = Counter::new("visited", "Visited samples")
+            .unwrap()
+            .with_labels(vec![Label::new("kind", "test").unwrap()]);
+        counter.inc_by(3);
+
+        let mut visited = Vec::new();
+        (&counter).for_each_sample(&mut |suffix, labels, value| {
+            visited.push((suffix.to_owned(), labels.to_vec(), value));
+        });
+
+        assert_eq!(
+            visited,
+            vec![(
+                String::new(),
+                vec![Label::new("kind", "test").unwrap()],
+                MetricValue::U64(3),
+            )],
+        );
+    }
+
+    #[test]
+    fn rated_counter() {
+        let rated: RatedCounter = RatedCounter::new(
+            Counter::new("rated_requests", "Total requests").unwrap(),
+            Duration::from_secs(16),
+        );
+        let start = Instant::now();
+        assert_eq!(rated.rate_at(start), 0.0);
+
+        // Ten increments a second for sixteen seconds, filling a one second slot every second
+        for tick in 0..160 {
+            rated.inc_by_at(1, start + Duration::from_millis(tick * 100));
+        }
+        assert_eq!(rated.get(), 160);
+        assert_eq!(rated.rate_at(start + Duration::from_millis(15900)), 10.0);
+        assert!(rated.increments.lock().unwrap().len() <= 17);
+
+        // Increments age out of the window while the counter keeps its total
+        assert_eq!(rated.rate_at(start + Duration::from_secs(24)), 5.0);
+        assert_eq!(rated.rate_at(start + Duration::from_secs(40)), 0.0);
+        assert_eq!(rated.get(), 160);
+    }
 }
@@ -0,0 +1,366 @@
+use crate::{
+    error::{PromError, PromErrorKind, Result},
+    label::escape_unquoted,
+    registry::Metric,
+};
+use std::{fmt::Write as _, io::Write};
+
+/// The content type of the Prometheus [text-based format]
+///
+/// [text-based format]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+pub const TEXT_FORMAT: &str = "text/plain; version=0.0.4";
+
+/// The content type of the [OpenMetrics] text format
+///
+/// [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+pub const OPENMETRICS_FORMAT: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Encodes metric families gathered by [`Registry::collect`] into an exposition format
+///
+/// [`Registry::collect`]: crate::Registry#method.collect
+pub trait Encoder {
+    /// Encode `families` into `writer`
+    fn encode(&self, families: &[Metric<'_>], writer: &mut dyn Write) -> Result<()>;
+
+    /// The content type of the encoded output, meant for the `Content-Type` header
+    fn format_type(&self) -> &str;
+}
+
+/// An [`Encoder`] for the Prometheus [text-based format]. Every family is written the same way as
+/// [`Registry::collect_to_string`] writes it, including the registry's
+/// [`RegistryBuilder::float_precision`], but only the families gathered by [`Registry::collect`]
+/// are written, so scoped collectors, `collector_scrape_errors_total` and the registry's own
+/// metrics are left out
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{Encoder, RegistryBuilder, TextEncoder};
+///
+/// let registry = RegistryBuilder::new()
+///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+///     .unwrap()
+///     .build()
+///     .unwrap();
+///
+/// let mut body = Vec::new();
+/// let encoder = TextEncoder::new();
+/// encoder.encode(&registry.collect(), &mut body).unwrap();
+///
+/// assert_eq!(encoder.format_type(), "text/plain; version=0.0.4");
+/// assert!(String::from_utf8(body).unwrap().contains("answer 42.0"));
+/// ```
+///
+/// [`Encoder`]: crate::Encoder
+/// [text-based format]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+/// [`Registry::collect_to_string`]: crate::Registry#method.collect_to_string
+/// [`Registry::collect`]: crate::Registry#method.collect
+/// [`RegistryBuilder::float_precision`]: crate::RegistryBuilder#method.float_precision
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct TextEncoder;
+
+impl TextEncoder {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Encoder for TextEncoder {
+    fn encode(&self, families: &[Metric<'_>], writer: &mut dyn Write) -> Result<()> {
+        let mut buf = String::new();
+        for family in families {
+            family.encode_text(&mut buf)?;
+        }
+
+        writer.write_all(buf.as_bytes()).map_err(|err| {
+            PromError::new(
+                format!("Failed to write the encoded metrics: {}", err),
+                PromErrorKind::FormattingError,
+            )
+        })
+    }
+
+    fn format_type(&self) -> &str {
+        TEXT_FORMAT
+    }
+}
+
+/// An [`Encoder`] for the [OpenMetrics] text format. Along with the `# HELP` and `# TYPE` lines
+/// it writes a `# UNIT` line for metrics with a unit and ends the exposition with `# EOF`.
+/// Samples are written the same way as the Prometheus text format, except that counter samples
+/// are always named with a `_total` suffix, which their family's name leaves out, and are
+/// followed by their `_created` timestamp
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{Encoder, OpenMetricsEncoder, RegistryBuilder};
+///
+/// let registry = RegistryBuilder::new()
+///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+///     .unwrap()
+///     .build()
+///     .unwrap();
+///
+/// let mut body = Vec::new();
+/// OpenMetricsEncoder::new().encode(&registry.collect(), &mut body).unwrap();
+///
+/// assert!(String::from_utf8(body).unwrap().ends_with("answer 42.0\n# EOF\n"));
+/// ```
+///
+/// [`Encoder`]: crate::Encoder
+/// [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct OpenMetricsEncoder;
+
+impl OpenMetricsEncoder {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Encoder for OpenMetricsEncoder {
+    fn encode(&self, families: &[Metric<'_>], writer: &mut dyn Write) -> Result<()> {
+        let mut buf = String::new();
+        for family in families {
+            let collectable = family.collectable();
+            let descriptor = collectable.descriptor();
+            let metric_type = collectable.openmetrics_type();
+
+            // The samples of info and counter families are named after the family with an
+            // `_info` or `_total` suffix
+            let name = match metric_type {
+                "info" => descriptor
+                    .name()
+                    .strip_suffix("_info")
+                    .unwrap_or_else(|| descriptor.name()),
+                "counter" => counter_family(descriptor.name()),
+                _ => descriptor.name(),
+            };
+
+            // OpenMetrics escapes quotes within help text as well as backslashes and newlines
+            write!(buf, "# HELP {} ", name)?;
+            if descriptor.raw_help {
+                buf.push_str(descriptor.help());
+            } else {
+                escape_unquoted(&mut buf, descriptor.help());
+            }
+            writeln!(buf)?;
+
+            writeln!(buf, "# TYPE {} {}", name, metric_type)?;
+            if let Some(unit) = descriptor.unit() {
+                writeln!(buf, "# UNIT {} {}", name, unit)?;
+            }
+
+            collectable.encode_openmetrics_samples(&mut buf, family.options())?;
+        }
+        writeln!(buf, "# EOF")?;
+
+        writer.write_all(buf.as_bytes()).map_err(|err| {
+            PromError::new(
+                format!("Failed to write the encoded metrics: {}", err),
+                PromErrorKind::FormattingError,
+            )
+        })
+    }
+
+    fn format_type(&self) -> &str {
+        OPENMETRICS_FORMAT
+    }
+}
+
+/// The name of a counter's OpenMetrics family, the counter's name without its `_total` suffix.
+/// The family's samples are named `<family>_total` and `<family>_created`
+pub(crate) fn counter_family(name: &str) -> &str {
+    name.strip_suffix("_total").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        counter::Counter,
+        group::CounterGroup,
+        histogram::{Histogram, HistogramBuilder},
+        label::Label,
+        registry::RegistryBuilder,
+    };
+    use once_cell::sync::Lazy;
+    use std::{
+        io,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn text_encoder_matches_collect_to_string() {
+        static COUNTER: Lazy<Counter> = Lazy::new(|| {
+            Counter::new("encoded_counter", "Counts things")
+                .unwrap()
+                .with_labels(vec![Label::new("kind", "test").unwrap()])
+        });
+        static HISTOGRAM: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("encoded_histogram")
+                .help("Hists grams")
+                .with_buckets(vec![1.0, 2.0])
+                .build()
+                .unwrap()
+        });
+
+        COUNTER.inc_by(4);
+        HISTOGRAM.observe(1.5);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .register(Box::new(&*HISTOGRAM))
+            .build()
+            .unwrap();
+
+        let mut encoded = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.collect(), &mut encoded)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(encoded).unwrap(),
+            registry.collect_to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn write_errors() {
+        struct Broken;
+
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let registry = RegistryBuilder::new()
+            .register_gauge_fn("answer", "The answer to everything", || 42.0)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let err = TextEncoder::new()
+            .encode(&registry.collect(), &mut Broken)
+            .unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::FormattingError);
+    }
+
+    #[test]
+    fn openmetrics_unit() {
+        static LATENCY: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("request_latency_seconds")
+                .help("Request \"latency\"")
+                .with_buckets(vec![0.5])
+                .unit("seconds")
+                .build()
+                .unwrap()
+        });
+        static REQUESTS: Lazy<Counter> = Lazy::new(|| {
+            let created_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+            Counter::restore("requests", "Total requests", 0, created_at).unwrap()
+        });
+        static SENT: Lazy<Counter> = Lazy::new(|| {
+            let created_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+            Counter::restore("sent_bytes_total", "Bytes sent", 2, created_at)
+                .unwrap()
+                .with_unit("bytes")
+                .unwrap()
+                // Label values that contain the metric's name are left alone
+                .with_labels(vec![Label::new("peer", "sent_bytes_total").unwrap()])
+        });
+        static ROUTES: Lazy<CounterGroup<&'static str>> = Lazy::new(|| {
+            CounterGroup::new(
+                "route_requests_total",
+                "Requests per route",
+                "route",
+                vec!["/"].into_iter(),
+            )
+            .unwrap()
+        });
+
+        LATENCY.observe(0.25);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*LATENCY))
+            .register(Box::new(&*REQUESTS))
+            .register(Box::new(&*SENT))
+            .register(Box::new(&*ROUTES))
+            .build()
+            .unwrap();
+
+        let mut encoded = Vec::new();
+        let encoder = OpenMetricsEncoder::new();
+        encoder.encode(&registry.collect(), &mut encoded).unwrap();
+
+        assert_eq!(encoder.format_type(), OPENMETRICS_FORMAT);
+        assert_eq!(
+            String::from_utf8(encoded).unwrap(),
+            "# HELP request_latency_seconds Request \\\"latency\\\"\n\
+             # TYPE request_latency_seconds histogram\n\
+             # UNIT request_latency_seconds seconds\n\
+             request_latency_seconds_sum 0.25\n\
+             request_latency_seconds_count 1\n\
+             request_latency_seconds_bucket{le=\"0.5\"} 1\n\
+             request_latency_seconds_bucket{le=\"+Inf\"} 1\n\
+             # HELP requests Total requests\n\
+             # TYPE requests counter\n\
+             requests_total 0\n\
+             requests_created 1600000000.0\n\
+             # HELP route_requests Requests per route\n\
+             # TYPE route_requests counter\n\
+             route_requests_total{route=\"/\"} 0\n\
+             # HELP sent_bytes Bytes sent\n\
+             # TYPE sent_bytes counter\n\
+             # UNIT sent_bytes bytes\n\
+             sent_bytes_total{peer=\"sent_bytes_total\"} 2\n\
+             sent_bytes_created{peer=\"sent_bytes_total\"} 1600000000.0\n\
+             # EOF\n",
+        );
+
+        // The text format has no `# UNIT` line
+        assert!(!registry.collect_to_string().unwrap().contains("# UNIT"));
+    }
+
+    #[test]
+    fn unit_suffix() {
+        let err = HistogramBuilder::<crate::AtomicF64>::new()
+            .name("request_latency")
+            .help("Request latency")
+            .with_buckets(vec![0.5])
+            .unit("seconds")
+            .build()
+            .unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidUnit);
+
+        // Only counters leave `_total` out of the name the unit is checked against
+        assert!(
+            crate::Gauge::<std::sync::atomic::AtomicU64>::new("sent_bytes_total", "Sent")
+                .unwrap()
+                .with_unit("bytes")
+                .is_err()
+        );
+
+        // The suffix has to be a whole `_`-separated component of the name
+        assert!(
+            Counter::<std::sync::atomic::AtomicU64>::new("milliseconds", "Time")
+                .unwrap()
+                .with_unit("seconds")
+                .is_err()
+        );
+        assert!(
+            Counter::<std::sync::atomic::AtomicU64>::new("sent_bytes", "Sent")
+                .unwrap()
+                .with_unit("bad unit")
+                .is_err()
+        );
+    }
+}
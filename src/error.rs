@@ -1,15 +1,16 @@
-use std::{error::Error, fmt};
+use std::{borrow::Cow, error::Error, fmt};
 
 pub type Result<T> = std::result::Result<T, PromError>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PromError {
-    message: String,
+    /// Fixed messages are borrowed so that failing validation doesn't allocate
+    message: Cow<'static, str>,
     kind: PromErrorKind,
 }
 
 impl PromError {
-    pub(crate) fn new(message: impl Into<String>, kind: PromErrorKind) -> Self {
+    pub(crate) fn new(message: impl Into<Cow<'static, str>>, kind: PromErrorKind) -> Self {
         Self {
             message: message.into(),
             kind,
@@ -34,18 +35,58 @@ impl fmt::Display for PromError {
 impl Error for PromError {}
 
 impl From<fmt::Error> for PromError {
-    fn from(err: fmt::Error) -> Self {
-        Self::new(err.to_string(), PromErrorKind::FormattingError)
+    fn from(_: fmt::Error) -> Self {
+        // `fmt::Error` carries no information, so its fixed message is used
+        Self::new(
+            "an error occurred when formatting an argument",
+            PromErrorKind::FormattingError,
+        )
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PromErrorKind {
     IncrementNegative,
+    InvalidObservation,
     InvalidLabelName,
+    InvalidLabelValue,
     InvalidMetricName,
+    InvalidUnit,
     MissingComponent,
+    MissingName,
+    MissingHelp,
+    MissingBuckets,
+    MismatchedBuckets,
     BucketNotFound,
+    DuplicatedBucket,
+    BucketOutOfRange,
     DuplicatedCollector,
+    InvalidSnapshot,
+    InvalidExposition,
     FormattingError,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::Counter;
+    use std::sync::atomic::AtomicU64;
+
+    #[test]
+    fn static_messages_are_borrowed() {
+        let err = Counter::<AtomicU64>::new("not a name", "Invalid").unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidMetricName);
+        assert!(matches!(err.message, Cow::Borrowed(_)));
+
+        let formatting = PromError::from(fmt::Error);
+        assert!(matches!(formatting.message, Cow::Borrowed(_)));
+        assert_eq!(formatting.message(), fmt::Error.to_string());
+
+        let dynamic = PromError::new(
+            format!("The bucket {} doesn't exist", 1),
+            PromErrorKind::BucketNotFound,
+        );
+        assert!(matches!(dynamic.message, Cow::Owned(_)));
+        assert_eq!(dynamic.message(), "The bucket 1 doesn't exist");
+    }
+}
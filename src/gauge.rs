@@ -1,15 +1,22 @@
 use crate::{
-    atomics::{AtomicF64, AtomicNum, Num},
+    atomics::{AtomicF64, AtomicNum, FormatOptions, MetricValue, Num},
+    counter::Counter,
     error::Result,
-    label::Label,
+    label::{write_labels, DebugLabels, Label},
     registry::{Collectable, Descriptor},
     timer::Timer,
 };
 use std::{
     borrow::Cow,
-    fmt::Write,
-    sync::atomic::{AtomicI64, AtomicU64},
-    time::{Instant, SystemTime},
+    cell::Cell,
+    collections::VecDeque,
+    convert::TryFrom,
+    fmt::{self, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant, SystemTime},
 };
 
 pub type UintGauge = Gauge<AtomicU64>;
@@ -17,10 +24,23 @@ pub type FloatGauge = Gauge<AtomicF64>;
 pub type IntGauge = Gauge<AtomicI64>;
 
 /// [Definition](https://prometheus.io/docs/instrumenting/writing_clientlibs/#gauge)
-#[derive(Debug)]
 pub struct Gauge<Atomic: AtomicNum = AtomicU64> {
     value: Atomic,
     descriptor: Descriptor,
+    /// The recent values of the gauge, only kept when enabled with [`Gauge::with_history`]
+    ///
+    /// [`Gauge::with_history`]: crate::Gauge#method.with_history
+    history: Option<History>,
+}
+
+impl<Atomic: AtomicNum> fmt::Debug for Gauge<Atomic> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gauge")
+            .field("name", &self.name())
+            .field("labels", &DebugLabels(self.labels()))
+            .field("value", &self.get())
+            .finish()
+    }
 }
 
 impl<Atomic: AtomicNum> Gauge<Atomic> {
@@ -28,35 +48,124 @@ impl<Atomic: AtomicNum> Gauge<Atomic> {
         Ok(Self {
             value: Atomic::new(),
             descriptor: Descriptor::new(name, help, Vec::new())?,
+            history: None,
         })
     }
 
+    /// Keep the last `capacity` values of the gauge along with the unix timestamp in milliseconds
+    /// of when they were set, meant for debugging unexpected values. The history isn't exported
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Gauge;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let gauge: Gauge<AtomicU64> = Gauge::new("queue_length", "The length of the queue")
+    ///     .unwrap()
+    ///     .with_history(2);
+    ///
+    /// gauge.set(10);
+    /// gauge.inc();
+    /// gauge.dec_by(5);
+    ///
+    /// let values: Vec<f64> = gauge.history().into_iter().map(|(_, value)| value).collect();
+    /// assert_eq!(values, vec![11.0, 6.0]);
+    /// ```
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = Some(History::new(capacity));
+        self
+    }
+
+    /// Get the recorded `(timestamp, value)` history of the gauge from oldest to newest, empty
+    /// unless enabled with [`Gauge::with_history`]
+    ///
+    /// [`Gauge::with_history`]: crate::Gauge#method.with_history
+    pub fn history(&self) -> Vec<(u64, f64)> {
+        self.history
+            .as_ref()
+            .map(History::points)
+            .unwrap_or_default()
+    }
+
     pub fn inc(&self) {
-        self.value.inc();
+        self.record(self.value.inc());
     }
 
     pub fn inc_by(&self, inc: Atomic::Type) {
-        self.value.inc_by(inc);
+        self.record(self.value.inc_by(inc));
     }
 
+    /// Decrement the gauge by 1, unsigned gauges stop at zero instead of wrapping around
     pub fn dec(&self) {
-        self.value.dec();
+        self.record(self.value.saturating_dec_by(Atomic::Type::from_u64(1)));
     }
 
+    /// Decrement the gauge by `dec`, unsigned gauges stop at zero instead of wrapping around
     pub fn dec_by(&self, dec: Atomic::Type) {
-        self.value.dec_by(dec);
+        self.record(self.value.saturating_dec_by(dec));
     }
 
     pub fn set(&self, val: Atomic::Type) {
         self.value.set(val);
+        self.record(val);
+    }
+
+    /// Set the gauge to `val` if it's greater than the gauge's current value, for tracking a
+    /// high-water mark like the most concurrent connections ever seen
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Gauge;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let max_connections: Gauge<AtomicU64> =
+    ///     Gauge::new("max_connections", "The most concurrent connections").unwrap();
+    ///
+    /// max_connections.update_max(12);
+    /// max_connections.update_max(4);
+    /// assert_eq!(max_connections.get(), 12);
+    ///
+    /// // Start tracking a new period from the current number of connections
+    /// max_connections.reset_watermark(4);
+    /// assert_eq!(max_connections.get(), 4);
+    /// ```
+    pub fn update_max(&self, val: Atomic::Type) {
+        self.record(self.value.update_max(val));
+    }
+
+    /// Set the gauge to `val` if it's less than the gauge's current value, for tracking a
+    /// low-water mark. Start the gauge from a high value with [`Gauge::reset_watermark`] since
+    /// it starts at zero
+    ///
+    /// [`Gauge::reset_watermark`]: crate::Gauge#method.reset_watermark
+    pub fn update_min(&self, val: Atomic::Type) {
+        self.record(self.value.update_min(val));
+    }
+
+    /// Reset a high or low-water mark tracked with [`Gauge::update_max`] or
+    /// [`Gauge::update_min`] to the `current` instantaneous value, e.g. at the start of each
+    /// reporting period
+    ///
+    /// [`Gauge::update_max`]: crate::Gauge#method.update_max
+    /// [`Gauge::update_min`]: crate::Gauge#method.update_min
+    pub fn reset_watermark(&self, current: Atomic::Type) {
+        self.set(current);
     }
 
     pub fn get(&self) -> Atomic::Type {
         self.value.get()
     }
 
+    /// Returns `true` if the gauge is zero, for float gauges both `0.0` and `-0.0` are zero
+    pub fn is_zero(&self) -> bool {
+        self.get() == Atomic::Type::default()
+    }
+
     pub fn clear(&self) {
-        self.value.clear()
+        self.value.clear();
+        self.record(Atomic::Type::default());
     }
 
     pub fn set_to_current_time(&self) {
@@ -65,10 +174,10 @@ impl<Atomic: AtomicNum> Gauge<Atomic> {
             .expect("Impossible to fail, `UNIX_EPOCH` will never be sooner than the current system time")
             .as_secs();
 
-        self.value.set(Atomic::Type::from_u64(current_time));
+        self.set(Atomic::Type::from_u64(current_time));
     }
 
-    pub fn start_timer<'a>(&'a self) -> Timer<'a, Self> {
+    pub fn start_timer(&self) -> Timer<'_, Self> {
         Timer::new(self)
     }
 
@@ -81,11 +190,11 @@ impl<Atomic: AtomicNum> Gauge<Atomic> {
     }
 
     pub fn name(&self) -> &str {
-        &self.descriptor.name()
+        self.descriptor.name()
     }
 
     pub fn help(&self) -> &str {
-        &self.descriptor.help()
+        self.descriptor.help()
     }
 
     pub fn labels(&self) -> &[Label] {
@@ -96,40 +205,664 @@ impl<Atomic: AtomicNum> Gauge<Atomic> {
         self.descriptor.labels = labels.into();
         self
     }
+
+    /// Set the gauge's unit, which the [`OpenMetricsEncoder`] emits as a `# UNIT` line
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the unit is invalid or the gauge's name doesn't end with `_{unit}`
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    /// [`PromError`]: crate::PromError
+    pub fn with_unit(mut self, unit: impl Into<Cow<'static, str>>) -> Result<Self> {
+        self.descriptor.set_unit(unit.into(), false)?;
+        Ok(self)
+    }
+
+    /// Write the gauge's help as-is instead of escaping backslashes and newlines, for help text
+    /// that's already escaped. The caller is responsible for the help being valid in the exposition format
+    pub fn with_raw_help(mut self) -> Self {
+        self.descriptor.raw_help = true;
+        self
+    }
+
+    /// Create a [`LocalGauge`] that starts at the gauge's current value and is modified without
+    /// atomics, the gauge is set to its value when it's flushed
+    ///
+    /// [`LocalGauge`]: crate::gauge::LocalGauge
+    pub fn local(&self) -> LocalGauge<'_, Atomic> {
+        LocalGauge::new(self)
+    }
+
+    /// Push `value`, the value produced by the operation that just modified the gauge, to the
+    /// history. Reading the gauge back instead could record another thread's later update twice
+    fn record(&self, value: Atomic::Type) {
+        if let Some(history) = self.history.as_ref() {
+            let value: MetricValue = value.into();
+            history.push(value.as_f64());
+        }
+    }
 }
 
-impl<Atomic: AtomicNum> Collectable for &Gauge<Atomic> {
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()> {
-        writeln!(buf, "# HELP {} {}", self.name(), self.help())?;
-        writeln!(buf, "# TYPE {} gauge", self.name())?;
+impl Gauge<AtomicI64> {
+    /// Set the gauge to the whole seconds of `duration`, saturating at `i64::MAX`
+    pub fn set_duration(&self, duration: Duration) {
+        self.set(i64::try_from(duration.as_secs()).unwrap_or(i64::MAX));
+    }
+}
+
+impl Gauge<AtomicF64> {
+    /// Set the gauge to `duration` in seconds, including the fractional part
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::gauge::FloatGauge;
+    /// use std::time::Duration;
+    ///
+    /// let since_backup = FloatGauge::new("seconds_since_backup", "Time since the last backup").unwrap();
+    /// since_backup.set_duration(Duration::from_millis(1500));
+    /// assert_eq!(since_backup.get(), 1.5);
+    /// ```
+    pub fn set_duration(&self, duration: Duration) {
+        self.set(duration.as_secs_f64());
+    }
+
+    /// Set the gauge to [`AtomicF64::STALE_MARKER`], telling exporters that forward samples to
+    /// Prometheus that the series has gone away. Setting the gauge to any other value un-marks it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::gauge::FloatGauge;
+    ///
+    /// let temperature = FloatGauge::new("temperature", "The current temperature").unwrap();
+    /// temperature.mark_stale();
+    /// assert!(temperature.is_stale());
+    ///
+    /// temperature.set(21.5);
+    /// assert!(!temperature.is_stale());
+    /// ```
+    ///
+    /// [`AtomicF64::STALE_MARKER`]: crate::AtomicF64#associatedconstant.STALE_MARKER
+    pub fn mark_stale(&self) {
+        self.set(AtomicF64::STALE_MARKER);
+    }
+
+    /// Returns `true` if the gauge was marked stale with [`Gauge::mark_stale`]
+    ///
+    /// [`Gauge::mark_stale`]: crate::Gauge#method.mark_stale
+    pub fn is_stale(&self) -> bool {
+        AtomicF64::is_stale_marker(self.get())
+    }
+}
 
+/// A single-threaded gauge backed by a [`Cell`] instead of an atomic, the [`Gauge`] it was
+/// created from is set to its value when it's flushed
+///
+/// `LocalGauge` isn't `Sync`, so it can't be shared between threads
+///
+/// ```rust,compile_fail
+/// use prometheus_rs::Gauge;
+/// use std::sync::atomic::AtomicI64;
+///
+/// fn assert_sync<T: Sync>(_: T) {}
+///
+/// let gauge: Gauge<AtomicI64> = Gauge::new("queue_length", "The length of the queue").unwrap();
+/// assert_sync(gauge.local());
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::Gauge;
+/// use std::sync::atomic::AtomicI64;
+///
+/// let gauge: Gauge<AtomicI64> = Gauge::new("queue_length", "The length of the queue").unwrap();
+/// gauge.set(5);
+///
+/// let local = gauge.local();
+/// local.inc_by(10);
+/// local.dec();
+/// assert_eq!(local.get(), 14);
+/// assert_eq!(gauge.get(), 5);
+///
+/// local.flush();
+/// assert_eq!(gauge.get(), 14);
+/// ```
+///
+/// [`Cell`]: std::cell::Cell
+/// [`Gauge`]: crate::Gauge
+#[derive(Debug)]
+pub struct LocalGauge<'a, Atomic: AtomicNum> {
+    gauge: &'a Gauge<Atomic>,
+    value: Cell<Atomic::Type>,
+}
+
+impl<'a, Atomic: AtomicNum> LocalGauge<'a, Atomic> {
+    pub(crate) fn new(gauge: &'a Gauge<Atomic>) -> Self {
+        Self {
+            gauge,
+            value: Cell::new(gauge.get()),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(Atomic::Type::from_u64(1));
+    }
+
+    /// Increment the gauge by `inc`, saturating at the type's maximum value instead of overflowing
+    pub fn inc_by(&self, inc: Atomic::Type) {
+        self.value.set(self.value.get().saturating_add(inc));
+    }
+
+    /// Decrement the gauge by 1, unsigned gauges stop at zero instead of wrapping around
+    pub fn dec(&self) {
+        self.dec_by(Atomic::Type::from_u64(1));
+    }
+
+    /// Decrement the gauge by `dec`, unsigned gauges stop at zero instead of wrapping around
+    pub fn dec_by(&self, dec: Atomic::Type) {
+        self.value.set(self.value.get().saturating_sub(dec));
+    }
+
+    pub fn set(&self, val: Atomic::Type) {
+        self.value.set(val);
+    }
+
+    pub fn get(&self) -> Atomic::Type {
+        self.value.get()
+    }
+
+    /// Returns `true` if the gauge is zero, for float gauges both `0.0` and `-0.0` are zero
+    pub fn is_zero(&self) -> bool {
+        self.get() == Atomic::Type::default()
+    }
+
+    /// Set the shared gauge to the local value
+    pub fn flush(&self) {
+        self.gauge.set(self.value.get());
+    }
+}
+
+/// A bounded ring buffer of a gauge's recent values
+#[derive(Debug)]
+struct History {
+    capacity: usize,
+    points: Mutex<VecDeque<(u64, f64)>>,
+}
+
+impl History {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            points: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn push(&self, value: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let timestamp = SystemTime::UNIX_EPOCH
+            .elapsed()
+            .expect("Impossible to fail, `UNIX_EPOCH` will never be sooner than the current system time")
+            .as_millis() as u64;
+
+        let mut points = self.points.lock().unwrap();
+        if points.len() == self.capacity {
+            points.pop_front();
+        }
+        points.push_back((timestamp, value));
+    }
+
+    fn points(&self) -> Vec<(u64, f64)> {
+        self.points.lock().unwrap().iter().copied().collect()
+    }
+}
+
+impl<Atomic: AtomicNum> Collectable for &Gauge<Atomic> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
         write!(buf, "{}", self.name())?;
         if !self.labels().is_empty() {
             write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
+        }
+        write!(buf, " ")?;
 
-            let (last, labels) = self
-                .labels()
-                .split_last()
-                .expect("There is at least 1 label");
-            for label in labels {
-                write!(buf, "{}={:?},", label.name(), label.value())?;
+        options.write_value(buf, self.get())?;
+        writeln!(buf)?;
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), self.get().into());
+    }
+}
+
+/// A gauge that reports the mean of the observations made within a trailing window of time,
+/// useful for smoothing out noisy measurements on dashboards
+///
+/// Observations older than the window are evicted lazily whenever the gauge is observed into
+/// or read. If there are no observations within the window the mean is `NaN`
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::gauge::WindowedGauge;
+/// use std::time::Duration;
+///
+/// let gauge = WindowedGauge::new("cpu_load", "Average CPU load", Duration::from_secs(10)).unwrap();
+/// gauge.observe(0.5);
+/// gauge.observe(1.5);
+///
+/// assert_eq!(gauge.mean(), 1.0);
+/// assert_eq!(gauge.count(), 2);
+/// ```
+#[derive(Debug)]
+pub struct WindowedGauge {
+    samples: Mutex<VecDeque<(Instant, f64)>>,
+    window: Duration,
+    descriptor: Descriptor,
+}
+
+impl WindowedGauge {
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        window: Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            samples: Mutex::new(VecDeque::new()),
+            window,
+            descriptor: Descriptor::new(name, help, Vec::new())?,
+        })
+    }
+
+    pub fn observe(&self, val: f64) {
+        self.observe_at(val, Instant::now());
+    }
+
+    /// Get the mean of all observations within the window
+    pub fn mean(&self) -> f64 {
+        self.mean_at(Instant::now())
+    }
+
+    /// Get the number of observations within the window
+    pub fn count(&self) -> usize {
+        self.evicted(Instant::now()).len()
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn clear(&self) {
+        self.samples.lock().unwrap().clear();
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
+
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Self {
+        self.descriptor.labels = labels.into();
+        self
+    }
+
+    pub(crate) fn observe_at(&self, val: f64, now: Instant) {
+        self.evicted(now).push_back((now, val));
+    }
+
+    pub(crate) fn mean_at(&self, now: Instant) -> f64 {
+        let samples = self.evicted(now);
+        if samples.is_empty() {
+            return f64::NAN;
+        }
+
+        samples.iter().map(|(_, val)| val).sum::<f64>() / samples.len() as f64
+    }
+
+    /// Locks the samples, removing any that fall outside of the window ending at `now`
+    fn evicted(&self, now: Instant) -> MutexGuard<'_, VecDeque<(Instant, f64)>> {
+        let mut samples = self.samples.lock().unwrap();
+        while let Some((time, _)) = samples.front() {
+            if now.saturating_duration_since(*time) > self.window {
+                samples.pop_front();
+            } else {
+                break;
             }
-            write!(buf, "{}={:?}", last.name(), last.value())?;
+        }
+
+        samples
+    }
+}
+
+impl Collectable for &WindowedGauge {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write!(buf, "{}", self.name())?;
+        if !self.labels().is_empty() {
+            write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
+        }
+        write!(buf, " ")?;
+
+        options.write_value(buf, self.mean())?;
+        writeln!(buf)?;
 
-            write!(buf, "}} ")?;
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), self.mean().into());
+    }
+}
+
+/// Create a gauge that's always `1` and carries informational labels, following the
+/// `build_info{version="...",revision="..."} 1` convention
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{gauge::build_info, Label, RegistryBuilder};
+///
+/// let info = build_info(
+///     "app_build_info",
+///     vec![Label::new("version", env!("CARGO_PKG_VERSION")).unwrap()],
+/// )
+/// .unwrap();
+///
+/// let registry = RegistryBuilder::new().register(Box::new(info)).build().unwrap();
+/// assert!(registry.collect_to_string().unwrap().contains("app_build_info{version="));
+/// ```
+///
+/// # Errors
+///
+/// Returns a [`PromError`] if the given name doesn't follow the [prometheus metric name specification]
+///
+/// [`PromError`]: crate::PromError
+/// [prometheus metric name specification]: https://prometheus.io/docs/concepts/data_model/#metric-names-and-labels
+pub fn build_info(
+    name: impl Into<Cow<'static, str>>,
+    labels: impl Into<Vec<Label>>,
+) -> Result<InfoGauge> {
+    Ok(InfoGauge {
+        descriptor: Descriptor::new(
+            name,
+            "A metric with a constant '1' value labeled with build information",
+            labels.into(),
+        )?,
+    })
+}
+
+/// A gauge that's always `1`, only carrying information through its labels. Created with [`build_info`]
+///
+/// [`build_info`]: crate::gauge::build_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfoGauge {
+    descriptor: Descriptor,
+}
+
+impl InfoGauge {
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
+}
+
+impl Collectable for InfoGauge {
+    fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+        write!(buf, "{}", self.name())?;
+        if !self.labels().is_empty() {
+            write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
+        }
+        writeln!(buf, " 1")?;
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), MetricValue::U64(1));
+    }
+}
+
+/// A gauge for boolean states like health checks or feature flags, exported as `0` or `1`
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::gauge::BoolGauge;
+///
+/// let healthy = BoolGauge::new("healthy", "Whether the service is healthy").unwrap();
+/// assert!(!healthy.get());
+///
+/// healthy.set(true);
+/// assert!(healthy.get());
+///
+/// healthy.toggle();
+/// assert!(!healthy.get());
+/// ```
+#[derive(Debug)]
+pub struct BoolGauge {
+    value: AtomicBool,
+    descriptor: Descriptor,
+}
+
+impl BoolGauge {
+    pub fn new(name: impl Into<Cow<'static, str>>, help: impl AsRef<str>) -> Result<Self> {
+        Ok(Self {
+            value: AtomicBool::new(false),
+            descriptor: Descriptor::new(name, help, Vec::new())?,
+        })
+    }
+
+    pub fn set(&self, val: bool) {
+        self.value.store(val, Ordering::SeqCst);
+    }
+
+    /// Flip the gauge's state, returning the new state
+    pub fn toggle(&self) -> bool {
+        !self.value.fetch_xor(true, Ordering::SeqCst)
+    }
+
+    pub fn get(&self) -> bool {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
+
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Self {
+        self.descriptor.labels = labels.into();
+        self
+    }
+}
+
+impl Collectable for &BoolGauge {
+    fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+        write!(buf, "{}", self.name())?;
+        if !self.labels().is_empty() {
+            write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
+        }
+        writeln!(buf, " {}", u8::from(self.get()))?;
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), MetricValue::U64(self.get().into()));
+    }
+}
+
+/// A gauge exporting the ratio of two [`Counter`]s, like a cache's hit rate, computed whenever
+/// it's collected. The ratio is `0` while the denominator is zero
+///
+/// Exposing both counters and dividing them in a query is usually preferable, since ratios
+/// can't be aggregated across instances, but some dashboards want the ratio directly
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{gauge::RatioGauge, Counter};
+/// use once_cell::sync::Lazy;
+/// use std::sync::atomic::AtomicU64;
+///
+/// static HITS: Lazy<Counter<AtomicU64>> =
+///     Lazy::new(|| Counter::new("cache_hits", "Cache hits").unwrap());
+/// static LOOKUPS: Lazy<Counter<AtomicU64>> =
+///     Lazy::new(|| Counter::new("cache_lookups", "Cache lookups").unwrap());
+///
+/// let hit_rate = RatioGauge::new("cache_hit_ratio", "Cache hit ratio", &*HITS, &*LOOKUPS).unwrap();
+///
+/// HITS.inc();
+/// LOOKUPS.inc_by(4);
+/// assert_eq!(hit_rate.ratio(), 0.25);
+/// ```
+///
+/// [`Counter`]: crate::Counter
+#[derive(Debug)]
+pub struct RatioGauge<'a, Atomic: AtomicNum = AtomicU64> {
+    descriptor: Descriptor,
+    numerator: &'a Counter<Atomic>,
+    denominator: &'a Counter<Atomic>,
+}
+
+impl<'a, Atomic: AtomicNum> RatioGauge<'a, Atomic> {
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        numerator: &'a Counter<Atomic>,
+        denominator: &'a Counter<Atomic>,
+    ) -> Result<Self> {
+        Ok(Self {
+            descriptor: Descriptor::new(name, help, Vec::new())?,
+            numerator,
+            denominator,
+        })
+    }
+
+    /// The current ratio of the numerator to the denominator, `0` if the denominator is zero
+    pub fn ratio(&self) -> f64 {
+        let numerator: MetricValue = self.numerator.get().into();
+        let denominator: MetricValue = self.denominator.get().into();
+
+        let denominator = denominator.as_f64();
+        if denominator == 0.0 {
+            0.0
         } else {
-            write!(buf, " ")?;
+            numerator.as_f64() / denominator
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
+
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Self {
+        self.descriptor.labels = labels.into();
+        self
+    }
+}
+
+impl<Atomic: AtomicNum> Collectable for RatioGauge<'_, Atomic> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write!(buf, "{}", self.name())?;
+        if !self.labels().is_empty() {
+            write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
         }
+        write!(buf, " ")?;
 
-        Atomic::format(self.get(), buf, false)?;
+        options.write_value(buf, self.ratio())?;
         writeln!(buf)?;
 
         Ok(())
     }
 
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
     fn descriptor(&self) -> &Descriptor {
         &self.descriptor
     }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), MetricValue::F64(self.ratio()));
+    }
 }
 
 #[cfg(test)]
@@ -161,6 +894,39 @@ mod tests {
         assert_eq!(uint.get(), 999);
     }
 
+    #[test]
+    fn local_gauge() {
+        let gauge: Gauge<AtomicU64> = Gauge::new("local_uint", "Counts things")
+            .unwrap()
+            .with_history(4);
+        gauge.set(3);
+
+        let local = gauge.local();
+        assert_eq!(local.get(), 3);
+
+        local.dec_by(10);
+        assert!(local.is_zero());
+
+        local.inc_by(7);
+        local.set(20);
+        local.inc();
+        assert_eq!(local.get(), 21);
+        assert_eq!(gauge.get(), 3);
+
+        // Only the flush is recorded in the gauge's history
+        local.flush();
+        assert_eq!(gauge.get(), 21);
+        let values: Vec<f64> = gauge
+            .history()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(values, vec![3.0, 21.0]);
+
+        local.inc_by(u64::MAX);
+        assert_eq!(local.get(), u64::MAX);
+    }
+
     #[test]
     fn uint_gauge_timer() {
         let uint: Gauge<AtomicU64> = Gauge::new("some_uint", "Counts things").unwrap();
@@ -260,6 +1026,145 @@ mod tests {
         assert_eq!(FLOAT.get(), 5.0);
     }
 
+    #[test]
+    fn bool_gauge() {
+        use crate::registry::RegistryBuilder;
+
+        static FEATURE: Lazy<BoolGauge> = Lazy::new(|| {
+            BoolGauge::new("feature_enabled", "Whether the feature is enabled")
+                .unwrap()
+                .with_labels(vec![Label::new("feature", "search").unwrap()])
+        });
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*FEATURE))
+            .build()
+            .unwrap();
+        let sample = |value| {
+            format!(
+                "# HELP feature_enabled Whether the feature is enabled\n\
+                 # TYPE feature_enabled gauge\n\
+                 feature_enabled{{feature=\"search\"}} {}\n",
+                value
+            )
+        };
+
+        FEATURE.set(true);
+        assert_eq!(registry.collect_to_string().unwrap(), sample(1));
+
+        FEATURE.set(false);
+        assert_eq!(registry.collect_to_string().unwrap(), sample(0));
+
+        assert!(FEATURE.toggle());
+        assert_eq!(registry.collect_to_string().unwrap(), sample(1));
+        assert_eq!(
+            (&*FEATURE).current_values(),
+            vec![("", FEATURE.labels().to_vec(), MetricValue::U64(1))],
+        );
+    }
+
+    #[test]
+    fn build_info_gauge() {
+        let info = build_info(
+            "app_build_info",
+            vec![
+                Label::new("version", "1.2.3").unwrap(),
+                Label::new("commit", "abc123").unwrap(),
+            ],
+        )
+        .unwrap();
+
+        let mut buf = String::new();
+        info.encode_text(&mut buf, &FormatOptions::new()).unwrap();
+        assert_eq!(
+            buf,
+            "# HELP app_build_info A metric with a constant '1' value labeled with build information\n\
+             # TYPE app_build_info gauge\n\
+             app_build_info{version=\"1.2.3\",commit=\"abc123\"} 1\n",
+        );
+
+        assert_eq!(
+            build_info("not a name", Vec::new()).unwrap_err().kind(),
+            crate::PromErrorKind::InvalidMetricName
+        );
+    }
+
+    #[test]
+    fn zero_gauges() {
+        let float: Gauge<AtomicF64> = Gauge::new("some_float", "Counts things").unwrap();
+        assert!(float.is_zero());
+        float.set(-0.0);
+        assert!(float.is_zero());
+        float.set(0.0);
+        assert!(float.is_zero());
+        float.dec();
+        assert!(!float.is_zero());
+        assert_eq!(float.get(), -1.0);
+
+        let uint: Gauge<AtomicU64> = Gauge::new("some_uint", "Counts things").unwrap();
+        uint.set(2);
+        uint.dec();
+        assert!(!uint.is_zero());
+        uint.dec_by(5);
+        assert!(uint.is_zero());
+        uint.dec();
+        assert_eq!(uint.get(), 0);
+
+        let int: Gauge<AtomicI64> = Gauge::new("some_int", "Counts things").unwrap();
+        int.dec_by(3);
+        assert_eq!(int.get(), -3);
+        int.set(i64::MIN);
+        int.dec();
+        assert_eq!(int.get(), i64::MIN);
+    }
+
+    #[test]
+    fn gauge_history() {
+        let int: Gauge<AtomicI64> = Gauge::new("some_int", "Counts things")
+            .unwrap()
+            .with_history(3);
+
+        int.set(5);
+        int.inc();
+        int.dec_by(10);
+        int.inc_by(2);
+
+        let history = int.history();
+        let values: Vec<f64> = history.iter().map(|&(_, value)| value).collect();
+        assert_eq!(values, vec![6.0, -4.0, -2.0]);
+        assert!(history.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        let untracked: Gauge<AtomicI64> = Gauge::new("some_int", "Counts things").unwrap();
+        untracked.set(5);
+        assert!(untracked.history().is_empty());
+    }
+
+    #[test]
+    fn concurrent_gauge_history() {
+        let gauge: Gauge<AtomicU64> = Gauge::new("concurrent", "Counts things")
+            .unwrap()
+            .with_history(400);
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        gauge.inc();
+                    }
+                });
+            }
+        });
+
+        // Every increment records the value it produced, so none are recorded twice
+        let mut values: Vec<f64> = gauge
+            .history()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, (1..=400).map(f64::from).collect::<Vec<_>>());
+    }
+
     #[test]
     fn int_gauge() {
         let int: Gauge<AtomicI64> = Gauge::new("some_int", "Counts things").unwrap();
@@ -320,4 +1225,167 @@ mod tests {
 
         assert_eq!(INT.get(), 5);
     }
+
+    #[test]
+    fn windowed_gauge_excludes_old_samples() {
+        let gauge =
+            WindowedGauge::new("windowed", "A windowed gauge", Duration::from_secs(1)).unwrap();
+        let start = Instant::now();
+
+        gauge.observe_at(100.0, start);
+        gauge.observe_at(10.0, start + Duration::from_secs(2));
+        gauge.observe_at(20.0, start + Duration::from_millis(2500));
+
+        assert_eq!(gauge.mean_at(start + Duration::from_secs(3)), 15.0);
+        assert_eq!(gauge.samples.lock().unwrap().len(), 2);
+
+        assert!(gauge.mean_at(start + Duration::from_secs(10)).is_nan());
+        assert!(gauge.samples.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn windowed_gauge_encoding() {
+        let gauge =
+            WindowedGauge::new("windowed", "A windowed gauge", Duration::from_secs(60)).unwrap();
+        gauge.observe(1.0);
+        gauge.observe(2.0);
+
+        let mut buf = String::new();
+        (&gauge)
+            .encode_text(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(
+            buf,
+            "# HELP windowed A windowed gauge\n# TYPE windowed gauge\nwindowed 1.5\n"
+        );
+    }
+
+    #[test]
+    fn stale_gauge() {
+        static TEMPERATURE: Lazy<Gauge<AtomicF64>> =
+            Lazy::new(|| Gauge::new("temperature", "The current temperature").unwrap());
+
+        TEMPERATURE.mark_stale();
+        assert_eq!(
+            TEMPERATURE.get().to_bits(),
+            AtomicF64::STALE_MARKER.to_bits()
+        );
+
+        let mut buf = String::new();
+        (&*TEMPERATURE)
+            .encode_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(buf, "temperature NaN\n");
+
+        // Other `NaN`s aren't stale markers
+        TEMPERATURE.set(f64::NAN);
+        assert!(!TEMPERATURE.is_stale());
+    }
+
+    #[test]
+    fn debug_output() {
+        let gauge: Gauge<AtomicI64> = Gauge::new("debug_gauge", "Gauges things")
+            .unwrap()
+            .with_labels(vec![Label::new("kind", "test").unwrap()]);
+        gauge.set(-5);
+
+        assert_eq!(
+            format!("{:?}", gauge),
+            r#"Gauge { name: "debug_gauge", labels: {"kind": "test"}, value: -5 }"#,
+        );
+    }
+
+    #[test]
+    fn ratio_gauge() {
+        use crate::registry::RegistryBuilder;
+
+        static HITS: Lazy<Counter<AtomicU64>> =
+            Lazy::new(|| Counter::new("ratio_hits", "Cache hits").unwrap());
+        static LOOKUPS: Lazy<Counter<AtomicU64>> =
+            Lazy::new(|| Counter::new("ratio_lookups", "Cache lookups").unwrap());
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(
+                RatioGauge::new("ratio_hit_rate", "Cache hit rate", &*HITS, &*LOOKUPS).unwrap(),
+            ))
+            .build()
+            .unwrap();
+
+        // Nothing has been looked up yet
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP ratio_hit_rate Cache hit rate\n\
+             # TYPE ratio_hit_rate gauge\n\
+             ratio_hit_rate 0.0\n",
+        );
+
+        HITS.inc_by(3);
+        LOOKUPS.inc_by(4);
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP ratio_hit_rate Cache hit rate\n\
+             # TYPE ratio_hit_rate gauge\n\
+             ratio_hit_rate 0.75\n",
+        );
+    }
+
+    #[test]
+    fn watermarks() {
+        let high: Gauge<AtomicI64> = Gauge::new("high_watermark", "The most things").unwrap();
+        let low: Gauge<AtomicF64> = Gauge::new("low_watermark", "The fewest things").unwrap();
+        low.reset_watermark(f64::INFINITY);
+
+        thread::scope(|scope| {
+            for thread in 0..8 {
+                let (high, low) = (&high, &low);
+
+                scope.spawn(move || {
+                    for val in 0..1000 {
+                        high.update_max(thread * 1000 + val);
+                        low.update_min((thread * 1000 + val) as f64);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(high.get(), 7999);
+        assert_eq!(low.get(), 0.0);
+
+        // Smaller and larger values don't move the marks
+        high.update_max(-1);
+        low.update_min(1.0);
+        assert_eq!(high.get(), 7999);
+        assert_eq!(low.get(), 0.0);
+
+        high.reset_watermark(3);
+        high.update_max(2);
+        assert_eq!(high.get(), 3);
+    }
+
+    #[test]
+    fn for_each_sample() {
+        let gauge: Gauge<AtomicI64> = Gauge::new("visited", "Visited samples").unwrap();
+        gauge.set(-2);
+
+        let mut visited = Vec::new();
+        (&gauge).for_each_sample(&mut |suffix, labels, value| {
+            visited.push((suffix.to_owned(), labels.len(), value));
+        });
+
+        assert_eq!(visited, vec![(String::new(), 0, MetricValue::I64(-2))]);
+    }
+
+    #[test]
+    fn set_duration() {
+        let float: FloatGauge = Gauge::new("float_duration", "Durations").unwrap();
+        float.set_duration(Duration::from_millis(1500));
+        assert_eq!(float.get(), 1.5);
+
+        let int: IntGauge = Gauge::new("int_duration", "Durations").unwrap();
+        int.set_duration(Duration::from_millis(1500));
+        assert_eq!(int.get(), 1);
+
+        int.set_duration(Duration::MAX);
+        assert_eq!(int.get(), i64::MAX);
+    }
 }
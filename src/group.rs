@@ -1,8 +1,9 @@
 use crate::{
-    atomics::AtomicNum,
+    atomics::{AtomicNum, FormatOptions, MetricValue},
+    encoder::counter_family,
     error::{PromError, PromErrorKind, Result},
-    histogram::HistogramCore,
-    label::{valid_label_name, Label},
+    histogram::{le_label, write_le, HistogramCore, HistogramSnapshot},
+    label::{escape_label_value, valid_label_name, write_labels, Label},
     registry::{Collectable, Descriptor},
 };
 use std::{
@@ -10,8 +11,9 @@ use std::{
     collections::HashMap,
     fmt::Write,
     hash::Hash,
-    iter::{self, FromIterator},
-    sync::atomic::AtomicU64,
+    iter,
+    ops::Deref,
+    sync::{atomic::AtomicU64, Arc, RwLock, RwLockReadGuard},
 };
 
 // TODO: Optional fast hashers like fnv and fxhash
@@ -25,6 +27,19 @@ impl<T, K: Key> Group<T, K> {
         Self { metrics }
     }
 
+    /// Create a group with a child made by `child` for every key, reserving room for the keys up
+    /// front from the iterator's size hint so that large groups aren't repeatedly rehashed
+    pub(crate) fn from_keys<V, F>(keys: V, mut child: F) -> Self
+    where
+        V: Iterator<Item = K>,
+        F: FnMut() -> T,
+    {
+        let mut metrics = HashMap::with_capacity(keys.size_hint().0);
+        metrics.extend(keys.map(|key| (key, child())));
+
+        Self::new(metrics)
+    }
+
     pub fn get(&self, key: K) -> &T {
         self.metrics
             .get(&key)
@@ -34,24 +49,109 @@ impl<T, K: Key> Group<T, K> {
     pub fn try_get(&self, key: K) -> Option<&T> {
         self.metrics.get(&key)
     }
+
+    /// Get the child with the key `key`, inserting the child made by `child` if it doesn't exist
+    pub(crate) fn get_or_insert_with<F>(&mut self, key: K, child: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        self.metrics.entry(key).or_insert_with(child)
+    }
+
+    pub(crate) fn contains(&self, key: &K) -> bool {
+        self.metrics.contains_key(key)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.metrics.len()
+    }
+
+    /// Iterate over every child of the group in an arbitrary order
+    pub(crate) fn children(&self) -> impl Iterator<Item = &T> + '_ {
+        self.metrics.values()
+    }
+
+    /// Every child of the group sorted by its key name, so that encoding doesn't depend on
+    /// the `HashMap`'s iteration order
+    pub(crate) fn sorted(&self) -> Vec<(Cow<'_, str>, &T)> {
+        let mut children: Vec<_> = self
+            .metrics
+            .iter()
+            .map(|(key, metric)| (key.key_name(), metric))
+            .collect();
+        children.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        children
+    }
 }
 
 pub trait Key: Hash + Eq {
-    fn key_name<'a>(&'a self) -> Cow<'a, str>;
+    fn key_name(&self) -> Cow<'_, str>;
 }
 
 impl<T> Key for T
 where
     T: AsRef<str> + Hash + Eq,
 {
-    fn key_name<'a>(&'a self) -> Cow<'a, str> {
+    fn key_name(&self) -> Cow<'_, str> {
         Cow::Borrowed(self.as_ref())
     }
 }
 
+/// Makes sure none of a group's shared labels collide with its bucket label
+fn validate_group_labels(bucket_label: &str, labels: &[Label]) -> Result<()> {
+    if let Some(label) = labels.iter().find(|label| label.name() == bucket_label) {
+        return Err(PromError::new(
+            format!(
+                "The label {} collides with the group's bucket label",
+                label.name()
+            ),
+            PromErrorKind::InvalidLabelName,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Creates the bucket label of the child with the key `key`
+fn key_label(bucket_label: Cow<'static, str>, key: &str) -> Label {
+    Label {
+        name: bucket_label,
+        value: Cow::Owned(key.to_owned()),
+    }
+}
+
+/// The children of a [`CounterGroup`], which are only locked when children can be added after
+/// construction, see [`CounterGroup::dynamic`]
+///
+/// [`CounterGroup`]: crate::CounterGroup
+/// [`CounterGroup::dynamic`]: crate::CounterGroup#method.dynamic
+#[derive(Debug)]
+enum Children<T, K: Key> {
+    Fixed(Group<T, K>),
+    Dynamic(RwLock<Group<T, K>>),
+}
+
+/// Borrowed [`Children`], holding the read lock of a dynamic group's children
+enum ChildrenRef<'a, T, K: Key> {
+    Fixed(&'a Group<T, K>),
+    Dynamic(RwLockReadGuard<'a, Group<T, K>>),
+}
+
+impl<T, K: Key> Deref for ChildrenRef<'_, T, K> {
+    type Target = Group<T, K>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Fixed(group) => group,
+            Self::Dynamic(group) => group,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CounterGroup<K: Key, Atomic: AtomicNum = AtomicU64> {
-    group: Group<Atomic, K>,
+    group: Children<Atomic, K>,
     descriptor: Descriptor,
     bucket_label: Cow<'static, str>,
 }
@@ -78,36 +178,91 @@ where
 
         // TODO: Check for duplicates
         Ok(Self {
-            group: Group::new(HashMap::from_iter(
-                keys.zip(iter::from_fn(|| Some(Atomic::new()))),
-            )),
+            group: Children::Fixed(Group::from_keys(keys, Atomic::new)),
             descriptor: Descriptor::new(group_name, group_help, Vec::new())?,
             bucket_label,
         })
     }
 
+    /// Create an empty group whose children are created the first time their key is used
+    /// instead of being fixed at construction, meant for keys that aren't known up front.
+    /// Children can be added from any thread, including while the group is being encoded
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::CounterGroup;
+    ///
+    /// let group: CounterGroup<String> =
+    ///     CounterGroup::dynamic("requests", "Requests per route", "route").unwrap();
+    /// assert!(group.is_empty());
+    ///
+    /// group.inc("/index.html".to_owned());
+    /// assert_eq!(group.get_or_create("/about.html".to_owned()), 0);
+    /// assert_eq!(group.len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the name or bucket label is invalid
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn dynamic<N, H, L>(group_name: N, group_help: H, bucket_label: L) -> Result<Self>
+    where
+        N: Into<Cow<'static, str>>,
+        H: AsRef<str>,
+        L: Into<Cow<'static, str>>,
+    {
+        let mut group = Self::new(group_name, group_help, bucket_label, iter::empty())?;
+        group.group = Children::Dynamic(RwLock::new(Group::new(HashMap::new())));
+
+        Ok(group)
+    }
+
+    /// Get the current value of the child with the key `key`, creating a zeroed child if the
+    /// group is [dynamic] and the child doesn't exist yet
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group isn't dynamic and the key doesn't exist, like [`CounterGroup::get`]
+    ///
+    /// [dynamic]: crate::CounterGroup#method.dynamic
+    /// [`CounterGroup::get`]: crate::CounterGroup#method.get
+    pub fn get_or_create(&self, key: K) -> Atomic::Type {
+        self.with_child(key, |child| child.get())
+    }
+
     pub fn inc(&self, key: K) {
-        self.group.get(key).inc();
+        self.with_child(key, |child| child.inc());
     }
 
     pub fn inc_by(&self, key: K, val: Atomic::Type) {
-        self.group.get(key).inc_by(val);
+        self.with_child(key, |child| child.inc_by(val));
     }
 
     pub fn set(&self, key: K, val: Atomic::Type) {
-        self.group.get(key).set(val);
+        self.with_child(key, |child| child.set(val));
     }
 
     pub fn get(&self, key: K) -> Atomic::Type {
-        self.group.get(key).get()
+        self.read().get(key).get()
     }
 
     pub fn try_get(&self, key: K) -> Option<Atomic::Type> {
-        self.group.try_get(key).map(|a| a.get())
+        self.read().try_get(key).map(|a| a.get())
     }
 
     pub fn clear(&self, key: K) {
-        self.group.get(key).clear();
+        self.read().get(key).clear();
+    }
+
+    /// The number of children the group currently holds
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     pub fn name(&self) -> &str {
@@ -121,54 +276,111 @@ where
     pub fn labels(&self) -> &[Label] {
         self.descriptor.labels()
     }
-}
 
-impl<K: Key, Atomic: AtomicNum> Collectable for &CounterGroup<K, Atomic> {
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()> {
-        writeln!(buf, "# HELP {} {}", self.name(), self.help())?;
-        writeln!(buf, "# TYPE {} counter", self.name())?;
-
-        for (bucket, value) in self.group.metrics.iter() {
-            write!(
-                buf,
-                "{}{{{}={:?}",
-                self.name(),
-                self.bucket_label,
-                bucket.key_name()
-            )?;
-
-            if !self.labels().is_empty() {
-                write!(buf, ",")?;
-
-                let mut labels = self.labels().iter();
-                let last = labels.next_back();
-
-                for label in labels {
-                    write!(buf, "{}={:?},", label.name(), label.value())?;
-                }
+    /// Set labels shared by every child of the group, emitted alongside each child's bucket label
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if any of the labels has the same name as the group's bucket label
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Result<Self> {
+        let labels = labels.into();
+        validate_group_labels(&self.bucket_label, &labels)?;
+
+        self.descriptor.labels = labels;
+        Ok(self)
+    }
 
-                if let Some(last) = last {
-                    write!(buf, "{}={:?}", last.name(), last.value())?;
-                }
+    fn read(&self) -> ChildrenRef<'_, Atomic, K> {
+        match &self.group {
+            Children::Fixed(group) => ChildrenRef::Fixed(group),
+            Children::Dynamic(group) => {
+                ChildrenRef::Dynamic(group.read().expect("a counter group's lock was poisoned"))
             }
+        }
+    }
+
+    /// Run `f` on the child with the key `key`, creating it first if the group is dynamic
+    fn with_child<R, F>(&self, key: K, f: F) -> R
+    where
+        F: FnOnce(&Atomic) -> R,
+    {
+        let lock = match &self.group {
+            Children::Fixed(group) => return f(group.get(key)),
+            Children::Dynamic(lock) => lock,
+        };
+
+        {
+            let group = lock.read().expect("a counter group's lock was poisoned");
+            if group.contains(&key) {
+                return f(group.get(key));
+            }
+        }
+
+        let mut group = lock.write().expect("a counter group's lock was poisoned");
+        f(group.get_or_insert_with(key, Atomic::new))
+    }
+}
 
+impl<K: Key, Atomic: AtomicNum> CounterGroup<K, Atomic> {
+    /// Writes the sample of every child, named `{{ name }}{{ suffix }}`
+    fn encode_children(
+        &self,
+        buf: &mut String,
+        name: &str,
+        suffix: &str,
+        options: &FormatOptions,
+    ) -> Result<()> {
+        // Children added while encoding wait for the read lock, so every sample comes from the
+        // same set of children
+        for (bucket, value) in self.read().sorted() {
+            write!(buf, "{}{}{{{}=", name, suffix, self.bucket_label)?;
+            escape_label_value(buf, &bucket);
+
+            write_labels(buf, self.labels(), true)?;
             write!(buf, "}} ")?;
 
-            <Atomic as AtomicNum>::format(value.get(), buf, false)?;
+            options.write_value(buf, value.get())?;
             writeln!(buf)?;
         }
 
         Ok(())
     }
+}
+
+impl<K: Key, Atomic: AtomicNum> Collectable for &CounterGroup<K, Atomic> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode_children(buf, self.name(), "", options)
+    }
+
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode_children(buf, counter_family(self.name()), "_total", options)
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "counter"
+    }
 
     fn descriptor(&self) -> &Descriptor {
         &self.descriptor
     }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        for (key, value) in self.read().sorted() {
+            let mut labels = vec![key_label(self.bucket_label.clone(), &key)];
+            labels.extend_from_slice(self.labels());
+
+            visit("", &labels, value.get().into());
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct HistogramGroup<K: Key, Atomic: AtomicNum = AtomicU64> {
     group: Group<HistogramCore<Atomic>, K>,
+    /// The bucket boundaries shared by every child, kept for groups without any children
+    buckets: Arc<[Atomic::Type]>,
     descriptor: Descriptor,
     bucket_label: Cow<'static, str>,
 }
@@ -200,13 +412,19 @@ where
             ));
         }
 
-        let buckets: Vec<Atomic::Type> = buckets.collect();
+        // Every child shares the same bucket boundaries instead of holding its own copy
+        let buckets: Arc<[Atomic::Type]> = buckets.collect();
+        if buckets.is_empty() {
+            return Err(PromError::new(
+                "Histograms must have buckets, but you didn't give any",
+                PromErrorKind::MissingBuckets,
+            ));
+        }
 
         // TODO: Check for duplicates
         Ok(Self {
-            group: Group::new(HashMap::from_iter(
-                keys.zip(iter::from_fn(|| Some(HistogramCore::new(buckets.clone())))),
-            )),
+            group: Group::from_keys(keys, || HistogramCore::new(buckets.clone())),
+            buckets,
             descriptor: Descriptor::new(group_name, group_help, Vec::new())?,
             bucket_label,
         })
@@ -220,6 +438,53 @@ where
         self.group.try_get(key)
     }
 
+    /// Combine every child into a single histogram by adding up their bucket counts, sums and
+    /// counts, which is possible since they all share the same buckets. The aggregate of a group
+    /// without children has the group's buckets, all of them empty
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::HistogramGroup;
+    ///
+    /// let latency: HistogramGroup<&str> = HistogramGroup::new(
+    ///     "latency",
+    ///     "Request latency per endpoint",
+    ///     "endpoint",
+    ///     vec!["/", "/about"].into_iter(),
+    ///     vec![10, 100].into_iter(),
+    /// )
+    /// .unwrap();
+    ///
+    /// latency.get("/").observe(5);
+    /// latency.get("/about").observe(50);
+    ///
+    /// let total = latency.aggregate();
+    /// assert_eq!(total.count(), 2);
+    /// assert_eq!(total.values(), &[1, 1]);
+    /// ```
+    pub fn aggregate(&self) -> HistogramSnapshot<Atomic::Type> {
+        let mut aggregate = HistogramSnapshot::empty(&self.buckets);
+        for child in self.group.children() {
+            aggregate.merge(&child.snapshot());
+        }
+
+        aggregate
+    }
+
+    /// Observe `val` into the histogram of `key`, see [`HistogramCore::observe_bucket`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] with [`PromErrorKind::BucketNotFound`] if `val` doesn't fall into any of the buckets
+    ///
+    /// [`HistogramCore::observe_bucket`]: crate::histogram::HistogramCore#method.observe_bucket
+    /// [`PromError`]: crate::PromError
+    /// [`PromErrorKind::BucketNotFound`]: crate::PromErrorKind#variant.BucketNotFound
+    pub fn observe_bucket(&self, key: K, val: Atomic::Type, bucket: Atomic::Type) -> Result<()> {
+        self.group.get(key).observe_bucket(val, bucket)
+    }
+
     pub fn clear(&self, key: K) {
         self.group.get(key).clear();
     }
@@ -235,78 +500,54 @@ where
     pub fn labels(&self) -> &[Label] {
         self.descriptor.labels()
     }
+
+    /// Set labels shared by every child of the group, emitted alongside each child's bucket label
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if any of the labels has the same name as the group's bucket label
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn with_labels(mut self, labels: impl Into<Vec<Label>>) -> Result<Self> {
+        let labels = labels.into();
+        validate_group_labels(&self.bucket_label, &labels)?;
+
+        self.descriptor.labels = labels;
+        Ok(self)
+    }
 }
 
 impl<K: Key, Atomic: AtomicNum> Collectable for &HistogramGroup<K, Atomic> {
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()> {
-        writeln!(buf, "# HELP {} {}", self.name(), self.help())?;
-        writeln!(buf, "# TYPE {} histogram", self.name())?;
-
-        let row = |buf: &mut String, name, bucket: &str| -> Result<()> {
-            write!(
-                buf,
-                "{}_{}{{{}={:?}",
-                self.name(),
-                name,
-                self.bucket_label,
-                bucket,
-            )?;
-
-            if !self.labels().is_empty() {
-                let mut labels = self.labels().iter();
-                let last = labels.next_back();
-
-                for label in labels {
-                    write!(buf, ",{}={:?}", label.name(), label.value())?;
-                }
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        let row = |out: &mut String, name, bucket: &str| -> Result<()> {
+            write!(out, "{}_{}{{{}=", self.name(), name, self.bucket_label)?;
+            escape_label_value(out, bucket);
 
-                if let Some(last) = last {
-                    write!(buf, "{}={:?}", last.name(), last.value())?;
-                }
-            }
-
-            write!(buf, "}} ")?;
+            write_labels(out, self.labels(), true)?;
+            write!(out, "}} ")?;
 
             Ok(())
         };
 
-        for (bucket, histogram) in self.group.metrics.iter() {
-            let bucket_name = bucket.key_name();
-
+        // Keep all of a key's series together
+        for (bucket_name, histogram) in self.group.sorted() {
             row(buf, "sum", &bucket_name)?;
-            Atomic::format(histogram.get_sum(), buf, false)?;
+            options.write_value(buf, histogram.get_sum())?;
             writeln!(buf)?;
 
             row(buf, "count", &bucket_name)?;
             <AtomicU64 as AtomicNum>::format(histogram.get_count(), buf, false)?;
             writeln!(buf)?;
 
-            for (i, bucket) in histogram.buckets.iter().enumerate() {
-                write!(
-                    buf,
-                    "{}_bucket{{{}={:?},le=",
-                    self.name(),
-                    self.bucket_label,
-                    &bucket_name,
-                )?;
-                Atomic::format(*bucket, buf, true)?;
-
-                if !self.labels().is_empty() {
-                    let mut labels = self.labels().iter();
-                    let last = labels.next_back();
-
-                    for label in labels {
-                        write!(buf, ",{}={:?}", label.name(), label.value())?;
-                    }
-
-                    if let Some(last) = last {
-                        write!(buf, "{}={:?}", last.name(), last.value())?;
-                    }
-                }
-
+            for (bucket, count) in histogram.bucket_counts() {
+                write!(buf, "{}_bucket{{{}=", self.name(), self.bucket_label)?;
+                escape_label_value(buf, &bucket_name);
+                write!(buf, ",le=")?;
+                write_le::<Atomic>(buf, bucket)?;
+                write_labels(buf, self.labels(), true)?;
                 write!(buf, "}} ")?;
 
-                Atomic::format(histogram.values[i].get(), buf, false)?;
+                <AtomicU64 as AtomicNum>::format(count, buf, false)?;
                 writeln!(buf)?;
             }
         }
@@ -314,9 +555,31 @@ impl<K: Key, Atomic: AtomicNum> Collectable for &HistogramGroup<K, Atomic> {
         Ok(())
     }
 
+    fn metric_type(&self) -> &'static str {
+        "histogram"
+    }
+
     fn descriptor(&self) -> &Descriptor {
         &self.descriptor
     }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        for (key, histogram) in self.group.sorted() {
+            let mut labels = vec![key_label(self.bucket_label.clone(), &key)];
+            labels.extend_from_slice(self.labels());
+
+            visit("_sum", &labels, histogram.get_sum().into());
+            visit("_count", &labels, histogram.get_count().into());
+
+            for (bucket, count) in histogram.bucket_counts() {
+                if let Ok(le) = le_label::<Atomic>(bucket) {
+                    labels.push(le);
+                    visit("_bucket", &labels, count.into());
+                    labels.pop();
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -335,7 +598,7 @@ mod tests {
     }
 
     impl Key for GroupKey {
-        fn key_name<'a>(&'a self) -> Cow<'a, str> {
+        fn key_name(&self) -> Cow<'_, str> {
             match self {
                 Self::A => "a",
                 Self::B => "b",
@@ -395,6 +658,93 @@ mod tests {
         assert_eq!(group.get("key_one"), 1);
     }
 
+    #[test]
+    fn counter_group_labels() {
+        let group: CounterGroup<&'static str> = CounterGroup::new(
+            "counters",
+            "A group of counters",
+            "key",
+            vec!["first"].into_iter(),
+        )
+        .unwrap()
+        .with_labels(vec![
+            Label::new("service", "x").unwrap(),
+            Label::new("region", "eu").unwrap(),
+        ])
+        .unwrap();
+
+        group.inc("first");
+
+        let mut buf = String::new();
+        (&group)
+            .encode_text(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(
+            buf,
+            "# HELP counters A group of counters\n\
+             # TYPE counters counter\n\
+             counters{key=\"first\",service=\"x\",region=\"eu\"} 1\n",
+        );
+    }
+
+    #[test]
+    fn counter_group_stable_order() {
+        let encode = |keys: Vec<&'static str>| {
+            let group: CounterGroup<&'static str> =
+                CounterGroup::new("counters", "A group of counters", "key", keys.into_iter())
+                    .unwrap();
+            group.inc_by("charlie", 3);
+            group.inc("alpha");
+
+            let mut buf = String::new();
+            (&group)
+                .encode_samples(&mut buf, &FormatOptions::new())
+                .unwrap();
+            buf
+        };
+
+        let expected = "counters{key=\"alpha\"} 1\n\
+                        counters{key=\"bravo\"} 0\n\
+                        counters{key=\"charlie\"} 3\n\
+                        counters{key=\"delta\"} 0\n";
+
+        // Every group gets its own randomly seeded `HashMap`, so repeating this exercises
+        // many different iteration orders
+        for _ in 0..16 {
+            assert_eq!(encode(vec!["delta", "alpha", "charlie", "bravo"]), expected);
+            assert_eq!(encode(vec!["alpha", "bravo", "charlie", "delta"]), expected);
+        }
+    }
+
+    #[test]
+    fn bucket_label_collision() {
+        let counters = CounterGroup::<&'static str>::new(
+            "counters",
+            "A group of counters",
+            "key",
+            vec!["first"].into_iter(),
+        )
+        .unwrap()
+        .with_labels(vec![
+            Label::new("service", "x").unwrap(),
+            Label::new("key", "y").unwrap(),
+        ])
+        .unwrap_err();
+        assert_eq!(counters.kind(), PromErrorKind::InvalidLabelName);
+
+        let histograms = HistogramGroup::<&'static str>::new(
+            "histogram_group",
+            "It's a group of histograms",
+            "endpoint",
+            vec!["index"].into_iter(),
+            vec![1u64].into_iter(),
+        )
+        .unwrap()
+        .with_labels(vec![Label::new("endpoint", "/").unwrap()])
+        .unwrap_err();
+        assert_eq!(histograms.kind(), PromErrorKind::InvalidLabelName);
+    }
+
     #[test]
     fn histogram_group() {
         let group: HistogramGroup<&'static str> = HistogramGroup::new(
@@ -416,4 +766,298 @@ mod tests {
         assert_eq!(group.get("bucket3").values(), vec![0, 1, 0, 0]);
         assert_eq!(group.get("bucket4").values(), vec![1, 0, 0, 0]);
     }
+
+    #[test]
+    fn histogram_group_shared_buckets() {
+        let keys: Vec<String> = (0..1000).map(|key| format!("key_{}", key)).collect();
+        let group: HistogramGroup<&str> = HistogramGroup::new(
+            "histogram_group",
+            "It's a group of histograms",
+            "key",
+            keys.iter().map(String::as_str),
+            vec![1u64, 2, 3, 4].into_iter(),
+        )
+        .unwrap();
+
+        let first = &group.get("key_0").buckets;
+        // One for each child and one held by the group itself
+        assert_eq!(Arc::strong_count(first), 1001);
+        assert!(keys
+            .iter()
+            .all(|key| Arc::ptr_eq(first, &group.get(key).buckets)));
+        assert_eq!(group.get("key_999").buckets(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn presized_group() {
+        let keys: Vec<String> = (0..10_000).map(|key| format!("key_{}", key)).collect();
+        let group: CounterGroup<&str> = CounterGroup::new(
+            "counter_group",
+            "It's a group of counters",
+            "key",
+            keys.iter().map(String::as_str),
+        )
+        .unwrap();
+
+        // The map was sized once up front instead of growing while the keys were inserted
+        let metrics = &group.read().metrics;
+        assert_eq!(metrics.len(), 10_000);
+        assert_eq!(
+            metrics.capacity(),
+            HashMap::<&str, AtomicU64>::with_capacity(10_000).capacity(),
+        );
+
+        group.inc("key_9999");
+        assert_eq!(group.get("key_9999"), 1);
+    }
+
+    #[test]
+    fn histogram_group_observe_bucket() {
+        let group: HistogramGroup<&'static str> = HistogramGroup::new(
+            "histogram_group",
+            "It's a group of histograms",
+            "key",
+            vec!["first", "second"].into_iter(),
+            vec![1u64, 2, 3].into_iter(),
+        )
+        .unwrap();
+
+        group.observe_bucket("first", 2, 2).unwrap();
+        assert_eq!(group.get("first").values(), vec![0, 1, 0]);
+        assert_eq!(group.get("first").get_count(), 1);
+
+        let err = group.observe_bucket("second", 4, 4).unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::BucketNotFound);
+        assert_eq!(group.get("second").get_count(), 0);
+    }
+
+    #[test]
+    fn histogram_group_label_commas() {
+        let group: HistogramGroup<&'static str> = HistogramGroup::new(
+            "histogram_group",
+            "It's a group of histograms",
+            "endpoint",
+            vec!["index"].into_iter(),
+            vec![1u64].into_iter(),
+        )
+        .unwrap()
+        .with_labels(vec![
+            Label::new("foo", "a").unwrap(),
+            Label::new("bar", "b").unwrap(),
+        ])
+        .unwrap();
+
+        group.get("index").observe(1);
+
+        let mut buf = String::new();
+        (&group)
+            .encode_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+
+        assert_eq!(
+            buf,
+            "histogram_group_sum{endpoint=\"index\",foo=\"a\",bar=\"b\"} 1\n\
+             histogram_group_count{endpoint=\"index\",foo=\"a\",bar=\"b\"} 1\n\
+             histogram_group_bucket{endpoint=\"index\",le=\"1\",foo=\"a\",bar=\"b\"} 1\n\
+             histogram_group_bucket{endpoint=\"index\",le=\"+Inf\",foo=\"a\",bar=\"b\"} 1\n",
+        );
+
+        // Sample values put `le` after every other label, like a single histogram's
+        let names: Vec<_> = (&group)
+            .current_values()
+            .into_iter()
+            .map(|(suffix, labels, _)| {
+                let names: Vec<_> = labels.iter().map(|label| label.name().to_owned()).collect();
+                (suffix, names.join(","))
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("_sum", "endpoint,foo,bar".to_owned()),
+                ("_count", "endpoint,foo,bar".to_owned()),
+                ("_bucket", "endpoint,foo,bar,le".to_owned()),
+                ("_bucket", "endpoint,foo,bar,le".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn histogram_group_stable_order() {
+        let group: HistogramGroup<&'static str> = HistogramGroup::new(
+            "histogram_group",
+            "It's a group of histograms",
+            "key",
+            vec!["delta", "alpha", "charlie", "bravo"].into_iter(),
+            vec![1u64, 2].into_iter(),
+        )
+        .unwrap();
+
+        let mut buf = String::new();
+        (&group)
+            .encode_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+
+        let keys: Vec<&str> = buf
+            .lines()
+            .map(|line| {
+                let start = line.find("key=\"").unwrap() + 5;
+                let end = start + line[start..].find('"').unwrap();
+                &line[start..end]
+            })
+            .collect();
+
+        // A sum, a count, two buckets and the `+Inf` bucket per key
+        let expected: Vec<&str> = ["alpha", "bravo", "charlie", "delta"]
+            .iter()
+            .flat_map(|key| vec![*key; 5])
+            .collect();
+
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn dynamic_counter_group() {
+        use std::thread;
+
+        let group: CounterGroup<String> =
+            CounterGroup::dynamic("dynamic_requests", "Requests per route", "route").unwrap();
+        assert!(group.is_empty());
+
+        thread::scope(|scope| {
+            for thread in 0..4 {
+                let group = &group;
+                scope.spawn(move || {
+                    for route in 0..100 {
+                        group.inc(format!("/route_{:03}", route));
+                        group.inc_by(format!("/thread_{}", thread), 2);
+                    }
+                });
+            }
+
+            // Encoding while the group grows always sees whole samples in key order
+            for _ in 0..10 {
+                let mut buf = String::new();
+                (&group)
+                    .encode_samples(&mut buf, &FormatOptions::new())
+                    .unwrap();
+
+                let keys: Vec<&str> = buf
+                    .lines()
+                    .map(|line| line.split(' ').next().unwrap())
+                    .collect();
+                let mut sorted = keys.clone();
+                sorted.sort_unstable();
+                assert_eq!(keys, sorted);
+            }
+        });
+
+        assert_eq!(group.len(), 104);
+        assert!((0..100).all(|route| group.get(format!("/route_{:03}", route)) == 4));
+        assert!((0..4).all(|thread| group.get(format!("/thread_{}", thread)) == 200));
+
+        let snapshot = (&group).current_values();
+        assert_eq!(snapshot.len(), 104);
+        assert_eq!(
+            snapshot
+                .iter()
+                .map(|(_, _, value)| value.as_f64())
+                .sum::<f64>(),
+            1200.0,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't exist")]
+    fn fixed_counter_group_doesnt_grow() {
+        let group: CounterGroup<&str> =
+            CounterGroup::new("fixed", "A fixed group", "key", vec!["known"].into_iter()).unwrap();
+
+        group.inc("unknown");
+    }
+
+    #[test]
+    fn histogram_group_aggregate() {
+        let group: HistogramGroup<&str> = HistogramGroup::new(
+            "aggregated",
+            "Aggregated histograms",
+            "key",
+            vec!["first", "second", "third"].into_iter(),
+            vec![10, 100].into_iter(),
+        )
+        .unwrap();
+
+        group.get("first").observe(5);
+        group.get("first").observe(50);
+        group.get("second").observe(50);
+        group.get("second").observe(500);
+        group.get("third").observe(7);
+
+        let aggregate = group.aggregate();
+        assert_eq!(aggregate.buckets(), &[10, 100]);
+        assert_eq!(aggregate.values(), &[2, 2]);
+        assert_eq!(aggregate.count(), 5);
+        assert_eq!(aggregate.sum(), 612);
+        assert_eq!(
+            aggregate.bucket_counts(),
+            vec![(10, 2), (100, 4), (u64::MAX, 5)]
+        );
+
+        let children = ["first", "second", "third"].map(|key| group.get(key).snapshot());
+        assert_eq!(
+            aggregate.count(),
+            children.iter().map(HistogramSnapshot::count).sum::<u64>(),
+        );
+        assert_eq!(
+            aggregate.sum(),
+            children.iter().map(HistogramSnapshot::sum).sum::<u64>(),
+        );
+
+        let empty: HistogramGroup<&str> = HistogramGroup::new(
+            "empty",
+            "No children",
+            "key",
+            Vec::new().into_iter(),
+            vec![10].into_iter(),
+        )
+        .unwrap();
+        let empty_aggregate = empty.aggregate();
+        assert_eq!(empty_aggregate.buckets(), &[10]);
+        assert_eq!(empty_aggregate.values(), &[0]);
+        assert_eq!(
+            empty_aggregate.bucket_counts(),
+            vec![(10, 0), (u64::MAX, 0)]
+        );
+
+        // Children near the cap saturate instead of overflowing
+        let full: HistogramGroup<&str> = HistogramGroup::new(
+            "full",
+            "Saturated histograms",
+            "key",
+            vec!["first", "second"].into_iter(),
+            vec![10].into_iter(),
+        )
+        .unwrap();
+        full.get("first").observe_n(u64::MAX / 2, u64::MAX - 1);
+        full.get("second").observe_n(5, 2);
+
+        let saturated = full.aggregate();
+        assert_eq!(saturated.values(), &[2]);
+        assert_eq!(saturated.count(), u64::MAX);
+        assert_eq!(saturated.sum(), u64::MAX);
+    }
+
+    #[test]
+    fn empty_histogram_group_buckets() {
+        let err = HistogramGroup::<&'static str>::new(
+            "empty_histogram_group",
+            "It's a group of histograms without buckets",
+            "endpoint",
+            vec!["index"].into_iter(),
+            Vec::<u64>::new().into_iter(),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.kind(), PromErrorKind::MissingBuckets);
+    }
 }
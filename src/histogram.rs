@@ -1,11 +1,22 @@
 use crate::{
-    atomics::{AtomicF64, AtomicNum},
+    atomics::{AtomicF64, AtomicNum, FormatOptions, MetricValue, Num},
+    counter::write_sample_name,
+    encoder::counter_family,
     error::{PromError, PromErrorKind, Result},
-    label::Label,
+    label::{write_labels, DebugLabels, Label},
     registry::{Collectable, Descriptor},
-    timer::Timer,
+    timer::{TimeUnit, Timer},
+};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Write},
+    iter,
+    sync::{atomic::AtomicU64, Arc, Mutex, RwLock},
+    thread::{self, ThreadId},
+    time::{Duration, Instant, SystemTime},
 };
-use std::{borrow::Cow, cell::RefCell, fmt::Write, iter, sync::atomic::AtomicU64};
 
 /// The default [`Histogram`] buckets. Meant to measure the response time in seconds of network operations
 pub const DEFAULT_BUCKETS: &[f64; 12] = &[
@@ -23,12 +34,75 @@ pub const DEFAULT_BUCKETS: &[f64; 12] = &[
     f64::INFINITY,
 ];
 
+/// [`DEFAULT_BUCKETS`] in milliseconds, meant for response times that are measured in milliseconds
+pub const DEFAULT_BUCKETS_MS: &[f64; 12] = &[
+    5.0,
+    10.0,
+    25.0,
+    50.0,
+    100.0,
+    250.0,
+    500.0,
+    1000.0,
+    2500.0,
+    5000.0,
+    10_000.0,
+    f64::INFINITY,
+];
+
+/// Buckets for sizes in bytes, every power of two from 128 bytes up to 1GiB
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{histogram::{bytes_buckets, HistogramBuilder}, AtomicF64};
+///
+/// let payload_sizes = HistogramBuilder::<AtomicF64>::new()
+///     .name("payload_size_bytes")
+///     .help("The size of request payloads")
+///     .with_buckets(bytes_buckets())
+///     .build()
+///     .unwrap();
+///
+/// payload_sizes.observe(4096.0);
+/// ```
+pub fn bytes_buckets() -> Vec<f64> {
+    (7..=30)
+        .map(|exp| (1u64 << exp) as f64)
+        .chain(iter::once(f64::INFINITY))
+        .collect()
+}
+
+/// Multiply every bucket of `buckets` by `factor`, letting [`DEFAULT_BUCKETS`] be used for
+/// measurements at a different scale. An infinite bucket stays infinite
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::histogram::{scaled_buckets, DEFAULT_BUCKETS};
+///
+/// // Response times in microseconds
+/// let buckets = scaled_buckets(DEFAULT_BUCKETS, 1_000_000.0);
+/// assert_eq!(buckets[0], 5000.0);
+/// ```
+pub fn scaled_buckets(buckets: &[f64], factor: f64) -> Vec<f64> {
+    buckets.iter().map(|bucket| bucket * factor).collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HistogramBuilder<Atomic: AtomicNum = AtomicF64> {
     name: Option<Cow<'static, str>>,
     help: Option<Cow<'static, str>>,
     labels: Option<Vec<Label>>,
     buckets: Option<Vec<Atomic::Type>>,
+    time_unit: TimeUnit,
+    unit: Option<Cow<'static, str>>,
+    /// The first bucket given to [`HistogramBuilder::float_buckets`] that didn't fit the
+    /// histogram's type, reported by [`HistogramBuilder::build`]
+    ///
+    /// [`HistogramBuilder::float_buckets`]: crate::histogram::HistogramBuilder#method.float_buckets
+    /// [`HistogramBuilder::build`]: crate::histogram::HistogramBuilder#method.build
+    bucket_error: Option<PromError>,
 }
 
 impl<Atomic: AtomicNum> HistogramBuilder<Atomic> {
@@ -38,6 +112,9 @@ impl<Atomic: AtomicNum> HistogramBuilder<Atomic> {
             help: None,
             labels: None,
             buckets: None,
+            time_unit: TimeUnit::Seconds,
+            unit: None,
+            bucket_error: None,
         }
     }
 
@@ -71,6 +148,45 @@ impl<Atomic: AtomicNum> HistogramBuilder<Atomic> {
         self
     }
 
+    /// Add buckets computed with float math, converting them to the histogram's type. Integer
+    /// histograms truncate fractional boundaries, and [`HistogramBuilder::build`] fails if any
+    /// boundary is `NaN` or outside of the type's range instead of silently saturating it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+    ///     .name("payload_size_bytes")
+    ///     .help("Payload sizes")
+    ///     .float_buckets((1..=4).map(|exp| 10f64.powi(exp)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(histogram.buckets(), &[10, 100, 1000, 10000]);
+    /// ```
+    ///
+    /// [`HistogramBuilder::build`]: crate::histogram::HistogramBuilder#method.build
+    pub fn float_buckets(mut self, buckets: impl IntoIterator<Item = f64>) -> Self {
+        for float in buckets {
+            match Atomic::Type::from_f64(float) {
+                Some(bucket) => self = self.bucket(bucket),
+                None => {
+                    self.bucket_error.get_or_insert_with(|| {
+                        PromError::new(
+                            format!("The bucket {:?} is out of the histogram's range", float),
+                            PromErrorKind::BucketOutOfRange,
+                        )
+                    });
+                }
+            }
+        }
+
+        self
+    }
+
     pub fn bucket(mut self, bucket: impl Into<Atomic::Type>) -> Self {
         if let Some(ref mut buckets) = self.buckets {
             buckets.push(bucket.into());
@@ -81,77 +197,218 @@ impl<Atomic: AtomicNum> HistogramBuilder<Atomic> {
         self
     }
 
+    /// Set the unit that timers record elapsed time in, defaults to [`TimeUnit::Seconds`].
+    /// Useful for integer histograms that need to track sub-second durations
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{histogram::{Histogram, HistogramBuilder}, TimeUnit};
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+    ///     .name("request_latency_ms")
+    ///     .help("Request latency in milliseconds")
+    ///     .with_buckets(vec![10, 50, 100, 500, u64::MAX])
+    ///     .time_unit(TimeUnit::Milliseconds)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let timer = histogram.start_timer();
+    /// timer.observe();
+    /// ```
+    ///
+    /// [`TimeUnit::Seconds`]: crate::TimeUnit#variant.Seconds
+    pub fn time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.time_unit = time_unit;
+        self
+    }
+
+    /// Set the histogram's OpenMetrics unit, emitted as a `# UNIT` line by the [`OpenMetricsEncoder`].
+    /// The histogram's name must end with `_{unit}`
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    pub fn unit(mut self, unit: impl Into<Cow<'static, str>>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
     pub fn build(self) -> Result<Histogram<Atomic>> {
         let name = self.name.ok_or_else(|| {
             PromError::new(
                 "Histograms must have a name, but you didn't give one",
-                PromErrorKind::MissingComponent,
+                PromErrorKind::MissingName,
             )
         })?;
         let help = self.help.ok_or_else(|| {
             PromError::new(
                 "Histograms must have a help, but you didn't give one",
-                PromErrorKind::MissingComponent,
+                PromErrorKind::MissingHelp,
             )
         })?;
+        if let Some(err) = self.bucket_error {
+            return Err(err);
+        }
         let buckets = self.buckets.ok_or_else(|| {
             PromError::new(
                 "Histograms must have buckets, but you didn't give any",
-                PromErrorKind::MissingComponent,
+                PromErrorKind::MissingBuckets,
             )
         })?;
         let labels = self.labels.unwrap_or_default();
 
+        // `Label::new` already refuses `le`, but the bucket label would be emitted twice if one slipped through
+        if labels.iter().any(|label| label.name() == "le") {
+            return Err(PromError::new(
+                "Histograms cannot have an `le` label, it's reserved for the bucket boundaries",
+                PromErrorKind::InvalidLabelName,
+            ));
+        }
+
         if buckets.is_empty() {
             Err(PromError::new(
                 "Histograms cannot have empty buckets",
-                PromErrorKind::MissingComponent,
+                PromErrorKind::MissingBuckets,
+            ))
+        } else if let Some(duplicate) = buckets
+            .iter()
+            .enumerate()
+            .find(|(idx, bucket)| buckets[..*idx].contains(bucket))
+            .map(|(_, bucket)| bucket)
+        {
+            // Comparing with `PartialEq` means that `-0.0` and `0.0` count as duplicates
+            Err(PromError::new(
+                format!(
+                    "Histograms cannot have duplicated buckets, but {:?} was given twice",
+                    duplicate
+                ),
+                PromErrorKind::DuplicatedBucket,
             ))
         } else {
+            let mut core = HistogramCore::new(buckets);
+            core.time_unit = self.time_unit;
+
+            let mut descriptor = Descriptor::new(name, help, labels)?;
+            if let Some(unit) = self.unit {
+                descriptor.set_unit(unit, false)?;
+            }
+
             Ok(Histogram {
-                descriptor: Descriptor::new(name, help, labels)?,
-                core: HistogramCore::new(buckets),
+                descriptor,
+                core,
+                exemplars: Mutex::new(Vec::new()),
             })
         }
     }
 }
 
+impl<Atomic: AtomicNum> Default for HistogramBuilder<Atomic> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug)]
 pub struct HistogramCore<Atomic: AtomicNum> {
-    pub(crate) buckets: Vec<Atomic::Type>,
-    pub(crate) values: Vec<Atomic>,
+    /// The bucket boundaries, shared between every child of a [`HistogramGroup`]
+    ///
+    /// [`HistogramGroup`]: crate::HistogramGroup
+    pub(crate) buckets: Arc<[Atomic::Type]>,
+    /// The number of observations that fell into each bucket, these aren't cumulative
+    pub(crate) values: Vec<AtomicU64>,
     count: AtomicU64,
     sum: Atomic,
+    pub(crate) time_unit: TimeUnit,
 }
 
 impl<Atomic: AtomicNum> HistogramCore<Atomic> {
-    pub(crate) fn new(buckets: Vec<Atomic::Type>) -> Self {
+    pub(crate) fn new(buckets: impl Into<Arc<[Atomic::Type]>>) -> Self {
+        let buckets = buckets.into();
+
         Self {
-            values: iter::from_fn(|| Some(Atomic::new()))
+            values: iter::from_fn(|| Some(AtomicU64::new(0)))
                 .take(buckets.len())
                 .collect(),
             buckets,
             count: AtomicU64::new(0),
             sum: Atomic::new(),
+            time_unit: TimeUnit::Seconds,
         }
     }
 
     pub fn observe(&self, val: Atomic::Type) {
-        if let Some(idx) = self.buckets.iter().position(|b| val <= *b) {
-            self.values[idx].inc();
+        self.observe_indexed(val);
+    }
+
+    /// Observe `val`, rejecting `NaN` instead of letting it poison the histogram's sum
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] without observing anything if `val` is `NaN`
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn try_observe(&self, val: Atomic::Type) -> Result<()> {
+        // `NaN` is the only value that isn't equal to itself
+        #[allow(clippy::eq_op)]
+        if val != val {
+            return Err(PromError::new(
+                "Histograms can't observe NaN",
+                PromErrorKind::InvalidObservation,
+            ));
         }
 
-        self.count.inc();
-        self.sum.inc_by(val);
+        self.observe(val);
+        Ok(())
     }
 
-    pub fn clear(&self) {
-        for val in self.values.iter() {
-            val.clear();
+    /// Observe `val` like [`HistogramCore::try_observe`], but also reject negative values for
+    /// histograms measuring things that can't be negative, like durations or sizes
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] without observing anything if `val` is `NaN` or negative
+    ///
+    /// [`HistogramCore::try_observe`]: crate::histogram::HistogramCore#method.try_observe
+    /// [`PromError`]: crate::PromError
+    pub fn try_observe_non_negative(&self, val: Atomic::Type) -> Result<()> {
+        if val < Atomic::Type::default() {
+            return Err(PromError::new(
+                format!("Histograms can't observe the negative value {:?}", val),
+                PromErrorKind::InvalidObservation,
+            ));
         }
 
-        self.count.clear();
-        self.sum.clear();
+        self.try_observe(val)
+    }
+
+    /// Observe `val`, returning the index of the smallest bucket it fell into or `None` if it's
+    /// larger than every bucket and was only counted by the implicit `+Inf` bucket
+    pub fn observe_indexed(&self, val: Atomic::Type) -> Option<usize> {
+        self.counts().observe_n(val, 1)
+    }
+
+    /// Observe `val` `n` times at once, for importing pre-aggregated data
+    pub fn observe_n(&self, val: Atomic::Type, n: u64) {
+        if n != 0 {
+            self.counts().observe_n(val, n);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.clear_buckets();
+        self.clear_aggregate();
+    }
+
+    /// Reset every bucket's count while keeping the histogram's sum and count. The `+Inf` bucket
+    /// is derived from the count, so it keeps its value
+    pub fn clear_buckets(&self) {
+        self.counts().clear_buckets();
+    }
+
+    /// Reset the histogram's sum and count while keeping every bucket's count. The `+Inf` bucket
+    /// is derived from the count, but it keeps at least the cumulative count of the other buckets
+    pub fn clear_aggregate(&self) {
+        self.counts().clear_aggregate();
     }
 
     pub fn get_count(&self) -> u64 {
@@ -162,11 +419,37 @@ impl<Atomic: AtomicNum> HistogramCore<Atomic> {
         self.sum.get()
     }
 
+    /// Add every bucket count, the sum and the count of `other` to this histogram
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the histograms' bucket boundaries differ
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn merge_from(&self, other: &Self) -> Result<()> {
+        if self.buckets != other.buckets {
+            return Err(PromError::new(
+                format!(
+                    "Can't merge histograms with the buckets {:?} and {:?}",
+                    self.buckets, other.buckets,
+                ),
+                PromErrorKind::MismatchedBuckets,
+            ));
+        }
+
+        self.counts().add(
+            other.values_iter().enumerate(),
+            other.get_count(),
+            other.get_sum(),
+        );
+
+        Ok(())
+    }
+
     pub fn observe_bucket(&self, val: Atomic::Type, bucket: Atomic::Type) -> Result<()> {
-        if let Some(idx) = self.buckets.iter().position(|b| val <= *b) {
-            self.values[idx].inc();
-            self.count.inc();
-            self.sum.inc_by(val);
+        let counts = self.counts();
+        if let Some(idx) = counts.bucket_index(val) {
+            counts.add(Some((idx, 1)), 1, val);
 
             Ok(())
         } else {
@@ -177,23 +460,326 @@ impl<Atomic: AtomicNum> HistogramCore<Atomic> {
         }
     }
 
-    pub fn local<'a>(&'a self) -> LocalHistogram<'a, Atomic> {
+    pub fn local(&self) -> LocalHistogram<'_, Atomic> {
         LocalHistogram::new(self)
     }
 
+    pub fn local_pool(&self, flush_interval: Duration) -> HistogramLocalPool<'_, Atomic> {
+        HistogramLocalPool::new(self, flush_interval)
+    }
+
     pub fn buckets(&self) -> &[Atomic::Type] {
         &self.buckets
     }
 
-    pub fn values(&self) -> Vec<Atomic::Type> {
-        self.values.iter().map(|v| v.get()).collect()
+    /// Get the number of observations that fell into each bucket. These counts aren't
+    /// cumulative, see [`HistogramCore::bucket_counts`] for the cumulative counts
+    ///
+    /// [`HistogramCore::bucket_counts`]: crate::histogram::HistogramCore#method.bucket_counts
+    pub fn values(&self) -> Vec<u64> {
+        self.values_iter().collect()
+    }
+
+    /// Iterate over the current value of each bucket without allocating
+    pub fn values_iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.values.iter().map(|v| v.get())
+    }
+
+    /// Get the current value of the bucket at `idx`, returning `None` if it's out of bounds
+    pub fn value_at(&self, idx: usize) -> Option<u64> {
+        self.values.get(idx).map(|v| v.get())
+    }
+
+    /// Get each bucket's upper boundary paired with the cumulative number of observations
+    /// less than or equal to it. The final entry is always the `+Inf` bucket (`f64::INFINITY`,
+    /// `u64::MAX` or `i64::MAX`) holding the total count, it's added if the buckets don't already end with it
+    pub fn bucket_counts(&self) -> Vec<(Atomic::Type, u64)> {
+        self.counts().bucket_counts()
+    }
+
+    /// Copy the histogram's current bucket counts, sum and count
+    pub fn snapshot(&self) -> HistogramSnapshot<Atomic::Type> {
+        HistogramSnapshot {
+            buckets: self.buckets.to_vec(),
+            values: self.values(),
+            sum: self.get_sum(),
+            count: self.get_count(),
+        }
+    }
+
+    fn counts(&self) -> HistogramCounts<'_, Atomic> {
+        HistogramCounts {
+            buckets: &self.buckets,
+            values: &self.values,
+            count: &self.count,
+            sum: &self.sum,
+        }
     }
 }
 
-#[derive(Debug)]
+/// The buckets and counters of a histogram, shared by [`HistogramCore`] and [`ArrayHistogram`]
+/// so that both observe and clear the same way
+///
+/// [`HistogramCore`]: crate::histogram::HistogramCore
+/// [`ArrayHistogram`]: crate::histogram::ArrayHistogram
+struct HistogramCounts<'a, Atomic: AtomicNum> {
+    buckets: &'a [Atomic::Type],
+    /// The number of observations that fell into each bucket, these aren't cumulative
+    values: &'a [AtomicU64],
+    count: &'a AtomicU64,
+    sum: &'a Atomic,
+}
+
+impl<Atomic: AtomicNum> HistogramCounts<'_, Atomic> {
+    /// The index of the smallest bucket `val` falls into, `None` if it's only in the `+Inf` bucket
+    fn bucket_index(&self, val: Atomic::Type) -> Option<usize> {
+        self.buckets.iter().position(|b| val <= *b)
+    }
+
+    /// Observe `val` `n` times, returning the index of the smallest bucket it fell into
+    fn observe_n(&self, val: Atomic::Type, n: u64) -> Option<usize> {
+        let idx = self.bucket_index(val);
+        self.add(
+            idx.map(|idx| (idx, n)),
+            n,
+            val.saturating_mul(Atomic::Type::from_u64(n)),
+        );
+
+        idx
+    }
+
+    /// Add `count` observations summing to `sum`, where `bucket_values` holds how many of them
+    /// fell into each bucket index. The buckets, count and sum all saturate instead of wrapping so
+    /// that the buckets stay cumulative and the sum never jumps backwards
+    fn add(
+        &self,
+        bucket_values: impl IntoIterator<Item = (usize, u64)>,
+        count: u64,
+        sum: Atomic::Type,
+    ) {
+        for (idx, value) in bucket_values {
+            self.values[idx].saturating_inc_by(value);
+        }
+        self.count.saturating_inc_by(count);
+        self.sum.saturating_inc_by(sum);
+    }
+
+    fn clear_buckets(&self) {
+        for val in self.values.iter() {
+            val.clear();
+        }
+    }
+
+    fn clear_aggregate(&self) {
+        self.count.clear();
+        self.sum.clear();
+    }
+
+    fn bucket_counts(&self) -> Vec<(Atomic::Type, u64)> {
+        cumulative_counts(
+            self.buckets,
+            self.values.iter().map(|value| value.get()),
+            self.count.get(),
+        )
+    }
+}
+
+/// Pairs each bucket with the cumulative number of observations less than or equal to it,
+/// adding the `+Inf` bucket holding `count` if the buckets don't already end with it. The `+Inf`
+/// bucket never holds less than the last bucket, even while the count was reset without them
+fn cumulative_counts<T: Num>(
+    buckets: &[T],
+    values: impl Iterator<Item = u64>,
+    count: u64,
+) -> Vec<(T, u64)> {
+    let mut cumulative: u64 = 0;
+    let mut counts: Vec<(T, u64)> = buckets
+        .iter()
+        .zip(values)
+        .map(|(&bucket, value)| {
+            cumulative = cumulative.saturating_add(value);
+            (bucket, cumulative)
+        })
+        .collect();
+
+    if counts.last().map(|&(bucket, _)| bucket) != Some(T::UPPER_BOUND) {
+        counts.push((T::UPPER_BOUND, count.max(cumulative)));
+    }
+
+    counts
+}
+
+/// A point in time copy of a histogram's buckets, sum and count, see [`HistogramCore::snapshot`]
+/// and [`HistogramGroup::aggregate`]
+///
+/// [`HistogramCore::snapshot`]: crate::histogram::HistogramCore#method.snapshot
+/// [`HistogramGroup::aggregate`]: crate::HistogramGroup#method.aggregate
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HistogramSnapshot<T> {
+    buckets: Vec<T>,
+    values: Vec<u64>,
+    sum: T,
+    count: u64,
+}
+
+impl<T: Num> HistogramSnapshot<T> {
+    pub fn buckets(&self) -> &[T] {
+        &self.buckets
+    }
+
+    /// The number of observations that fell into each bucket, these aren't cumulative
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    pub fn sum(&self) -> T {
+        self.sum
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Get each bucket's upper boundary paired with its cumulative count, ending with the
+    /// `+Inf` bucket, like [`HistogramCore::bucket_counts`]
+    ///
+    /// [`HistogramCore::bucket_counts`]: crate::histogram::HistogramCore#method.bucket_counts
+    pub fn bucket_counts(&self) -> Vec<(T, u64)> {
+        cumulative_counts(&self.buckets, self.values.iter().copied(), self.count)
+    }
+
+    /// An empty snapshot of a histogram with `buckets`, holding no observations
+    pub(crate) fn empty(buckets: &[T]) -> Self {
+        Self {
+            buckets: buckets.to_vec(),
+            values: vec![0; buckets.len()],
+            sum: T::default(),
+            count: 0,
+        }
+    }
+
+    /// Add the counts and sum of a snapshot with the same buckets to this one, saturating like
+    /// the histograms they were taken from
+    pub(crate) fn merge(&mut self, other: &Self) {
+        debug_assert_eq!(self.buckets, other.buckets);
+
+        for (value, other_value) in self.values.iter_mut().zip(&other.values) {
+            *value = value.saturating_add(*other_value);
+        }
+        self.sum = self.sum.saturating_add(other.sum);
+        self.count = self.count.saturating_add(other.count);
+    }
+}
+
+/// Creates the `le` label of a bucket boundary, using `+Inf` for the type's upper bound
+pub(crate) fn le_label<Atomic: AtomicNum>(bucket: Atomic::Type) -> Result<Label> {
+    let mut value = String::new();
+    if bucket == Atomic::Type::UPPER_BOUND {
+        value.push_str("+Inf");
+    } else {
+        Atomic::format(bucket, &mut value, false)?;
+    }
+
+    Ok(Label {
+        name: Cow::Borrowed("le"),
+        value: Cow::Owned(value),
+    })
+}
+
+/// Writes a bucket boundary as a quoted `le` label value, writing the type's upper bound as `+Inf`
+pub(crate) fn write_le<Atomic: AtomicNum>(buf: &mut String, bucket: Atomic::Type) -> Result<()> {
+    if bucket == Atomic::Type::UPPER_BOUND {
+        write!(buf, "\"+Inf\"")?;
+    } else {
+        Atomic::format(bucket, buf, true)?;
+    }
+
+    Ok(())
+}
+
 pub struct Histogram<Atomic: AtomicNum = AtomicF64> {
     descriptor: Descriptor,
     core: HistogramCore<Atomic>,
+    /// The latest exemplar of each bucket including `+Inf`, in the same order as
+    /// [`Histogram::bucket_counts`]. Empty until the first exemplar is observed
+    ///
+    /// [`Histogram::bucket_counts`]: crate::histogram::Histogram#method.bucket_counts
+    exemplars: Mutex<Vec<Option<Exemplar>>>,
+}
+
+/// An observation tagged with labels like a trace id, attached to the bucket it fell into
+/// and written after the bucket's sample by the [`OpenMetricsEncoder`]
+///
+/// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exemplar {
+    labels: Vec<Label>,
+    value: f64,
+    timestamp: SystemTime,
+}
+
+impl Exemplar {
+    /// The labels the value was observed with, like a trace id
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// The observed value
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// When the value was observed
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Writes the exemplar as ` # {labels} value timestamp`, with the timestamp as seconds since
+    /// the unix epoch with millisecond precision. The timestamp is formatted from integers so
+    /// that it's never written in scientific notation
+    fn encode(&self, buf: &mut String) -> Result<()> {
+        write!(buf, " # {{")?;
+        write_labels(buf, &self.labels, false)?;
+        write!(buf, "}} ")?;
+        AtomicF64::format(self.value, buf, false)?;
+
+        // Exemplars observed before the unix epoch are written without a timestamp
+        if let Ok(since_epoch) = self.timestamp.duration_since(SystemTime::UNIX_EPOCH) {
+            write!(
+                buf,
+                " {}.{:03}",
+                since_epoch.as_secs(),
+                since_epoch.subsec_millis()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Atomic: AtomicNum> fmt::Debug for Histogram<Atomic> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let buckets = self.bucket_counts();
+
+        f.debug_struct("Histogram")
+            .field("name", &self.name())
+            .field("labels", &DebugLabels(self.labels()))
+            .field("count", &self.get_count())
+            .field("sum", &self.get_sum())
+            .field("buckets", &DebugBuckets(&buckets))
+            .finish()
+    }
+}
+
+/// Formats cumulative bucket counts as a `{boundary: count}` map
+struct DebugBuckets<'a, T>(&'a [(T, u64)]);
+
+impl<T: fmt::Debug> fmt::Debug for DebugBuckets<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(bucket, count)| (bucket, count)))
+            .finish()
+    }
 }
 
 impl<Atomic: AtomicNum> Histogram<Atomic> {
@@ -201,8 +787,212 @@ impl<Atomic: AtomicNum> Histogram<Atomic> {
         self.core.observe(val)
     }
 
+    /// Observe `val` and keep it as the exemplar of the bucket it fell into along with
+    /// `labels` and the current time, replacing the bucket's previous exemplar. Exemplars are
+    /// only written by the [`OpenMetricsEncoder`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{
+    ///     histogram::{Histogram, HistogramBuilder},
+    ///     Label,
+    /// };
+    ///
+    /// let latency: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// latency.observe_with_exemplar(0.7, vec![Label::new("trace_id", "4bf92f3577b34da6").unwrap()]);
+    ///
+    /// let exemplars = latency.exemplars();
+    /// assert!(exemplars[0].is_none());
+    /// assert_eq!(exemplars[1].as_ref().unwrap().value(), 0.7);
+    /// ```
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    pub fn observe_with_exemplar(&self, val: Atomic::Type, labels: impl Into<Vec<Label>>) {
+        self.observe_with_exemplar_at(val, labels.into(), SystemTime::now());
+    }
+
+    pub(crate) fn observe_with_exemplar_at(
+        &self,
+        val: Atomic::Type,
+        labels: Vec<Label>,
+        timestamp: SystemTime,
+    ) {
+        // Values larger than every bucket land in the `+Inf` bucket after the others
+        let idx = self
+            .core
+            .observe_indexed(val)
+            .unwrap_or_else(|| self.core.buckets.len());
+
+        let mut exemplars = self.exemplars.lock().unwrap();
+        if exemplars.len() <= idx {
+            exemplars.resize(idx + 1, None);
+        }
+
+        let value: MetricValue = val.into();
+        exemplars[idx] = Some(Exemplar {
+            labels,
+            value: value.as_f64(),
+            timestamp,
+        });
+    }
+
+    /// Get the latest exemplar of each bucket in the same order as [`Histogram::bucket_counts`],
+    /// `None` for buckets without one
+    ///
+    /// [`Histogram::bucket_counts`]: crate::histogram::Histogram#method.bucket_counts
+    pub fn exemplars(&self) -> Vec<Option<Exemplar>> {
+        let mut exemplars = self.exemplars.lock().unwrap().clone();
+        exemplars.resize(self.bucket_counts().len(), None);
+
+        exemplars
+    }
+
+    /// Observe `val`, rejecting `NaN` instead of letting it poison the histogram's sum forever
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let histogram: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(histogram.try_observe(f64::NAN).is_err());
+    /// assert_eq!(histogram.get_count(), 0);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] without observing anything if `val` is `NaN`
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn try_observe(&self, val: Atomic::Type) -> Result<()> {
+        self.core.try_observe(val)
+    }
+
+    /// Clamp `val` into `[min, max]` before observing it, for inputs that are occasionally out
+    /// of range like a negative latency caused by clock skew. `NaN` isn't clamped, see
+    /// [`Histogram::try_observe`] for rejecting it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let latency: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// latency.observe_clamped(-0.25, 0.0, f64::INFINITY);
+    /// assert_eq!(latency.get_sum(), 0.0);
+    /// ```
+    ///
+    /// [`Histogram::try_observe`]: crate::histogram::Histogram#method.try_observe
+    pub fn observe_clamped(&self, val: Atomic::Type, min: Atomic::Type, max: Atomic::Type) {
+        let clamped = if val < min {
+            min
+        } else if val > max {
+            max
+        } else {
+            val
+        };
+
+        self.core.observe(clamped)
+    }
+
+    /// Observe `val`, rejecting `NaN` and negative values, see [`HistogramCore::try_observe_non_negative`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] without observing anything if `val` is `NaN` or negative
+    ///
+    /// [`HistogramCore::try_observe_non_negative`]: crate::histogram::HistogramCore#method.try_observe_non_negative
+    /// [`PromError`]: crate::PromError
+    pub fn try_observe_non_negative(&self, val: Atomic::Type) -> Result<()> {
+        self.core.try_observe_non_negative(val)
+    }
+
+    /// Observe `val`, returning the index of the bucket it landed in. Buckets are cumulative
+    /// so the observation counts towards every later bucket too, but the returned index is
+    /// the smallest bucket containing it. `None` means it only landed in the `+Inf` bucket
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let histogram: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(histogram.observe_indexed(0.7), Some(1));
+    /// assert_eq!(histogram.observe_indexed(5.0), None);
+    /// ```
+    pub fn observe_indexed(&self, val: Atomic::Type) -> Option<usize> {
+        self.core.observe_indexed(val)
+    }
+
+    /// Observe `val` `n` times at once, adding `n` to the count and `val * n` to the sum.
+    /// Meant for importing pre-aggregated data, like events that another system already counted
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let histogram: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// histogram.observe_n(0.75, 4);
+    /// assert_eq!(histogram.get_count(), 4);
+    /// assert_eq!(histogram.get_sum(), 3.0);
+    /// ```
+    pub fn observe_n(&self, val: Atomic::Type, n: u64) {
+        self.core.observe_n(val, n)
+    }
+
+    /// Reset the histogram, dropping every bucket's exemplar along with the counts
     pub fn clear(&self) {
-        self.core.clear()
+        self.core.clear();
+        self.exemplars.lock().unwrap().clear();
+    }
+
+    /// Reset every bucket's count and exemplar while keeping the histogram's sum and count, see
+    /// [`HistogramCore::clear_buckets`]
+    ///
+    /// [`HistogramCore::clear_buckets`]: crate::histogram::HistogramCore#method.clear_buckets
+    pub fn clear_buckets(&self) {
+        self.core.clear_buckets();
+        self.exemplars.lock().unwrap().clear();
+    }
+
+    /// Reset the histogram's sum and count while keeping every bucket's count, see
+    /// [`HistogramCore::clear_aggregate`]
+    ///
+    /// [`HistogramCore::clear_aggregate`]: crate::histogram::HistogramCore#method.clear_aggregate
+    pub fn clear_aggregate(&self) {
+        self.core.clear_aggregate()
     }
 
     pub fn get_count(&self) -> u64 {
@@ -213,165 +1003,838 @@ impl<Atomic: AtomicNum> Histogram<Atomic> {
         self.core.get_sum()
     }
 
-    pub fn start_timer<'a>(&'a self) -> Timer<'a, Self> {
+    pub fn start_timer(&self) -> Timer<'_, Self> {
         Timer::new(self)
     }
 
-    pub fn local<'a>(&'a self) -> LocalHistogram<'a, Atomic> {
+    pub fn local(&self) -> LocalHistogram<'_, Atomic> {
         self.core.local()
     }
 
+    /// Create a [`HistogramLocalPool`] that buffers observations per thread and flushes them into
+    /// this histogram at least every `flush_interval`
+    ///
+    /// [`HistogramLocalPool`]: crate::histogram::HistogramLocalPool
+    pub fn local_pool(&self, flush_interval: Duration) -> HistogramLocalPool<'_, Atomic> {
+        self.core.local_pool(flush_interval)
+    }
+
     pub fn name(&self) -> &str {
-        &self.descriptor.name()
+        self.descriptor.name()
     }
 
     pub fn help(&self) -> &str {
-        &self.descriptor.help()
+        self.descriptor.help()
     }
 
     pub fn labels(&self) -> &[Label] {
-        &self.descriptor.labels()
+        self.descriptor.labels()
     }
 
     pub fn buckets(&self) -> &[Atomic::Type] {
         self.core.buckets()
     }
 
-    pub fn observe_bucket(&self, val: Atomic::Type, bucket: Atomic::Type) -> Result<()> {
-        self.core.observe_bucket(val, bucket)
+    /// The unit that timers started from this histogram record elapsed time in
+    pub fn time_unit(&self) -> TimeUnit {
+        self.core.time_unit
     }
-}
-
-impl<Atomic: AtomicNum> Collectable for &Histogram<Atomic> {
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()> {
-        writeln!(buf, "# HELP {} {}", self.name(), self.help())?;
-        writeln!(buf, "# TYPE {} histogram", self.name())?;
 
-        let row = |buf: &mut String, name| -> Result<()> {
-            write!(buf, "{}_{}", self.name(), name)?;
+    /// Set the histogram's unit, which the [`OpenMetricsEncoder`] emits as a `# UNIT` line
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the unit is invalid or the histogram's name doesn't end with `_{unit}`
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    /// [`PromError`]: crate::PromError
+    pub fn with_unit(mut self, unit: impl Into<Cow<'static, str>>) -> Result<Self> {
+        self.descriptor.set_unit(unit.into(), false)?;
+        Ok(self)
+    }
 
-            if !self.labels().is_empty() {
-                write!(buf, "{{")?;
+    /// Write the histogram's help as-is instead of escaping backslashes and newlines, for help text
+    /// that's already escaped. The caller is responsible for the help being valid in the exposition format
+    pub fn with_raw_help(mut self) -> Self {
+        self.descriptor.raw_help = true;
+        self
+    }
 
-                let mut labels = self.labels().iter();
-                let last = labels.next_back();
+    pub fn observe_bucket(&self, val: Atomic::Type, bucket: Atomic::Type) -> Result<()> {
+        self.core.observe_bucket(val, bucket)
+    }
 
-                for label in labels {
-                    write!(buf, "{}={:?},", label.name(), label.value())?;
-                }
+    /// Add every bucket count, the sum and the count of `other` to this histogram, meant for
+    /// aggregating per-shard histograms into a global one. `other` is left unchanged
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let shard = |name: &'static str| -> Histogram {
+    ///     HistogramBuilder::new()
+    ///         .name(name)
+    ///         .help("Request latency")
+    ///         .with_buckets(vec![0.5, 1.0])
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let (global, local) = (shard("global_latency"), shard("local_latency"));
+    ///
+    /// local.observe(0.25);
+    /// global.merge_from(&local).unwrap();
+    /// assert_eq!(global.get_count(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the histograms' bucket boundaries differ
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn merge_from(&self, other: &Self) -> Result<()> {
+        self.core.merge_from(&other.core)
+    }
 
-                if let Some(last) = last {
-                    write!(buf, "{}={:?}", last.name(), last.value())?;
-                }
+    /// Get each bucket's upper boundary paired with its cumulative count, ending with the `+Inf` bucket
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let histogram: Histogram = HistogramBuilder::new()
+    ///     .name("response_time")
+    ///     .help("Response times in seconds")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// histogram.observe(0.25);
+    /// histogram.observe(0.75);
+    /// histogram.observe(5.0);
+    ///
+    /// assert_eq!(
+    ///     histogram.bucket_counts(),
+    ///     vec![(0.5, 1), (1.0, 2), (f64::INFINITY, 3)],
+    /// );
+    /// ```
+    pub fn bucket_counts(&self) -> Vec<(Atomic::Type, u64)> {
+        self.core.bucket_counts()
+    }
 
-                write!(buf, "}} ")?;
-            } else {
-                write!(buf, " ")?;
-            }
+    /// Get the `le` label value written for each bucket boundary, in the same order as
+    /// [`Histogram::bucket_counts`] and ending with `+Inf`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let histogram: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.005, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(histogram.le_strings(), vec!["0.005", "1.0", "+Inf"]);
+    /// ```
+    ///
+    /// [`Histogram::bucket_counts`]: crate::histogram::Histogram#method.bucket_counts
+    pub fn le_strings(&self) -> Vec<String> {
+        self.bucket_counts()
+            .into_iter()
+            // Formatting into a `String` can't fail
+            .filter_map(|(bucket, _)| le_label::<Atomic>(bucket).ok())
+            .map(|label| label.value.into_owned())
+            .collect()
+    }
 
-            Ok(())
-        };
+    /// Get a view of the histogram's `_sum` as a standalone counter named `{name}_sum`, for
+    /// backends that ingest the sum and count as plain counters but don't understand
+    /// histograms. See [`HistogramCounterView`]
+    ///
+    /// [`HistogramCounterView`]: crate::histogram::HistogramCounterView
+    pub fn sum_counter(&self) -> HistogramCounterView<'_, Atomic> {
+        HistogramCounterView::new(self, CounterViewPart::Sum)
+    }
 
-        row(buf, "sum")?;
-        Atomic::format(self.get_sum(), buf, false)?;
-        writeln!(buf)?;
+    /// Get a view of the histogram's `_count` as a standalone counter named `{name}_count`,
+    /// see [`Histogram::sum_counter`]
+    ///
+    /// [`Histogram::sum_counter`]: crate::histogram::Histogram#method.sum_counter
+    pub fn count_counter(&self) -> HistogramCounterView<'_, Atomic> {
+        HistogramCounterView::new(self, CounterViewPart::Count)
+    }
 
-        row(buf, "count")?;
-        <AtomicU64 as AtomicNum>::format(self.get_count(), buf, false)?;
-        writeln!(buf)?;
+    /// Get every bucket's boundary as an `f64`, its `le` label value and its cumulative count,
+    /// combining [`Histogram::bucket_counts`] and [`Histogram::le_strings`] for exporters that
+    /// need both. The final entry is the `+Inf` bucket with a boundary of `f64::INFINITY`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+    ///
+    /// let histogram: Histogram = HistogramBuilder::new()
+    ///     .name("latency")
+    ///     .help("Request latency")
+    ///     .with_buckets(vec![0.5, 1.0])
+    ///     .build()
+    ///     .unwrap();
+    /// histogram.observe(0.75);
+    ///
+    /// let buckets = histogram.buckets_detailed();
+    /// assert_eq!(buckets[1].le(), 1.0);
+    /// assert_eq!(buckets[1].le_string(), "1.0");
+    /// assert_eq!(buckets[1].cumulative_count(), 1);
+    /// ```
+    ///
+    /// [`Histogram::bucket_counts`]: crate::histogram::Histogram#method.bucket_counts
+    /// [`Histogram::le_strings`]: crate::histogram::Histogram#method.le_strings
+    pub fn buckets_detailed(&self) -> Vec<BucketInfo> {
+        self.bucket_counts()
+            .into_iter()
+            .filter_map(|(bucket, cumulative_count)| {
+                let le = if bucket == Atomic::Type::UPPER_BOUND {
+                    f64::INFINITY
+                } else {
+                    bucket.into().as_f64()
+                };
+
+                // Formatting into a `String` can't fail
+                le_label::<Atomic>(bucket).ok().map(|label| BucketInfo {
+                    le,
+                    le_string: label.value.into_owned(),
+                    cumulative_count,
+                })
+            })
+            .collect()
+    }
+}
 
-        for (i, bucket) in self.core.buckets.iter().enumerate() {
-            write!(buf, "{}_bucket", self.name())?;
+/// The `_sum` or `_count` of a [`Histogram`] exposed as a counter, created with
+/// [`Histogram::sum_counter`] and [`Histogram::count_counter`]
+///
+/// The views write the same series as the histogram itself, so they're meant to be registered
+/// instead of the histogram, e.g. in a separate registry for a backend that doesn't understand
+/// histograms
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{
+///     histogram::{Histogram, HistogramBuilder},
+///     RegistryBuilder,
+/// };
+/// use once_cell::sync::Lazy;
+///
+/// static LATENCY: Lazy<Histogram> = Lazy::new(|| {
+///     HistogramBuilder::new()
+///         .name("latency")
+///         .help("Request latency")
+///         .with_buckets(vec![0.5, 1.0])
+///         .build()
+///         .unwrap()
+/// });
+///
+/// let registry = RegistryBuilder::new()
+///     .register(Box::new(LATENCY.sum_counter()))
+///     .register(Box::new(LATENCY.count_counter()))
+///     .build()
+///     .unwrap();
+///
+/// LATENCY.observe(0.25);
+/// assert!(registry.collect_to_string().unwrap().contains("# TYPE latency_count counter\nlatency_count 1\n"));
+/// ```
+///
+/// [`Histogram`]: crate::histogram::Histogram
+/// [`Histogram::sum_counter`]: crate::histogram::Histogram#method.sum_counter
+/// [`Histogram::count_counter`]: crate::histogram::Histogram#method.count_counter
+#[derive(Debug)]
+pub struct HistogramCounterView<'a, Atomic: AtomicNum> {
+    descriptor: Descriptor,
+    histogram: &'a Histogram<Atomic>,
+    part: CounterViewPart,
+}
 
-            if !self.labels().is_empty() {
-                write!(buf, "{{")?;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CounterViewPart {
+    Sum,
+    Count,
+}
 
-                for label in self.labels() {
-                    write!(buf, "{}={:?},", label.name(), label.value())?;
-                }
-                write!(buf, "le=")?;
-                Atomic::format(*bucket, buf, true)?;
+impl<'a, Atomic: AtomicNum> HistogramCounterView<'a, Atomic> {
+    fn new(histogram: &'a Histogram<Atomic>, part: CounterViewPart) -> Self {
+        let suffix = match part {
+            CounterViewPart::Sum => "_sum",
+            CounterViewPart::Count => "_count",
+        };
 
-                write!(buf, "}} ")?;
-            } else {
-                write!(buf, " ")?;
-            }
+        Self {
+            descriptor: histogram.descriptor.with_name_suffix(suffix),
+            histogram,
+            part,
+        }
+    }
 
-            Atomic::format(self.core.values[i].get(), buf, false)?;
-            writeln!(buf)?;
+    /// The current value of the histogram's sum or count
+    pub fn get(&self) -> MetricValue {
+        match self.part {
+            CounterViewPart::Sum => self.histogram.get_sum().into(),
+            CounterViewPart::Count => self.histogram.get_count().into(),
         }
+    }
 
-        Ok(())
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
     }
 
-    fn descriptor(&self) -> &Descriptor {
-        &self.descriptor
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
     }
-}
 
-#[derive(Debug)]
-pub struct LocalHistogram<'a, Atomic: AtomicNum> {
-    pub(crate) inner: RefCell<InnerLocalHist<'a, Atomic>>,
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
 }
 
-impl<'a, Atomic: AtomicNum> LocalHistogram<'a, Atomic> {
-    pub(crate) fn new(histogram: &'a HistogramCore<Atomic>) -> Self {
-        Self {
-            inner: RefCell::new(InnerLocalHist {
-                histogram,
-                values: vec![Atomic::Type::default(); histogram.values.len()],
-                count: 0,
-                sum: Atomic::Type::default(),
-            }),
+impl<Atomic: AtomicNum> HistogramCounterView<'_, Atomic> {
+    /// Writes the view's sample named `{{ name }}{{ suffix }}`
+    fn encode_sample(
+        &self,
+        buf: &mut String,
+        name: &str,
+        suffix: &str,
+        options: &FormatOptions,
+    ) -> Result<()> {
+        write_sample_name(buf, name, suffix, self.labels())?;
+        match self.part {
+            CounterViewPart::Sum => options.write_value(buf, self.histogram.get_sum())?,
+            CounterViewPart::Count => {
+                <AtomicU64 as AtomicNum>::format(self.histogram.get_count(), buf, false)?
+            }
         }
+        writeln!(buf)?;
+
+        Ok(())
     }
+}
 
-    pub fn flush(&mut self) {
-        self.inner.borrow_mut().flush();
+impl<Atomic: AtomicNum> Collectable for HistogramCounterView<'_, Atomic> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode_sample(buf, self.name(), "", options)
     }
 
-    pub fn observe(&mut self, val: Atomic::Type) {
-        self.inner.borrow_mut().observe(val);
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode_sample(buf, counter_family(self.name()), "_total", options)
     }
 
-    pub fn clear(&mut self) {
-        self.inner.borrow_mut().clear();
+    fn metric_type(&self) -> &'static str {
+        "counter"
     }
 
-    pub fn get_count(&self) -> u64 {
-        self.inner.borrow().count
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
     }
 
-    pub fn get_sum(&self) -> Atomic::Type {
-        self.inner.borrow().sum
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), self.get());
     }
+}
 
-    pub fn start_timer<'b>(&'b self) -> Timer<'b, Self> {
-        Timer::new(self)
+/// Observe `val` into every histogram in `histograms`, for correlated histograms like a global
+/// and a per-endpoint request latency. Each histogram is observed separately, so a collection
+/// can run between the observations
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::histogram::{self, Histogram, HistogramBuilder};
+///
+/// let build = |name| -> Histogram {
+///     HistogramBuilder::new()
+///         .name(name)
+///         .help("Request latency")
+///         .with_buckets(vec![0.5, 1.0])
+///         .build()
+///         .unwrap()
+/// };
+/// let (global, endpoint) = (build("latency"), build("index_latency"));
+///
+/// histogram::observe_all(&[&global, &endpoint], 0.25);
+/// assert_eq!(global.get_count(), 1);
+/// assert_eq!(endpoint.get_count(), 1);
+/// ```
+pub fn observe_all<Atomic: AtomicNum>(histograms: &[&Histogram<Atomic>], val: Atomic::Type) {
+    for histogram in histograms {
+        histogram.observe(val);
+    }
+}
+
+/// A single bucket of a [`Histogram`], see [`Histogram::buckets_detailed`]
+///
+/// [`Histogram`]: crate::histogram::Histogram
+/// [`Histogram::buckets_detailed`]: crate::histogram::Histogram#method.buckets_detailed
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketInfo {
+    le: f64,
+    le_string: String,
+    cumulative_count: u64,
+}
+
+impl BucketInfo {
+    /// The bucket's upper boundary, `f64::INFINITY` for the `+Inf` bucket
+    pub fn le(&self) -> f64 {
+        self.le
+    }
+
+    /// The bucket's `le` label value as it's written in the text format
+    pub fn le_string(&self) -> &str {
+        &self.le_string
+    }
+
+    /// The number of observations less than or equal to the bucket's boundary
+    pub fn cumulative_count(&self) -> u64 {
+        self.cumulative_count
+    }
+}
+
+/// Writes the `_sum`, `_count` and cumulative `_bucket` samples of a histogram
+fn write_histogram_samples<Atomic: AtomicNum>(
+    buf: &mut String,
+    name: &str,
+    labels: &[Label],
+    counts: HistogramCounts<'_, Atomic>,
+    exemplars: &[Option<Exemplar>],
+    options: &FormatOptions,
+) -> Result<()> {
+    let row = |out: &mut String, suffix| -> Result<()> {
+        write!(out, "{}_{}", name, suffix)?;
+
+        if !labels.is_empty() {
+            write!(out, "{{")?;
+            write_labels(out, labels, false)?;
+            write!(out, "}}")?;
+        }
+        write!(out, " ")?;
+
+        Ok(())
+    };
+
+    row(buf, "sum")?;
+    options.write_value(buf, counts.sum.get())?;
+    writeln!(buf)?;
+
+    row(buf, "count")?;
+    <AtomicU64 as AtomicNum>::format(counts.count.get(), buf, false)?;
+    writeln!(buf)?;
+
+    for (idx, (bucket, cumulative)) in counts.bucket_counts().into_iter().enumerate() {
+        write!(buf, "{}_bucket{{", name)?;
+        write_labels(buf, labels, false)?;
+        if !labels.is_empty() {
+            write!(buf, ",")?;
+        }
+        write!(buf, "le=")?;
+        write_le::<Atomic>(buf, bucket)?;
+        write!(buf, "}} ")?;
+
+        <AtomicU64 as AtomicNum>::format(cumulative, buf, false)?;
+        if let Some(Some(exemplar)) = exemplars.get(idx) {
+            exemplar.encode(buf)?;
+        }
+        writeln!(buf)?;
+    }
+
+    Ok(())
+}
+
+impl<Atomic: AtomicNum> Collectable for &Histogram<Atomic> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write_histogram_samples(
+            buf,
+            self.name(),
+            self.labels(),
+            self.core.counts(),
+            &[],
+            options,
+        )
+    }
+
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write_histogram_samples(
+            buf,
+            self.name(),
+            self.labels(),
+            self.core.counts(),
+            &self.exemplars.lock().unwrap(),
+            options,
+        )
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "histogram"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("_sum", self.labels(), self.get_sum().into());
+        visit("_count", self.labels(), self.get_count().into());
+
+        // The `le` label is swapped out for each bucket, reusing the same label set
+        let mut labels = self.labels().to_vec();
+        for (bucket, count) in self.bucket_counts() {
+            if let Ok(le) = le_label::<Atomic>(bucket) {
+                labels.push(le);
+                visit("_bucket", &labels, count.into());
+                labels.pop();
+            }
+        }
+    }
+}
+
+/// A float histogram with a fixed number of buckets stored inline, so that it can be created in
+/// a const context and stored in a plain `static` without `Lazy` or any allocation
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::histogram::ArrayHistogram;
+///
+/// static LATENCY: ArrayHistogram<3> =
+///     ArrayHistogram::with_buckets("latency_seconds", "Request latency", [0.1, 0.5, 1.0]);
+///
+/// LATENCY.observe(0.3);
+/// assert_eq!(LATENCY.get_count(), 1);
+/// ```
+///
+/// Invalid names or buckets are a compile error when the histogram is a `static`
+///
+/// ```rust,compile_fail
+/// use prometheus_rs::histogram::ArrayHistogram;
+///
+/// static LATENCY: ArrayHistogram<3> =
+///     ArrayHistogram::with_buckets("latency_seconds", "Request latency", [0.1, 0.1, 1.0]);
+/// ```
+#[derive(Debug)]
+pub struct ArrayHistogram<const N: usize> {
+    descriptor: Descriptor,
+    buckets: [f64; N],
+    /// The number of observations that fell into each bucket, these aren't cumulative
+    values: [AtomicU64; N],
+    count: AtomicU64,
+    sum: AtomicF64,
+}
+
+impl<const N: usize> ArrayHistogram<N> {
+    /// Create a histogram with the given buckets, which must be strictly increasing.
+    /// A `+Inf` bucket is added when encoding if the buckets don't end with one
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is invalid, if there are no buckets or if the buckets aren't strictly
+    /// increasing, which is a compile error when evaluated in a const context
+    pub const fn with_buckets(name: &'static str, help: &'static str, buckets: [f64; N]) -> Self {
+        assert!(N != 0, "Histograms cannot have empty buckets");
+
+        let mut idx = 1;
+        while idx < N {
+            // Also rejects duplicated buckets, `-0.0` and `0.0` count as duplicates
+            assert!(
+                buckets[idx - 1] < buckets[idx],
+                "Histogram buckets must be strictly increasing"
+            );
+            idx += 1;
+        }
+
+        Self {
+            descriptor: Descriptor::from_static(name, help),
+            buckets,
+            values: [const { AtomicU64::new(0) }; N],
+            count: AtomicU64::new(0),
+            sum: AtomicF64::zeroed(),
+        }
+    }
+
+    pub fn observe(&self, val: f64) {
+        self.observe_indexed(val);
+    }
+
+    /// Observe `val`, returning the index of the smallest bucket it fell into or `None` if it's
+    /// larger than every bucket and was only counted by the implicit `+Inf` bucket
+    pub fn observe_indexed(&self, val: f64) -> Option<usize> {
+        self.counts().observe_n(val, 1)
+    }
+
+    pub fn clear(&self) {
+        let counts = self.counts();
+        counts.clear_buckets();
+        counts.clear_aggregate();
+    }
+
+    pub fn get_count(&self) -> u64 {
+        self.count.get()
+    }
+
+    pub fn get_sum(&self) -> f64 {
+        self.sum.get()
+    }
+
+    pub fn buckets(&self) -> &[f64; N] {
+        &self.buckets
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    /// Get each bucket's upper boundary paired with the cumulative number of observations
+    /// less than or equal to it, ending with the `+Inf` bucket holding the total count
+    pub fn bucket_counts(&self) -> Vec<(f64, u64)> {
+        self.counts().bucket_counts()
+    }
+
+    fn counts(&self) -> HistogramCounts<'_, AtomicF64> {
+        HistogramCounts {
+            buckets: &self.buckets,
+            values: &self.values,
+            count: &self.count,
+            sum: &self.sum,
+        }
+    }
+}
+
+impl<const N: usize> Collectable for &ArrayHistogram<N> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        write_histogram_samples(buf, self.name(), &[], self.counts(), &[], options)
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "histogram"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("_sum", &[], self.get_sum().into());
+        visit("_count", &[], self.get_count().into());
+
+        for (bucket, count) in self.bucket_counts() {
+            if let Ok(le) = le_label::<AtomicF64>(bucket) {
+                visit("_bucket", &[le], count.into());
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LocalHistogram<'a, Atomic: AtomicNum> {
+    pub(crate) inner: RefCell<InnerLocalHist<'a, Atomic>>,
+}
+
+impl<'a, Atomic: AtomicNum> LocalHistogram<'a, Atomic> {
+    pub(crate) fn new(histogram: &'a HistogramCore<Atomic>) -> Self {
+        Self {
+            inner: RefCell::new(InnerLocalHist::new(histogram)),
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.inner.borrow_mut().flush();
+    }
+
+    pub fn observe(&mut self, val: Atomic::Type) {
+        self.inner.borrow_mut().observe(val);
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.borrow_mut().clear();
+    }
+
+    pub fn get_count(&self) -> u64 {
+        self.inner.borrow().count
+    }
+
+    pub fn get_sum(&self) -> Atomic::Type {
+        self.inner.borrow().sum
+    }
+
+    pub fn start_timer(&self) -> Timer<'_, Self> {
+        Timer::new(self)
+    }
+}
+
+/// Hands out a local buffer to every thread that observes through it, so that observations can
+/// be made from tasks that migrate between threads without contending on the histogram's atomics.
+/// Each thread's buffer is flushed into the histogram by the first observation made after
+/// `flush_interval` has passed since its last flush, when [`HistogramLocalPool::flush`] is called
+/// or when the pool is dropped. Buffers of threads that stopped observing are only flushed by the
+/// latter two, and [`HistogramLocalPool::flush`] releases every buffer so that they aren't kept forever
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::histogram::{Histogram, HistogramBuilder};
+/// use std::{thread, time::Duration};
+///
+/// let histogram: Histogram = HistogramBuilder::new()
+///     .name("latency_seconds")
+///     .help("Request latency")
+///     .with_buckets(vec![0.1, 0.5, 1.0])
+///     .build()
+///     .unwrap();
+///
+/// let pool = histogram.local_pool(Duration::from_secs(1));
+/// thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| pool.observe(0.25));
+///     }
+/// });
+///
+/// drop(pool);
+/// assert_eq!(histogram.get_count(), 4);
+/// ```
+///
+/// [`HistogramLocalPool::flush`]: crate::histogram::HistogramLocalPool#method.flush
+#[derive(Debug)]
+pub struct HistogramLocalPool<'a, Atomic: AtomicNum> {
+    histogram: &'a HistogramCore<Atomic>,
+    flush_interval: Duration,
+    /// Each thread only ever locks its own buffer outside of flushing the whole pool, so the
+    /// buffers' locks are uncontended
+    buffers: RwLock<HashMap<ThreadId, Mutex<PooledBuffer<'a, Atomic>>>>,
+}
+
+#[derive(Debug)]
+struct PooledBuffer<'a, Atomic: AtomicNum> {
+    local: InnerLocalHist<'a, Atomic>,
+    last_flush: Instant,
+}
+
+impl<'a, Atomic: AtomicNum> HistogramLocalPool<'a, Atomic> {
+    pub(crate) fn new(histogram: &'a HistogramCore<Atomic>, flush_interval: Duration) -> Self {
+        Self {
+            histogram,
+            flush_interval,
+            buffers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Observe `val` into the current thread's buffer, flushing the buffer if the flush interval
+    /// has passed since it was last flushed
+    pub fn observe(&self, val: Atomic::Type) {
+        let thread = thread::current().id();
+
+        {
+            let buffers = self
+                .buffers
+                .read()
+                .expect("a histogram pool's lock was poisoned");
+            if let Some(buffer) = buffers.get(&thread) {
+                self.observe_buffered(buffer, val);
+                return;
+            }
+        }
+
+        let mut buffers = self
+            .buffers
+            .write()
+            .expect("a histogram pool's lock was poisoned");
+        let buffer = buffers.entry(thread).or_insert_with(|| {
+            Mutex::new(PooledBuffer {
+                local: InnerLocalHist::new(self.histogram),
+                last_flush: Instant::now(),
+            })
+        });
+        self.observe_buffered(buffer, val);
+    }
+
+    /// Flush every thread's buffered observations into the histogram and release the emptied
+    /// buffers, threads that observe again get a new one
+    pub fn flush(&self) {
+        let mut buffers = self
+            .buffers
+            .write()
+            .expect("a histogram pool's lock was poisoned");
+
+        for (_, buffer) in buffers.drain() {
+            buffer
+                .into_inner()
+                .expect("a histogram pool's lock was poisoned")
+                .local
+                .flush();
+        }
+    }
+
+    fn observe_buffered(&self, buffer: &Mutex<PooledBuffer<'a, Atomic>>, val: Atomic::Type) {
+        let mut buffer = buffer.lock().expect("a histogram pool's lock was poisoned");
+        buffer.local.observe(val);
+
+        if buffer.last_flush.elapsed() >= self.flush_interval {
+            buffer.local.flush();
+            buffer.last_flush = Instant::now();
+        }
+    }
+}
+
+impl<Atomic: AtomicNum> Drop for HistogramLocalPool<'_, Atomic> {
+    fn drop(&mut self) {
+        // Flush even if a thread panicked while holding a lock so that no observations are lost
+        let buffers = match self.buffers.get_mut() {
+            Ok(buffers) => buffers,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        for buffer in buffers.values_mut() {
+            match buffer.get_mut() {
+                Ok(buffer) => buffer.local.flush(),
+                Err(poisoned) => poisoned.into_inner().local.flush(),
+            }
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct InnerLocalHist<'a, Atomic: AtomicNum> {
-    histogram: &'a HistogramCore<Atomic>,
-    values: Vec<Atomic::Type>,
+    pub(crate) histogram: &'a HistogramCore<Atomic>,
+    values: Vec<u64>,
     count: u64,
     sum: Atomic::Type,
 }
 
 impl<'a, Atomic: AtomicNum> InnerLocalHist<'a, Atomic> {
+    pub(crate) fn new(histogram: &'a HistogramCore<Atomic>) -> Self {
+        Self {
+            histogram,
+            values: vec![0; histogram.values.len()],
+            count: 0,
+            sum: Atomic::Type::default(),
+        }
+    }
+
     pub(crate) fn observe(&mut self, val: Atomic::Type) {
-        if let Some(idx) = self.histogram.buckets.iter().position(|b| val <= *b) {
-            self.values[idx] += val;
+        if let Some(idx) = self.histogram.counts().bucket_index(val) {
+            self.values[idx] = self.values[idx].saturating_add(1);
         }
 
-        self.count += 1;
-        self.sum += val;
+        self.count = self.count.saturating_add(1);
+        self.sum = self.sum.saturating_add(val);
     }
 
     pub(crate) fn clear(&mut self) {
         for val in self.values.iter_mut() {
-            *val = Atomic::Type::default();
+            *val = 0;
         }
 
         self.count = 0;
@@ -383,12 +1846,11 @@ impl<'a, Atomic: AtomicNum> InnerLocalHist<'a, Atomic> {
             return;
         }
 
-        for (i, val) in self.values.iter().enumerate() {
-            self.histogram.values[i].inc_by(*val);
-        }
-
-        self.histogram.count.inc_by(self.count);
-        self.histogram.sum.inc_by(self.sum);
+        self.histogram.counts().add(
+            self.values.iter().copied().enumerate(),
+            self.count,
+            self.sum,
+        );
         self.clear();
     }
 }
@@ -402,7 +1864,7 @@ mod tests {
         let built: Histogram<AtomicF64> = HistogramBuilder::new()
             .name("some_histogram")
             .help("It hist's grams")
-            .with_buckets(vec![-1.0, -0.0, 0.0, 1.0])
+            .with_buckets(vec![-1.0, 0.0, 1.0])
             .with_labels(vec![Label::new("some_random_label", "whee").unwrap()])
             .label(Label::new("another_label", "I ran out of ideas").unwrap())
             .build()
@@ -410,7 +1872,7 @@ mod tests {
 
         assert_eq!(built.name(), "some_histogram");
         assert_eq!(built.help(), "It hist's grams");
-        assert_eq!(built.buckets(), &[-1.0, -0.0, 0.0, 1.0]);
+        assert_eq!(built.buckets(), &[-1.0, 0.0, 1.0]);
         assert_eq!(
             built.labels(),
             &[
@@ -419,4 +1881,875 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn observe_n() {
+        let weighted: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("weighted_histogram")
+            .help("Hists grams")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+        let repeated: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("weighted_histogram")
+            .help("Hists grams")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+
+        for &(val, n) in &[(5, 3), (50, 7), (500, 2), (50, 0)] {
+            weighted.observe_n(val, n);
+            for _ in 0..n {
+                repeated.observe(val);
+            }
+        }
+
+        assert_eq!(weighted.get_count(), 12);
+        assert_eq!(weighted.get_sum(), 5 * 3 + 50 * 7 + 500 * 2);
+        assert_eq!(
+            weighted.bucket_counts(),
+            vec![(10, 3), (100, 10), (u64::MAX, 12)],
+        );
+        assert_eq!(weighted.bucket_counts(), repeated.bucket_counts());
+        assert_eq!(weighted.get_sum(), repeated.get_sum());
+
+        // Large weights saturate the sum instead of overflowing, even past `i64::MAX`
+        let heavy: Histogram<std::sync::atomic::AtomicI64> = HistogramBuilder::new()
+            .name("heavy_histogram")
+            .help("Hists grams")
+            .with_buckets(vec![10])
+            .build()
+            .unwrap();
+        heavy.observe_n(3, u64::MAX);
+        assert_eq!(heavy.get_sum(), i64::MAX);
+        assert_eq!(heavy.get_count(), u64::MAX);
+
+        weighted.observe_n(2, u64::MAX / 2 + 1);
+        assert_eq!(weighted.get_sum(), u64::MAX);
+    }
+
+    #[test]
+    fn local_pool() {
+        let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("pooled_histogram")
+            .help("Hists grams")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+
+        // Nothing is flushed before an explicit flush or the pool being dropped
+        let pool = histogram.local_pool(Duration::from_secs(3600));
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for val in 0..1000 {
+                        pool.observe(val);
+                    }
+                });
+            }
+        });
+        assert_eq!(histogram.get_count(), 0);
+
+        assert_eq!(pool.buffers.read().unwrap().len(), 8);
+
+        // Flushing releases the buffers of threads that are gone
+        pool.flush();
+        assert!(pool.buffers.read().unwrap().is_empty());
+        assert_eq!(histogram.get_count(), 8000);
+        assert_eq!(histogram.get_sum(), 8 * (0..1000).sum::<u64>());
+        assert_eq!(
+            histogram.bucket_counts(),
+            vec![(10, 8 * 11), (100, 8 * 101), (u64::MAX, 8000)],
+        );
+
+        pool.observe(5);
+        drop(pool);
+        assert_eq!(histogram.get_count(), 8001);
+
+        // A zero interval flushes on every observation
+        let eager = histogram.local_pool(Duration::from_secs(0));
+        eager.observe(5);
+        assert_eq!(histogram.get_count(), 8002);
+    }
+
+    #[test]
+    fn static_array_histogram() {
+        static ARRAY_HIST: ArrayHistogram<3> =
+            ArrayHistogram::with_buckets("array_histogram", "It hist's grams", [-1.0, 0.0, 1.0]);
+
+        let heap: Histogram<AtomicF64> = HistogramBuilder::new()
+            .name("array_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![-1.0, 0.0, 1.0])
+            .build()
+            .unwrap();
+
+        for &val in &[-2.0, 0.0, 0.5, 3.0] {
+            assert_eq!(ARRAY_HIST.observe_indexed(val), heap.observe_indexed(val));
+        }
+        assert_eq!(ARRAY_HIST.get_count(), 4);
+        assert_eq!(ARRAY_HIST.get_sum(), 1.5);
+        assert_eq!(ARRAY_HIST.bucket_counts(), heap.bucket_counts());
+
+        // The array histogram encodes exactly like a heap allocated one
+        let (mut array_text, mut heap_text) = (String::new(), String::new());
+        (&ARRAY_HIST)
+            .encode_text(&mut array_text, &FormatOptions::new())
+            .unwrap();
+        (&heap)
+            .encode_text(&mut heap_text, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(array_text, heap_text);
+        assert_eq!((&ARRAY_HIST).current_values(), (&heap).current_values());
+
+        ARRAY_HIST.clear();
+        assert_eq!(ARRAY_HIST.get_count(), 0);
+        assert_eq!(ARRAY_HIST.bucket_counts().last(), Some(&(f64::INFINITY, 0)));
+    }
+
+    #[test]
+    fn reserved_le_label() {
+        let le = Label {
+            name: Cow::Borrowed("le"),
+            value: Cow::Borrowed("1.0"),
+        };
+
+        let err = HistogramBuilder::<AtomicF64>::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![1.0])
+            .label(le)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidLabelName);
+
+        assert_eq!(
+            Label::new("le", "1.0").unwrap_err().kind(),
+            PromErrorKind::InvalidLabelName
+        );
+    }
+
+    #[test]
+    fn observe_indexed() {
+        let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![10, 50, 100])
+            .build()
+            .unwrap();
+
+        assert_eq!(histogram.observe_indexed(0), Some(0));
+        assert_eq!(histogram.observe_indexed(10), Some(0));
+        assert_eq!(histogram.observe_indexed(11), Some(1));
+        assert_eq!(histogram.observe_indexed(100), Some(2));
+        assert_eq!(histogram.observe_indexed(101), None);
+
+        assert_eq!(histogram.get_count(), 5);
+        assert_eq!(histogram.core.values(), vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn le_strings_match_encoding() {
+        let histogram: Histogram<AtomicF64> = HistogramBuilder::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(DEFAULT_BUCKETS.to_vec())
+            .build()
+            .unwrap();
+
+        let mut buf = String::new();
+        (&histogram)
+            .encode_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+
+        let encoded: Vec<&str> = buf
+            .lines()
+            .filter_map(|line| {
+                let start = line.find("le=\"")? + 4;
+                let end = start + line[start..].find('"')?;
+                Some(&line[start..end])
+            })
+            .collect();
+
+        assert_eq!(histogram.le_strings(), encoded);
+        assert_eq!(encoded.first(), Some(&"0.005"));
+        assert_eq!(encoded.last(), Some(&"+Inf"));
+
+        let ints: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+        assert_eq!(ints.le_strings(), vec!["10", "100", "+Inf"]);
+    }
+
+    #[test]
+    fn signed_zero_buckets() {
+        let duplicated = HistogramBuilder::<AtomicF64>::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![-1.0, -0.0, 0.0, 1.0])
+            .build()
+            .unwrap_err();
+        assert_eq!(duplicated.kind(), PromErrorKind::DuplicatedBucket);
+
+        let negative_zero: Histogram<AtomicF64> = HistogramBuilder::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![-1.0, -0.0, 1.0])
+            .build()
+            .unwrap();
+
+        let mut buf = String::new();
+        (&negative_zero)
+            .encode_text(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert!(
+            buf.contains("some_histogram_bucket{le=\"0.0\"} 0\n"),
+            "{}",
+            buf
+        );
+        assert!(!buf.contains("-0.0"), "{}", buf);
+    }
+
+    #[test]
+    fn missing_components() {
+        let missing_name = HistogramBuilder::<AtomicF64>::new()
+            .help("It hist's grams")
+            .with_buckets(vec![1.0])
+            .build()
+            .unwrap_err();
+        assert_eq!(missing_name.kind(), PromErrorKind::MissingName);
+
+        let missing_help = HistogramBuilder::<AtomicF64>::new()
+            .name("some_histogram")
+            .with_buckets(vec![1.0])
+            .build()
+            .unwrap_err();
+        assert_eq!(missing_help.kind(), PromErrorKind::MissingHelp);
+
+        let missing_buckets = HistogramBuilder::<AtomicF64>::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .build()
+            .unwrap_err();
+        assert_eq!(missing_buckets.kind(), PromErrorKind::MissingBuckets);
+
+        let empty_buckets = HistogramBuilder::<AtomicF64>::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(Vec::new())
+            .build()
+            .unwrap_err();
+        assert_eq!(empty_buckets.kind(), PromErrorKind::MissingBuckets);
+    }
+
+    #[test]
+    fn millisecond_timer() {
+        use std::{thread, time::Duration};
+
+        let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("latency_ms")
+            .help("Latency in milliseconds")
+            .with_buckets(vec![10, 50, 100, 1000, u64::MAX])
+            .time_unit(TimeUnit::Milliseconds)
+            .build()
+            .unwrap();
+        assert_eq!(histogram.time_unit(), TimeUnit::Milliseconds);
+
+        {
+            let _timer = histogram.start_timer();
+            thread::sleep(Duration::from_millis(60));
+        }
+
+        assert_eq!(histogram.get_count(), 1);
+        assert!(histogram.get_sum() >= 60);
+        assert_eq!(histogram.core.value_at(0), Some(0));
+        assert_eq!(histogram.core.value_at(1), Some(0));
+
+        let mut local = histogram.local();
+        {
+            let _timer = local.start_timer();
+            thread::sleep(Duration::from_millis(20));
+        }
+        local.flush();
+
+        assert_eq!(histogram.get_count(), 2);
+        assert!(histogram.get_sum() >= 80);
+    }
+
+    #[test]
+    fn time_unit_conversion() {
+        use std::time::Duration;
+
+        let duration = Duration::from_micros(1_500_250);
+
+        assert_eq!(TimeUnit::Seconds.convert(duration), 1);
+        assert_eq!(TimeUnit::Milliseconds.convert(duration), 1500);
+        assert_eq!(TimeUnit::Microseconds.convert(duration), 1_500_250);
+    }
+
+    #[test]
+    fn cumulative_bucket_counts() {
+        let histogram: Histogram<AtomicF64> = HistogramBuilder::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(vec![1.0, 2.5, 5.0])
+            .build()
+            .unwrap();
+
+        for &val in &[0.5, 1.0, 2.0, 3.0, 4.0, 4.5, 100.0] {
+            histogram.observe(val);
+        }
+
+        assert_eq!(
+            histogram.bucket_counts(),
+            vec![(1.0, 2), (2.5, 3), (5.0, 6), (f64::INFINITY, 7)],
+        );
+
+        let mut buf = String::new();
+        (&histogram)
+            .encode_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(
+            buf,
+            "some_histogram_sum 115.0\n\
+             some_histogram_count 7\n\
+             some_histogram_bucket{le=\"1.0\"} 2\n\
+             some_histogram_bucket{le=\"2.5\"} 3\n\
+             some_histogram_bucket{le=\"5.0\"} 6\n\
+             some_histogram_bucket{le=\"+Inf\"} 7\n",
+        );
+    }
+
+    #[test]
+    fn explicit_infinite_bucket() {
+        let histogram: Histogram<AtomicF64> = HistogramBuilder::new()
+            .name("some_histogram")
+            .help("It hist's grams")
+            .with_buckets(DEFAULT_BUCKETS.to_vec())
+            .build()
+            .unwrap();
+        histogram.observe(20.0);
+
+        let counts = histogram.bucket_counts();
+        assert_eq!(counts.len(), DEFAULT_BUCKETS.len());
+        assert_eq!(counts.last(), Some(&(f64::INFINITY, 1)));
+    }
+
+    #[test]
+    fn bucket_values() {
+        let core: HistogramCore<AtomicU64> = HistogramCore::new(vec![1, 2, 3, 4]);
+        core.observe(1);
+        core.observe(3);
+        core.observe(3);
+
+        let mut values = core.values_iter();
+        assert_eq!(values.next(), Some(1));
+        assert_eq!(values.next(), Some(0));
+        assert_eq!(values.next(), Some(2));
+        assert_eq!(values.next(), Some(0));
+        assert_eq!(values.next(), None);
+
+        assert_eq!(core.value_at(2), Some(2));
+        assert_eq!(core.value_at(4), None);
+        assert_eq!(core.values(), vec![1, 0, 2, 0]);
+    }
+
+    #[test]
+    fn bucket_presets() {
+        assert_eq!(scaled_buckets(DEFAULT_BUCKETS, 1000.0), DEFAULT_BUCKETS_MS);
+        assert_eq!(
+            scaled_buckets(&[1.0, f64::INFINITY], 0.5),
+            [0.5, f64::INFINITY]
+        );
+
+        let bytes = bytes_buckets();
+        assert_eq!(bytes.len(), 25);
+        assert_eq!(&bytes[..4], &[128.0, 256.0, 512.0, 1024.0]);
+        assert_eq!(bytes[23], 1024.0 * 1024.0 * 1024.0);
+        assert_eq!(bytes[24], f64::INFINITY);
+        assert!(bytes
+            .windows(2)
+            .all(|pair| pair[1] == pair[0] * 2.0 || pair[1].is_infinite()));
+    }
+
+    #[test]
+    fn merge_from() {
+        let shard = |buckets: Vec<u64>| -> Histogram<AtomicU64> {
+            HistogramBuilder::new()
+                .name("shard_latency")
+                .help("Shard latency")
+                .with_buckets(buckets)
+                .build()
+                .unwrap()
+        };
+
+        let global = shard(vec![10, 100]);
+        let first = shard(vec![10, 100]);
+        let second = shard(vec![10, 100]);
+
+        first.observe(5);
+        first.observe(50);
+        second.observe(50);
+        second.observe(500);
+
+        global.merge_from(&first).unwrap();
+        global.merge_from(&second).unwrap();
+        assert_eq!(global.get_count(), 4);
+        assert_eq!(global.get_sum(), 605);
+        assert_eq!(global.core.values(), vec![1, 2]);
+
+        // The merged histograms are untouched
+        assert_eq!(first.get_count(), 2);
+
+        let err = global.merge_from(&shard(vec![10, 1000])).unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::MismatchedBuckets);
+        assert_eq!(global.get_count(), 4);
+    }
+
+    #[test]
+    fn buckets_detailed() {
+        let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("detailed")
+            .help("Detailed buckets")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+        histogram.observe(5);
+        histogram.observe(50);
+        histogram.observe(500);
+
+        let detailed = histogram.buckets_detailed();
+        assert_eq!(
+            detailed.iter().map(BucketInfo::le).collect::<Vec<_>>(),
+            vec![10.0, 100.0, f64::INFINITY],
+        );
+
+        let mut text = String::new();
+        (&histogram)
+            .encode_text(&mut text, &FormatOptions::new())
+            .unwrap();
+        for bucket in &detailed {
+            let sample = format!(
+                "detailed_bucket{{le=\"{}\"}} {}\n",
+                bucket.le_string(),
+                bucket.cumulative_count(),
+            );
+            assert!(
+                text.contains(&sample),
+                "{} is missing from {}",
+                sample,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn partial_clears() {
+        let core: HistogramCore<AtomicU64> = HistogramCore::new(vec![10, 100]);
+        let observe = || {
+            core.observe(5);
+            core.observe(50);
+        };
+
+        observe();
+        core.clear_buckets();
+        assert_eq!(core.values(), vec![0, 0]);
+        assert_eq!(core.get_count(), 2);
+        assert_eq!(core.get_sum(), 55);
+
+        core.clear();
+        observe();
+        core.clear_aggregate();
+        assert_eq!(core.values(), vec![1, 1]);
+        assert_eq!(core.get_count(), 0);
+        assert_eq!(core.get_sum(), 0);
+        // The buckets stay cumulative up to `+Inf`
+        assert_eq!(core.bucket_counts(), vec![(10, 1), (100, 2), (u64::MAX, 2)],);
+
+        core.clear();
+        observe();
+        core.clear();
+        assert_eq!(core.values(), vec![0, 0]);
+        assert_eq!(core.get_count(), 0);
+        assert_eq!(core.get_sum(), 0);
+    }
+
+    #[test]
+    fn debug_output() {
+        let histogram: Histogram = HistogramBuilder::new()
+            .name("debug_histogram")
+            .help("Hists grams")
+            .with_buckets(vec![0.5, 1.0])
+            .build()
+            .unwrap();
+        histogram.observe(0.25);
+        histogram.observe(5.0);
+
+        assert_eq!(
+            format!("{:?}", histogram),
+            "Histogram { name: \"debug_histogram\", labels: {}, count: 2, sum: 5.25, \
+             buckets: {0.5: 1, 1.0: 1, inf: 2} }",
+        );
+    }
+
+    #[test]
+    fn try_observe() {
+        let histogram: Histogram = HistogramBuilder::new()
+            .name("fallible")
+            .help("Fallible observations")
+            .with_buckets(vec![0.5, 1.0])
+            .build()
+            .unwrap();
+
+        histogram.try_observe(0.25).unwrap();
+        let err = histogram.try_observe(f64::NAN).unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidObservation);
+        assert_eq!(histogram.get_count(), 1);
+        assert_eq!(histogram.get_sum(), 0.25);
+
+        // Negative values are only rejected when asked to
+        histogram.try_observe(-0.25).unwrap();
+        assert!(histogram.try_observe_non_negative(-1.0).is_err());
+        assert!(histogram.try_observe_non_negative(f64::NAN).is_err());
+        histogram.try_observe_non_negative(0.0).unwrap();
+
+        assert_eq!(histogram.get_count(), 3);
+        assert!(histogram.get_sum().is_finite());
+        assert_eq!(histogram.get_sum(), 0.0);
+    }
+
+    #[test]
+    fn multi_label_histogram() {
+        let histogram: Histogram<AtomicU64> = HistogramBuilder::new()
+            .name("multi_label")
+            .help("Labels on every line")
+            .with_labels(vec![Label::new("method", "GET").unwrap()])
+            .label(Label::new("path", "/a,b").unwrap())
+            .with_buckets(vec![10])
+            .build()
+            .unwrap();
+        histogram.observe(5);
+
+        let mut buf = String::new();
+        (&histogram)
+            .encode_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(
+            buf,
+            "multi_label_sum{method=\"GET\",path=\"/a,b\"} 5\n\
+             multi_label_count{method=\"GET\",path=\"/a,b\"} 1\n\
+             multi_label_bucket{method=\"GET\",path=\"/a,b\",le=\"10\"} 1\n\
+             multi_label_bucket{method=\"GET\",path=\"/a,b\",le=\"+Inf\"} 1\n",
+        );
+    }
+
+    #[test]
+    fn float_buckets_range() {
+        let builder = || {
+            HistogramBuilder::<AtomicU64>::new()
+                .name("float_buckets")
+                .help("Buckets from float math")
+        };
+
+        let histogram = builder()
+            .float_buckets(vec![0.0, 2.5, 1e19])
+            .build()
+            .unwrap();
+        assert_eq!(histogram.buckets(), &[0, 2, 10_000_000_000_000_000_000]);
+
+        for out_of_range in [-1.0, 1e20, f64::NAN, f64::INFINITY] {
+            let err = builder()
+                .float_buckets(vec![1.0, out_of_range])
+                .build()
+                .unwrap_err();
+            assert_eq!(err.kind(), PromErrorKind::BucketOutOfRange);
+        }
+
+        // Exactly 2^63 is just past `i64::MAX`
+        let err = HistogramBuilder::<std::sync::atomic::AtomicI64>::new()
+            .name("float_buckets")
+            .help("Buckets from float math")
+            .float_buckets(vec![9_223_372_036_854_775_808.0])
+            .build()
+            .unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::BucketOutOfRange);
+    }
+
+    #[test]
+    fn observe_clamped() {
+        let histogram: Histogram<std::sync::atomic::AtomicI64> = HistogramBuilder::new()
+            .name("clamped")
+            .help("Clamped observations")
+            .with_buckets(vec![0, 10, 100])
+            .build()
+            .unwrap();
+
+        // A negative latency is floored at zero and lands in the first bucket
+        histogram.observe_clamped(-5, 0, 100);
+        assert_eq!(histogram.core.values(), vec![1, 0, 0]);
+        assert_eq!(histogram.get_sum(), 0);
+
+        histogram.observe_clamped(1000, 0, 100);
+        histogram.observe_clamped(50, 0, 100);
+        assert_eq!(histogram.core.values(), vec![1, 0, 2]);
+        assert_eq!(histogram.get_count(), 3);
+        assert_eq!(histogram.get_sum(), 150);
+    }
+
+    #[test]
+    fn for_each_sample() {
+        let histogram: Histogram = HistogramBuilder::new()
+            .name("visited")
+            .help("Visited samples")
+            .with_buckets(vec![0.5, 1.0])
+            .with_labels(vec![Label::new("kind", "test").unwrap()])
+            .build()
+            .unwrap();
+        histogram.observe(0.25);
+        histogram.observe(0.75);
+
+        let mut visited = Vec::new();
+        (&histogram).for_each_sample(&mut |suffix, labels, value| {
+            let le = labels
+                .iter()
+                .find(|label| label.name() == "le")
+                .map(|label| label.value().to_owned());
+
+            assert_eq!(labels[0], Label::new("kind", "test").unwrap());
+            visited.push((suffix.to_owned(), le, value));
+        });
+
+        // The sum and count plus one sample per bucket, including `+Inf`
+        assert_eq!(visited.len(), 5);
+        assert_eq!(
+            visited,
+            vec![
+                ("_sum".to_owned(), None, MetricValue::F64(1.0)),
+                ("_count".to_owned(), None, MetricValue::U64(2)),
+                (
+                    "_bucket".to_owned(),
+                    Some("0.5".to_owned()),
+                    MetricValue::U64(1)
+                ),
+                (
+                    "_bucket".to_owned(),
+                    Some("1.0".to_owned()),
+                    MetricValue::U64(2)
+                ),
+                (
+                    "_bucket".to_owned(),
+                    Some("+Inf".to_owned()),
+                    MetricValue::U64(2)
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn exemplar_timestamps() {
+        let histogram: Histogram = HistogramBuilder::new()
+            .name("traced")
+            .help("Traced observations")
+            .with_buckets(vec![0.5, 1.0])
+            .build()
+            .unwrap();
+
+        let trace = vec![Label::new("trace_id", "abc").unwrap()];
+        let observed_at = SystemTime::UNIX_EPOCH + Duration::from_micros(1_520_879_607_789_123);
+        histogram.observe_with_exemplar_at(0.75, trace.clone(), observed_at);
+        histogram.observe_with_exemplar_at(
+            3.0,
+            trace,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(5),
+        );
+
+        let mut buf = String::new();
+        (&histogram)
+            .encode_openmetrics_samples(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(
+            buf,
+            "traced_sum 3.75\n\
+             traced_count 2\n\
+             traced_bucket{le=\"0.5\"} 0\n\
+             traced_bucket{le=\"1.0\"} 1 # {trace_id=\"abc\"} 0.75 1520879607.789\n\
+             traced_bucket{le=\"+Inf\"} 2 # {trace_id=\"abc\"} 3.0 5.000\n",
+        );
+
+        // The exemplar timestamp is plain fractional seconds, never scientific notation
+        let line = buf.lines().nth(3).unwrap();
+        let timestamp = line.rsplit(' ').next().unwrap();
+        let (secs, millis) = timestamp.split_once('.').unwrap();
+        assert!(secs.bytes().all(|byte| byte.is_ascii_digit()));
+        assert!(millis.len() == 3 && millis.bytes().all(|byte| byte.is_ascii_digit()));
+
+        // The text format has no exemplars
+        let mut text = String::new();
+        (&histogram)
+            .encode_samples(&mut text, &FormatOptions::new())
+            .unwrap();
+        assert!(!text.contains('#'));
+
+        // Clearing the buckets drops their exemplars
+        histogram.clear_buckets();
+        assert_eq!(histogram.exemplars(), vec![None, None, None]);
+
+        let mut cleared = String::new();
+        (&histogram)
+            .encode_openmetrics_samples(&mut cleared, &FormatOptions::new())
+            .unwrap();
+        assert!(!cleared.contains('#'));
+    }
+
+    #[test]
+    fn saturating_count() {
+        let core: HistogramCore<AtomicF64> = HistogramCore::new(vec![1.0]);
+        core.count.set(u64::MAX - 1);
+
+        core.observe(0.5);
+        assert_eq!(core.get_count(), u64::MAX);
+        core.observe(0.5);
+        assert_eq!(core.get_count(), u64::MAX);
+
+        core.observe_n(2.0, 10);
+        assert_eq!(core.get_count(), u64::MAX);
+        assert_eq!(core.value_at(0), Some(2));
+
+        // Bucket counts saturate too, however they're incremented
+        let buckets: HistogramCore<AtomicF64> = HistogramCore::new(vec![1.0]);
+        buckets.observe_n(0.5, u64::MAX - 1);
+        buckets.observe(0.5);
+        buckets.observe(0.5);
+        assert_eq!(buckets.value_at(0), Some(u64::MAX));
+
+        buckets.observe_n(0.5, 10);
+        assert_eq!(buckets.value_at(0), Some(u64::MAX));
+
+        let merged: HistogramCore<AtomicF64> = HistogramCore::new(vec![1.0]);
+        merged.observe(0.5);
+        merged.merge_from(&buckets).unwrap();
+        assert_eq!(merged.value_at(0), Some(u64::MAX));
+        assert_eq!(merged.get_count(), u64::MAX);
+
+        // A saturated bucket followed by another non-empty one stays cumulative
+        let cumulative: HistogramCore<AtomicF64> = HistogramCore::new(vec![1.0, 2.0]);
+        cumulative.observe_n(0.5, u64::MAX);
+        cumulative.observe(1.5);
+        assert_eq!(
+            cumulative.bucket_counts(),
+            vec![(1.0, u64::MAX), (2.0, u64::MAX), (f64::INFINITY, u64::MAX)],
+        );
+    }
+
+    #[test]
+    fn saturating_sum() {
+        let near_max = u64::MAX - 1;
+
+        // Every way of adding to a histogram saturates its sum instead of wrapping it
+        let observed: HistogramCore<std::sync::atomic::AtomicU64> = HistogramCore::new(vec![10]);
+        observed.observe(near_max);
+        observed.observe(near_max);
+        assert_eq!(observed.get_sum(), u64::MAX);
+        observed.observe_n(near_max, 2);
+        assert_eq!(observed.get_sum(), u64::MAX);
+
+        let bucketed: HistogramCore<std::sync::atomic::AtomicU64> =
+            HistogramCore::new(vec![u64::MAX]);
+        bucketed.observe_bucket(near_max, u64::MAX).unwrap();
+        bucketed.observe_bucket(near_max, u64::MAX).unwrap();
+        assert_eq!(bucketed.get_sum(), u64::MAX);
+        assert_eq!(bucketed.value_at(0), Some(2));
+
+        let merged: HistogramCore<std::sync::atomic::AtomicU64> = HistogramCore::new(vec![10]);
+        merged.observe(near_max);
+        merged.merge_from(&observed).unwrap();
+        assert_eq!(merged.get_sum(), u64::MAX);
+        assert_eq!(merged.get_count(), 5);
+
+        let flushed: HistogramCore<std::sync::atomic::AtomicU64> = HistogramCore::new(vec![10]);
+        flushed.observe(near_max);
+        let mut local = flushed.local();
+        local.observe(near_max);
+        local.observe(near_max);
+        assert_eq!(local.get_sum(), u64::MAX);
+        local.flush();
+        assert_eq!(flushed.get_sum(), u64::MAX);
+        assert_eq!(flushed.get_count(), 3);
+    }
+
+    #[test]
+    fn observe_all() {
+        let global: Histogram<std::sync::atomic::AtomicU64> = HistogramBuilder::new()
+            .name("global_latency")
+            .help("Request latency")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+        let endpoint: Histogram<std::sync::atomic::AtomicU64> = HistogramBuilder::new()
+            .name("endpoint_latency")
+            .help("Request latency")
+            .with_buckets(vec![10, 100])
+            .build()
+            .unwrap();
+
+        super::observe_all(&[&global, &endpoint], 42);
+        super::observe_all(&[&global], 5);
+
+        assert_eq!(global.core.values(), vec![1, 1]);
+        assert_eq!(global.get_sum(), 47);
+        assert_eq!(endpoint.core.values(), vec![0, 1]);
+        assert_eq!(endpoint.get_sum(), 42);
+    }
+
+    #[test]
+    fn counter_views() {
+        static LATENCY: once_cell::sync::Lazy<Histogram> = once_cell::sync::Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("viewed_latency")
+                .help("Request latency")
+                .with_buckets(vec![0.5, 1.0])
+                .with_labels(vec![Label::new("method", "GET").unwrap()])
+                .build()
+                .unwrap()
+        });
+
+        LATENCY.observe(0.25);
+        LATENCY.observe(1.25);
+
+        let registry = crate::RegistryBuilder::new()
+            .register(Box::new(LATENCY.sum_counter()))
+            .register(Box::new(LATENCY.count_counter()))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP viewed_latency_count Request latency\n\
+             # TYPE viewed_latency_count counter\n\
+             viewed_latency_count{method=\"GET\"} 2\n\
+             # HELP viewed_latency_sum Request latency\n\
+             # TYPE viewed_latency_sum counter\n\
+             viewed_latency_sum{method=\"GET\"} 1.5\n",
+        );
+        registry.assert_valid().unwrap();
+        assert_eq!(LATENCY.count_counter().get(), MetricValue::U64(2));
+
+        // The views keep the histogram's raw help and unit
+        let sized: Histogram = HistogramBuilder::new()
+            .name("response_bytes")
+            .help("Response size\\n in bytes")
+            .with_buckets(vec![512.0])
+            .unit("bytes")
+            .build()
+            .unwrap()
+            .with_raw_help();
+        let sum = sized.sum_counter();
+        assert_eq!(sum.name(), "response_bytes_sum");
+        assert_eq!(sum.descriptor().unit(), Some("bytes"));
+        assert!(sum.descriptor().raw_help);
+        assert_eq!(sum.descriptor().help_escaped(), "Response size\\n in bytes");
+    }
 }
@@ -0,0 +1,172 @@
+use crate::{atomics::MetricValue, error::Result, registry::Registry};
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+impl Registry {
+    /// Encode every sample of [`Registry::snapshot`] as InfluxDB [line protocol], one line per
+    /// sample. The sample name is the measurement, labels become tags and the value is written
+    /// to the `value` field. Histograms are written as their `_sum`, `_count` and `_bucket`
+    /// samples, with one line per bucket tagged with `le`
+    ///
+    /// Integer values are written as integer fields like `value=42i`, unsigned values above
+    /// `i64::MAX` saturate since line protocol integers are signed. Float values are rounded to
+    /// the registry's [`RegistryBuilder::float_precision`]
+    ///
+    /// Every line shares the collection's timestamp in nanoseconds. Line protocol can't represent
+    /// `NaN` or infinite values, so samples with them are skipped, as are labels with empty values.
+    /// It has no way to escape newlines either, so newlines in label values are written as spaces
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::RegistryBuilder;
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(registry.collect_to_influx().unwrap().starts_with("answer value=42.0 "));
+    /// ```
+    ///
+    /// [`Registry::snapshot`]: crate::Registry#method.snapshot
+    /// [`RegistryBuilder::float_precision`]: crate::RegistryBuilder#method.float_precision
+    /// [line protocol]: https://docs.influxdata.com/influxdb/v1.8/write_protocols/line_protocol_reference/
+    pub fn collect_to_influx(&self) -> Result<String> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Impossible to fail, `UNIX_EPOCH` will never be sooner than the current system time")
+            .as_nanos();
+
+        self.collect_to_influx_at(timestamp)
+    }
+
+    fn collect_to_influx_at(&self, timestamp: u128) -> Result<String> {
+        let mut buf = String::new();
+        for (name, labels, value) in self.snapshot().samples() {
+            if let MetricValue::F64(float) = value {
+                if !float.is_finite() {
+                    continue;
+                }
+            }
+
+            escape_influx(&mut buf, name, false);
+            for label in labels.iter().filter(|label| !label.value().is_empty()) {
+                buf.push(',');
+                escape_influx(&mut buf, label.name(), true);
+                buf.push('=');
+                escape_influx(&mut buf, label.value(), true);
+            }
+
+            buf.push_str(" value=");
+            match value {
+                MetricValue::U64(int) => write!(buf, "{}i", int.min(i64::MAX as u64))?,
+                MetricValue::I64(int) => write!(buf, "{}i", int)?,
+                MetricValue::F64(float) => self.options.write_value(&mut buf, float)?,
+            }
+            writeln!(buf, " {}", timestamp)?;
+        }
+
+        Ok(buf)
+    }
+}
+
+/// Escapes the backslashes, commas and spaces of a measurement, along with equals signs for tag
+/// keys and values. Line protocol ends a line at any newline, so they're replaced with spaces
+fn escape_influx(buf: &mut String, value: &str, tag: bool) {
+    for c in value.chars() {
+        let c = if c == '\n' || c == '\r' { ' ' } else { c };
+        if c == '\\' || c == ',' || c == ' ' || (tag && c == '=') {
+            buf.push('\\');
+        }
+        buf.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        counter::Counter,
+        gauge::Gauge,
+        histogram::{Histogram, HistogramBuilder},
+        label::Label,
+        registry::RegistryBuilder,
+        AtomicF64,
+    };
+    use once_cell::sync::Lazy;
+    use std::sync::atomic::AtomicU64;
+
+    #[test]
+    fn line_protocol() {
+        static REQUESTS: Lazy<Counter<AtomicU64>> = Lazy::new(|| {
+            Counter::new("influx_requests", "Total requests")
+                .unwrap()
+                .with_labels(vec![
+                    Label::new("path", "/a b,c=d").unwrap(),
+                    Label::new("query", "x\\\ny").unwrap(),
+                    Label::new("empty", "").unwrap(),
+                ])
+        });
+        static TEMPERATURE: Lazy<Gauge<AtomicF64>> =
+            Lazy::new(|| Gauge::new("influx_temperature", "The temperature").unwrap());
+        static BYTES: Lazy<Counter<AtomicU64>> =
+            Lazy::new(|| Counter::new("influx_bytes", "Total bytes").unwrap());
+        static LATENCY: Lazy<Histogram<AtomicU64>> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("influx_latency")
+                .help("Latency")
+                .with_buckets(vec![10, 100])
+                .build()
+                .unwrap()
+        });
+
+        REQUESTS.inc_by(3);
+        TEMPERATURE.set(-1.5);
+        LATENCY.observe(50);
+        // Past 2^53, where integers can't be represented exactly as floats
+        BYTES.inc_by((1 << 53) + 1);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*REQUESTS))
+            .register(Box::new(&*TEMPERATURE))
+            .register(Box::new(&*LATENCY))
+            .register(Box::new(&*BYTES))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            registry
+                .collect_to_influx_at(1_600_000_000_000_000_000)
+                .unwrap(),
+            "influx_bytes value=9007199254740993i 1600000000000000000\n\
+             influx_latency_sum value=50i 1600000000000000000\n\
+             influx_latency_count value=1i 1600000000000000000\n\
+             influx_latency_bucket,le=10 value=0i 1600000000000000000\n\
+             influx_latency_bucket,le=100 value=1i 1600000000000000000\n\
+             influx_latency_bucket,le=+Inf value=1i 1600000000000000000\n\
+             influx_requests,path=/a\\ b\\,c\\=d,query=x\\\\\\ y value=3i 1600000000000000000\n\
+             influx_temperature value=-1.5 1600000000000000000\n",
+        );
+
+        // Floats are rounded to the registry's precision
+        let rounded = RegistryBuilder::new()
+            .register(Box::new(&*TEMPERATURE))
+            .float_precision(Some(1))
+            .build()
+            .unwrap();
+        assert!(rounded
+            .collect_to_influx()
+            .unwrap()
+            .starts_with("influx_temperature value=-2.0 "));
+
+        // Values line protocol can't represent are left out
+        TEMPERATURE.set(f64::NAN);
+        assert!(!registry
+            .collect_to_influx()
+            .unwrap()
+            .contains("influx_temperature"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    atomics::{FormatOptions, MetricValue},
+    error::{PromError, PromErrorKind, Result},
+    label::{write_labels, Label},
+    registry::{Collectable, Descriptor},
+};
+use std::{borrow::Cow, fmt::Write};
+
+/// Static key/value metadata about a target, like its version or environment, exposed as the
+/// labels of a sample that's always `1`
+///
+/// The [`OpenMetricsEncoder`] writes it with the OpenMetrics `info` type, the Prometheus text
+/// format has no such type so it's written as a gauge instead. Info metric names must end with
+/// `_info`, which OpenMetrics leaves out of the family name
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{labels, Info, RegistryBuilder};
+///
+/// let target = Info::new(
+///     "target_info",
+///     "Target metadata",
+///     labels!["version" => "1.2.3", "env" => "prod"].unwrap(),
+/// )
+/// .unwrap();
+///
+/// let registry = RegistryBuilder::new().register(Box::new(target)).build().unwrap();
+/// assert!(registry
+///     .collect_to_string()
+///     .unwrap()
+///     .contains("target_info{version=\"1.2.3\",env=\"prod\"} 1"));
+/// ```
+///
+/// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    descriptor: Descriptor,
+}
+
+impl Info {
+    /// Create a new `Info` metric with the given labels
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the name is invalid or doesn't end with `_info`
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        labels: impl Into<Vec<Label>>,
+    ) -> Result<Self> {
+        let descriptor = Descriptor::new(name, help, labels)?;
+
+        if !descriptor.name().ends_with("_info") || descriptor.name() == "_info" {
+            return Err(PromError::new(
+                format!(
+                    "The info metric {} must end with `_info`",
+                    descriptor.name()
+                ),
+                PromErrorKind::InvalidMetricName,
+            ));
+        }
+
+        Ok(Self { descriptor })
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        self.descriptor.labels()
+    }
+}
+
+impl Collectable for Info {
+    fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+        write!(buf, "{}", self.name())?;
+        if !self.labels().is_empty() {
+            write!(buf, "{{")?;
+            write_labels(buf, self.labels(), false)?;
+            write!(buf, "}}")?;
+        }
+        writeln!(buf, " 1")?;
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn openmetrics_type(&self) -> &'static str {
+        "info"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        visit("", self.labels(), MetricValue::U64(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoder::{Encoder, OpenMetricsEncoder},
+        registry::RegistryBuilder,
+    };
+
+    #[test]
+    fn info_type() {
+        let target = Info::new(
+            "target_info",
+            "Target metadata",
+            vec![Label::new("version", "1.2.3").unwrap()],
+        )
+        .unwrap();
+        let registry = RegistryBuilder::new()
+            .register(Box::new(target))
+            .build()
+            .unwrap();
+
+        let mut openmetrics = Vec::new();
+        OpenMetricsEncoder::new()
+            .encode(&registry.collect(), &mut openmetrics)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(openmetrics).unwrap(),
+            "# HELP target Target metadata\n\
+             # TYPE target info\n\
+             target_info{version=\"1.2.3\"} 1\n\
+             # EOF\n",
+        );
+
+        // The text format falls back to a gauge
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP target_info Target metadata\n\
+             # TYPE target_info gauge\n\
+             target_info{version=\"1.2.3\"} 1\n",
+        );
+    }
+
+    #[test]
+    fn info_suffix() {
+        let err = Info::new("target", "Target metadata", Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidMetricName);
+        assert!(Info::new("_info", "Target metadata", Vec::new()).is_err());
+    }
+}
@@ -1,34 +1,153 @@
-use crate::error::{PromError, PromErrorKind, Result};
-use std::{borrow::Cow, convert::TryFrom};
-
-/// Label names follow the regex `[a-zA-Z_][a-zA-Z0-9_]*` with the exception that labels starting with `__` are reserved,
-/// as well as the label name `le`
-// TODO: Make this const when rust/#68983 and rust/#49146 land
-pub(crate) fn valid_label_name(label: &str) -> bool {
-    let mut chars = label.chars();
-
-    !label.is_empty()
-        && label != "le"
-        && matches!(chars.next(), Some(next) if next.is_ascii_alphabetic() || next == '_')
-        && match chars.next() {
-            Some(next) if next.is_ascii_alphabetic() || next != '_' => true,
-            None => true,
-            _ => false,
+use crate::{
+    atomics::{AtomicF64, AtomicNum},
+    error::{PromError, PromErrorKind, Result},
+};
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    fmt::{self, Write},
+};
+
+/// Returns `true` if `label` is a valid label name, meant for validating names built at
+/// runtime before creating a [`Label`]
+///
+/// Label names follow the regex `[a-zA-Z_][a-zA-Z0-9_]*` with the exception that labels
+/// starting with `__` are reserved, as well as the label name `le` which histograms use
+/// for their buckets
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::valid_label_name;
+///
+/// assert!(valid_label_name("status_code"));
+/// assert!(valid_label_name("_private"));
+///
+/// assert!(!valid_label_name("__reserved"));
+/// assert!(!valid_label_name("le"));
+/// assert!(!valid_label_name("status code"));
+/// assert!(!valid_label_name("0status"));
+/// ```
+///
+/// [`Label`]: crate::Label
+pub const fn valid_label_name(label: &str) -> bool {
+    let bytes = label.as_bytes();
+    match bytes {
+        [] | [b'l', b'e'] | [b'_', b'_', ..] => return false,
+        _ => {}
+    }
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        let valid =
+            byte.is_ascii_alphabetic() || byte == b'_' || (idx != 0 && byte.is_ascii_digit());
+
+        if !valid {
+            return false;
         }
-        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        idx += 1;
+    }
+
+    true
 }
 
+/// Returns `true` if `metric` is a valid metric name, meant for validating names built at
+/// runtime before creating a metric
+///
 /// Metric names follow the regex `[a-zA-Z_:][a-zA-Z0-9_:]*`
-// TODO: Make this const when rust/#68983 and rust/#49146 land
-pub(crate) fn valid_metric_name(metric: &str) -> bool {
-    let mut chars = metric.chars();
+///
+/// Valid names are pure ascii, so the name is checked byte by byte which allows this to be
+/// used in const contexts
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::valid_metric_name;
+///
+/// assert!(valid_metric_name("http_requests_total"));
+/// assert!(valid_metric_name("job:http_requests:rate5m"));
+///
+/// assert!(!valid_metric_name(""));
+/// assert!(!valid_metric_name("http requests"));
+/// assert!(!valid_metric_name("5xx_responses"));
+/// ```
+pub const fn valid_metric_name(metric: &str) -> bool {
+    let bytes = metric.as_bytes();
+    if bytes.is_empty() {
+        return false;
+    }
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        let valid = byte.is_ascii_alphabetic()
+            || byte == b'_'
+            || byte == b':'
+            || (idx != 0 && byte.is_ascii_digit());
+
+        if !valid {
+            return false;
+        }
+        idx += 1;
+    }
 
-    !metric.is_empty()
-        && matches!(chars.next(), Some(next) if next.is_ascii_alphabetic() || next == '_' || next == ':')
-        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+    true
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Formats a set of labels as a compact `{name: value}` map for `Debug` impls
+pub(crate) struct DebugLabels<'a>(pub(crate) &'a [Label]);
+
+impl fmt::Debug for DebugLabels<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|label| (label.name(), label.value())))
+            .finish()
+    }
+}
+
+/// Builds metric names out of a namespace, subsystem and name like the Go client does
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MetricName;
+
+impl MetricName {
+    /// Join `namespace`, `subsystem` and `name` into `namespace_subsystem_name`, skipping any
+    /// empty parts
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::MetricName;
+    ///
+    /// assert_eq!(MetricName::build("http", "server", "requests").unwrap(), "http_server_requests");
+    /// assert_eq!(MetricName::build("", "server", "requests").unwrap(), "server_requests");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the joined name isn't a valid metric name
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn build(namespace: &str, subsystem: &str, name: &str) -> Result<Cow<'static, str>> {
+        let joined = [namespace, subsystem, name]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("_");
+
+        if valid_metric_name(&joined) {
+            Ok(Cow::Owned(joined))
+        } else {
+            Err(PromError::new(
+                "Metric name contains invalid characters",
+                PromErrorKind::InvalidMetricName,
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Label {
     pub(crate) name: Cow<'static, str>,
     pub(crate) value: Cow<'static, str>,
@@ -57,6 +176,73 @@ impl Label {
         }
     }
 
+    /// Create a new label like [`Label::new`], but also reject values containing control
+    /// characters other than `\n` and `\t`. Raw control characters are valid in label values but
+    /// can break downstream parsers and logs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Label;
+    ///
+    /// assert!(Label::new_strict("path", "/index.html").is_ok());
+    /// assert!(Label::new_strict("path", "/index\u{7}.html").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the name is invalid or the value contains a control character
+    ///
+    /// [`Label::new`]: crate::Label#method.new
+    /// [`PromError`]: crate::PromError
+    pub fn new_strict(
+        name: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Result<Self> {
+        let value = value.into();
+        if value
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t')
+        {
+            return Err(PromError::new(
+                "Label value contains control characters",
+                PromErrorKind::InvalidLabelValue,
+            ));
+        }
+
+        Self::new(name, value)
+    }
+
+    /// Create the `quantile` label of a summary's quantile, formatted with the same canonical
+    /// float formatting as histogram `le` labels so `0.99` is written as `0.99`. Whole quantiles
+    /// are written without a fractional part like Prometheus writes them, so `1.0` is `1`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Label;
+    ///
+    /// assert_eq!(Label::quantile(0.99).value(), "0.99");
+    /// assert_eq!(Label::quantile(1.0).value(), "1");
+    /// assert_eq!(Label::quantile(0.5).name(), "quantile");
+    /// ```
+    pub fn quantile(quantile: f64) -> Self {
+        let mut value = String::new();
+        if quantile.is_finite() && quantile.fract() == 0.0 {
+            // `-0.0` is written as `0` like the `0.0` it's equal to
+            let whole = if quantile == 0.0 { 0.0 } else { quantile };
+            write!(value, "{}", whole)
+        } else {
+            <AtomicF64 as AtomicNum>::format(quantile, &mut value, false)
+        }
+        .expect("Formatting into a `String` can't fail");
+
+        Self {
+            name: Cow::Borrowed("quantile"),
+            value: Cow::Owned(value),
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -64,6 +250,16 @@ impl Label {
     pub fn value(&self) -> &str {
         &self.value
     }
+
+    /// Borrow the label as a [`BorrowedLabel`]
+    ///
+    /// [`BorrowedLabel`]: crate::BorrowedLabel
+    pub fn as_borrowed(&self) -> BorrowedLabel<'_> {
+        BorrowedLabel {
+            name: &self.name,
+            value: &self.value,
+        }
+    }
 }
 
 impl<L, V> TryFrom<(L, V)> for Label
@@ -77,3 +273,460 @@ where
         Self::new(label, value)
     }
 }
+
+impl Label {
+    /// Create a set of labels from `(name, value)` pairs, validating every name
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::Label;
+    ///
+    /// let labels = Label::from_pairs(vec![("method", "GET"), ("status", "200")]).unwrap();
+    /// assert_eq!(labels[1], Label::new("status", "200").unwrap());
+    ///
+    /// assert!(Label::from_pairs(vec![("not a name", "GET")]).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] for the first pair with an invalid label name
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn from_pairs<I, L, V>(pairs: I) -> Result<Vec<Self>>
+    where
+        I: IntoIterator<Item = (L, V)>,
+        L: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        pairs.into_iter().map(Self::try_from).collect()
+    }
+}
+
+/// Create a `Result<Vec<Label>, PromError>` from `name => value` pairs, failing on the first
+/// invalid label name
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{labels, Label};
+///
+/// let labels = labels!["method" => "GET", "status" => String::from("200")].unwrap();
+/// assert_eq!(
+///     labels,
+///     vec![Label::new("method", "GET").unwrap(), Label::new("status", "200").unwrap()],
+/// );
+/// ```
+#[macro_export]
+macro_rules! labels {
+    ($($name:expr => $value:expr),* $(,)?) => {
+        ::std::vec![$($crate::Label::new($name, $value)),*]
+            .into_iter()
+            .collect::<::std::result::Result<::std::vec::Vec<$crate::Label>, $crate::PromError>>()
+    };
+}
+
+/// A label that borrows its name and value instead of requiring `'static` data
+///
+/// Meant for encoding transient, high-cardinality label sets (like request paths) with
+/// [`write_label_set`] without cloning every value into an owned `String`
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{write_label_set, BorrowedLabel};
+///
+/// let path = String::from("/index.html");
+/// let labels = [BorrowedLabel::new("path", &path).unwrap()];
+///
+/// let mut buf = String::new();
+/// write_label_set(&mut buf, labels.iter().copied()).unwrap();
+/// assert_eq!(buf, r#"{path="/index.html"}"#);
+/// ```
+///
+/// [`write_label_set`]: crate::write_label_set
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BorrowedLabel<'a> {
+    name: &'a str,
+    value: &'a str,
+}
+
+impl<'a> BorrowedLabel<'a> {
+    /// Create a new borrowed label with the given name and value.
+    ///
+    /// Returns `Err` if `label` doesn't follow the regex `[a-zA-Z_][a-zA-Z0-9_]*`
+    pub fn new(name: &'a str, value: &'a str) -> Result<Self> {
+        if valid_label_name(name) {
+            Ok(Self { name, value })
+        } else {
+            Err(PromError::new(
+                "Label name contains invalid characters",
+                PromErrorKind::InvalidLabelName,
+            ))
+        }
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn value(&self) -> &'a str {
+        self.value
+    }
+}
+
+impl<'a> From<&'a Label> for BorrowedLabel<'a> {
+    fn from(label: &'a Label) -> Self {
+        label.as_borrowed()
+    }
+}
+
+/// Writes a set of labels in the form `{name="value",other="value"}`, writing nothing
+/// if there are no labels
+pub fn write_label_set<'a, I>(buf: &mut String, labels: I) -> fmt::Result
+where
+    I: IntoIterator<Item = BorrowedLabel<'a>>,
+{
+    let mut labels = labels.into_iter().peekable();
+    if labels.peek().is_none() {
+        return Ok(());
+    }
+
+    write!(buf, "{{")?;
+    write_label_pairs(buf, labels, false)?;
+    write!(buf, "}}")
+}
+
+/// Writes `labels` as comma separated `name="value"` pairs without surrounding braces.
+/// If `leading_comma` is true and there's at least one label, a comma is written before
+/// the first label so the labels can follow other pairs like a group's bucket label
+pub(crate) fn write_labels(buf: &mut String, labels: &[Label], leading_comma: bool) -> fmt::Result {
+    write_label_pairs(buf, labels.iter().map(Label::as_borrowed), leading_comma)
+}
+
+fn write_label_pairs<'a, I>(buf: &mut String, labels: I, leading_comma: bool) -> fmt::Result
+where
+    I: IntoIterator<Item = BorrowedLabel<'a>>,
+{
+    for (i, label) in labels.into_iter().enumerate() {
+        if i != 0 || leading_comma {
+            write!(buf, ",")?;
+        }
+
+        write!(buf, "{}=", label.name())?;
+        escape_label_value(buf, label.value());
+    }
+
+    Ok(())
+}
+
+/// Writes `value` as a quoted label value, escaping backslashes, double quotes and newlines
+/// as required by the [text-based format]
+///
+/// Values without any of those characters are written as-is after a single scan
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::escape_label_value;
+///
+/// let mut buf = String::new();
+/// escape_label_value(&mut buf, "C:\\Users\n\"quoted\"");
+/// assert_eq!(buf, r#""C:\\Users\n\"quoted\"""#);
+/// ```
+///
+/// [text-based format]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+pub fn escape_label_value(buf: &mut String, value: &str) {
+    buf.push('"');
+    escape_unquoted(buf, value);
+    buf.push('"');
+}
+
+/// Writes `value` escaping backslashes, double quotes and newlines without surrounding it
+/// with quotes
+pub(crate) fn escape_unquoted(buf: &mut String, value: &str) {
+    match value.find(['\\', '"', '\n']) {
+        None => buf.push_str(value),
+
+        Some(idx) => {
+            buf.push_str(&value[..idx]);
+
+            for c in value[idx..].chars() {
+                match c {
+                    '\\' => buf.push_str("\\\\"),
+                    '"' => buf.push_str("\\\""),
+                    '\n' => buf.push_str("\\n"),
+                    c => buf.push(c),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{atomics::FormatOptions, counter::Counter, registry::Collectable};
+    use std::sync::atomic::AtomicU64;
+
+    #[test]
+    fn quantile_labels() {
+        use crate::histogram::{Histogram, HistogramBuilder};
+
+        let quantiles = [0.5, 0.9, 0.99, 1.0];
+        let values: Vec<String> = quantiles
+            .iter()
+            .map(|&quantile| Label::quantile(quantile).value().to_owned())
+            .collect();
+        assert_eq!(values, vec!["0.5", "0.9", "0.99", "1"]);
+        assert_eq!(Label::quantile(0.0).value(), "0");
+        assert_eq!(Label::quantile(-0.0).value(), "0");
+
+        // Fractional quantiles are formatted exactly like bucket boundaries
+        let histogram: Histogram<AtomicF64> = HistogramBuilder::new()
+            .name("quantiles")
+            .help("Formats quantiles")
+            .with_buckets(quantiles.to_vec())
+            .build()
+            .unwrap();
+        assert_eq!(histogram.le_strings()[..3], values[..3]);
+
+        let mut buf = String::new();
+        write_labels(&mut buf, &[Label::quantile(0.99)], false).unwrap();
+        assert_eq!(buf, r#"quantile="0.99""#);
+    }
+
+    #[test]
+    fn escape_plain_label_value() {
+        let mut buf = String::new();
+        escape_label_value(&mut buf, "/api/v1/users");
+        assert_eq!(buf, "\"/api/v1/users\"");
+
+        buf.clear();
+        escape_label_value(&mut buf, "");
+        assert_eq!(buf, "\"\"");
+
+        // Only the three special characters are escaped, unlike with `Debug`
+        buf.clear();
+        escape_label_value(&mut buf, "tab\there ünïcödé");
+        assert_eq!(buf, "\"tab\there ünïcödé\"");
+    }
+
+    #[test]
+    fn escape_special_label_value() {
+        let mut buf = String::new();
+        escape_label_value(&mut buf, "a\\b\"c\nd");
+        assert_eq!(buf, r#""a\\b\"c\nd""#);
+
+        buf.clear();
+        escape_label_value(&mut buf, "ünïcödé\n");
+        assert_eq!(buf, r#""ünïcödé\n""#);
+    }
+
+    #[test]
+    fn borrowed_labels_encode_like_owned() {
+        let method = String::from("GET");
+        let path = String::from("/some/\"quoted\"/path");
+
+        let counter: Counter<AtomicU64> = Counter::new("requests", "Counts requests")
+            .unwrap()
+            .with_labels(vec![
+                Label::new("method", method.clone()).unwrap(),
+                Label::new("path", path.clone()).unwrap(),
+            ]);
+        counter.inc();
+
+        let mut owned = String::new();
+        (&counter)
+            .encode_samples(&mut owned, &FormatOptions::new())
+            .unwrap();
+
+        let borrowed_labels = [
+            BorrowedLabel::new("method", &method).unwrap(),
+            BorrowedLabel::new("path", &path).unwrap(),
+        ];
+        let mut borrowed = String::from("requests");
+        write_label_set(&mut borrowed, borrowed_labels.iter().copied()).unwrap();
+        borrowed.push_str(" 1\n");
+
+        assert_eq!(owned, borrowed);
+
+        let mut from_owned = String::new();
+        write_label_set(
+            &mut from_owned,
+            counter.labels().iter().map(Label::as_borrowed),
+        )
+        .unwrap();
+        let mut from_borrowed = String::new();
+        write_label_set(&mut from_borrowed, borrowed_labels.iter().copied()).unwrap();
+        assert_eq!(from_owned, from_borrowed);
+    }
+
+    #[test]
+    fn write_no_labels() {
+        let mut buf = String::new();
+        write_labels(&mut buf, &[], false).unwrap();
+        assert_eq!(buf, "");
+
+        write_labels(&mut buf, &[], true).unwrap();
+        assert_eq!(buf, "");
+    }
+
+    #[test]
+    fn write_one_label() {
+        let labels = [Label::new("first", "1").unwrap()];
+
+        let mut without_comma = String::new();
+        write_labels(&mut without_comma, &labels, false).unwrap();
+        assert_eq!(without_comma, r#"first="1""#);
+
+        let mut with_comma = String::new();
+        write_labels(&mut with_comma, &labels, true).unwrap();
+        assert_eq!(with_comma, r#",first="1""#);
+    }
+
+    #[test]
+    fn write_many_labels() {
+        let labels = [
+            Label::new("first", "1").unwrap(),
+            Label::new("second", "2").unwrap(),
+            Label::new("third", "3").unwrap(),
+        ];
+
+        let mut without_comma = String::new();
+        write_labels(&mut without_comma, &labels, false).unwrap();
+        assert_eq!(without_comma, r#"first="1",second="2",third="3""#);
+
+        let mut with_comma = String::new();
+        write_labels(&mut with_comma, &labels, true).unwrap();
+        assert_eq!(with_comma, r#",first="1",second="2",third="3""#);
+    }
+
+    #[test]
+    fn labels_macro() {
+        let labels = labels!["a" => "1", "b" => "2",].unwrap();
+        assert_eq!(
+            labels,
+            vec![Label::new("a", "1").unwrap(), Label::new("b", "2").unwrap()]
+        );
+
+        assert!(labels![].unwrap().is_empty());
+        assert_eq!(
+            labels!["a" => "1", "not valid" => "2"].unwrap_err().kind(),
+            PromErrorKind::InvalidLabelName
+        );
+    }
+
+    #[test]
+    fn label_pairs() {
+        let labels = Label::from_pairs(vec![("a", "1"), ("b", "2")]).unwrap();
+        assert_eq!(
+            labels,
+            vec![Label::new("a", "1").unwrap(), Label::new("b", "2").unwrap()]
+        );
+
+        let invalid = Label::from_pairs(vec![("a", "1"), ("__reserved", "2")]).unwrap_err();
+        assert_eq!(invalid.kind(), PromErrorKind::InvalidLabelName);
+    }
+
+    #[test]
+    fn empty_borrowed_label_set() {
+        let mut buf = String::new();
+        write_label_set(&mut buf, Vec::new()).unwrap();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn invalid_borrowed_label() {
+        assert_eq!(
+            BorrowedLabel::new("not valid", "value").unwrap_err().kind(),
+            PromErrorKind::InvalidLabelName,
+        );
+    }
+
+    #[test]
+    fn strict_label_values() {
+        // Lenient labels accept anything, the NUL is kept as-is
+        let lenient = Label::new("path", "/index\0.html").unwrap();
+        assert_eq!(lenient.value(), "/index\0.html");
+
+        let err = Label::new_strict("path", "/index\0.html").unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidLabelValue);
+
+        // Newlines and tabs are escaped or harmless, so they're still allowed
+        assert!(Label::new_strict("path", "multi\nline\tvalue").is_ok());
+        assert_eq!(
+            Label::new_strict("not a name", "value").unwrap_err().kind(),
+            PromErrorKind::InvalidLabelName,
+        );
+    }
+
+    #[test]
+    fn namespaced_names() {
+        assert_eq!(
+            MetricName::build("http", "server", "requests_total").unwrap(),
+            "http_server_requests_total",
+        );
+        assert_eq!(
+            MetricName::build("http", "", "requests_total").unwrap(),
+            "http_requests_total",
+        );
+        assert_eq!(
+            MetricName::build("", "server", "requests_total").unwrap(),
+            "server_requests_total",
+        );
+        assert_eq!(
+            MetricName::build("", "", "requests_total").unwrap(),
+            "requests_total",
+        );
+
+        let empty = MetricName::build("", "", "").unwrap_err();
+        assert_eq!(empty.kind(), PromErrorKind::InvalidMetricName);
+        assert!(MetricName::build("http server", "", "requests").is_err());
+        assert!(MetricName::build("", "", "0requests").is_err());
+    }
+
+    #[test]
+    fn label_name_validation() {
+        for valid in [
+            "a",
+            "_",
+            "_a",
+            "a_b",
+            "a__b",
+            "status_code",
+            "a1",
+            "_1",
+            "lee",
+            "l",
+        ] {
+            assert!(valid_label_name(valid), "{} should be valid", valid);
+        }
+
+        for invalid in [
+            "", "le", "__", "__name__", "a b", "a-b", "a:b", "1a", "é", "a\0",
+        ] {
+            assert!(!valid_label_name(invalid), "{} should be invalid", invalid);
+        }
+    }
+
+    #[test]
+    fn metric_name_validation() {
+        for valid in [
+            "a",
+            "_",
+            ":",
+            "a:b",
+            "a_b",
+            "a1",
+            "__name",
+            "http_requests_total",
+        ] {
+            assert!(valid_metric_name(valid), "{} should be valid", valid);
+        }
+
+        for invalid in ["", "1a", "a b", "a-b", "a.b", "é"] {
+            assert!(!valid_metric_name(invalid), "{} should be invalid", invalid);
+        }
+    }
+}
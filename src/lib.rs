@@ -8,20 +8,42 @@
 )]
 
 mod atomics;
+mod callback;
 pub mod counter;
+mod encoder;
 mod error;
 pub mod gauge;
 mod group;
 pub mod histogram;
+#[cfg(feature = "influx")]
+mod influx;
+mod info;
 mod label;
+#[cfg(all(feature = "process", target_os = "linux"))]
+mod process;
 mod registry;
+mod snapshot;
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
 mod timer;
+mod validate;
+mod vec;
 
-pub use atomics::AtomicF64;
+pub use atomics::{AtomicF64, FormatOptions, MetricValue};
+pub use callback::CallbackGauge;
 pub use counter::Counter;
+pub use encoder::{Encoder, OpenMetricsEncoder, TextEncoder, OPENMETRICS_FORMAT, TEXT_FORMAT};
 pub use error::{PromError, PromErrorKind};
 pub use gauge::Gauge;
 pub use group::{CounterGroup, Group, HistogramGroup, Key};
-pub use label::Label;
-pub use registry::{Registry, RegistryBuilder};
-pub use timer::Timer;
+pub use info::Info;
+pub use label::{
+    escape_label_value, valid_label_name, valid_metric_name, write_label_set, BorrowedLabel, Label,
+    MetricName,
+};
+#[cfg(all(feature = "process", target_os = "linux"))]
+pub use process::ProcessCollector;
+pub use registry::{Collectable, Descriptor, Metric, RegistrationGuard, Registry, RegistryBuilder};
+pub use snapshot::{MetricDelta, RegistrySnapshot};
+pub use timer::{Observable, TimeUnit, Timer};
+pub use vec::CounterVec;
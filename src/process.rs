@@ -0,0 +1,310 @@
+//! Standard process metrics read from `/proc/self`, only available on Linux with the `process` feature
+//!
+//! # Examples
+//!
+//! ```rust
+//! use prometheus_rs::{ProcessCollector, RegistryBuilder};
+//!
+//! let registry = RegistryBuilder::new()
+//!     .register(Box::new(ProcessCollector::new()))
+//!     .build()
+//!     .unwrap();
+//!
+//! println!("{}", registry.collect_to_string().unwrap());
+//! ```
+
+use crate::{
+    atomics::{FormatOptions, MetricValue},
+    error::Result,
+    label::Label,
+    registry::{Collectable, Descriptor},
+};
+use std::{fmt::Write, fs, io};
+
+/// The number of clock ticks per second used by `/proc/self/stat`, `USER_HZ` is 100 on every
+/// mainstream Linux architecture
+const CLOCK_TICKS_PER_SECOND: f64 = 100.0;
+
+/// The name, sample suffix, help and type of every metric family reported by [`ProcessCollector`].
+/// The suffix is the part of the name after the collector's `process` name
+const FAMILIES: [(&str, &str, &str, &str); 7] = [
+    (
+        "process_cpu_seconds_total",
+        "_cpu_seconds_total",
+        "Total user and system CPU time spent in seconds.",
+        "counter",
+    ),
+    (
+        "process_resident_memory_bytes",
+        "_resident_memory_bytes",
+        "Resident memory size in bytes.",
+        "gauge",
+    ),
+    (
+        "process_virtual_memory_bytes",
+        "_virtual_memory_bytes",
+        "Virtual memory size in bytes.",
+        "gauge",
+    ),
+    (
+        "process_open_fds",
+        "_open_fds",
+        "Number of open file descriptors.",
+        "gauge",
+    ),
+    (
+        "process_max_fds",
+        "_max_fds",
+        "Maximum number of open file descriptors.",
+        "gauge",
+    ),
+    (
+        "process_threads",
+        "_threads",
+        "Number of OS threads in the process.",
+        "gauge",
+    ),
+    (
+        "process_start_time_seconds",
+        "_start_time_seconds",
+        "Start time of the process since unix epoch in seconds.",
+        "gauge",
+    ),
+];
+
+/// A collector that reports the standard Prometheus process metrics for the current process
+///
+/// The following series are emitted every time the collector is encoded
+///
+/// - `process_cpu_seconds_total`: Total user and system CPU time spent in seconds
+/// - `process_resident_memory_bytes`: Resident memory size in bytes
+/// - `process_virtual_memory_bytes`: Virtual memory size in bytes
+/// - `process_open_fds`: Number of open file descriptors
+/// - `process_max_fds`: Maximum number of open file descriptors
+/// - `process_threads`: Number of OS threads in the process
+/// - `process_start_time_seconds`: Start time of the process since the unix epoch in seconds
+///
+/// If `/proc` can't be read, nothing is emitted instead of failing the whole scrape
+#[derive(Debug)]
+pub struct ProcessCollector {
+    descriptor: Descriptor,
+}
+
+impl ProcessCollector {
+    pub fn new() -> Self {
+        Self {
+            descriptor: Descriptor::new("process", "Process metrics read from /proc", Vec::new())
+                .expect("`process` is a valid metric name"),
+        }
+    }
+
+    fn encode(&self, buf: &mut String, headers: bool, options: &FormatOptions) -> Result<()> {
+        let stats = match ProcessStats::read() {
+            Ok(stats) => stats,
+            Err(_) => return Ok(()),
+        };
+
+        for ((name, _, help, metric_type), value) in FAMILIES.iter().zip(stats.values().iter()) {
+            if headers {
+                writeln!(buf, "# HELP {} {}", name, help)?;
+                writeln!(buf, "# TYPE {} {}", name, metric_type)?;
+            }
+
+            write!(buf, "{} ", name)?;
+            options.write_value(buf, *value)?;
+            writeln!(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ProcessCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collectable for ProcessCollector {
+    /// Encodes every process metric family along with its own `# HELP` and `# TYPE` headers
+    fn encode_text(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode(buf, true, options)
+    }
+
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode(buf, false, options)
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "untyped"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    /// Every family's sample, named with the suffix of the family's name after the collector's
+    /// `process` name. Nothing is reported if `/proc` can't be read
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        let stats = match ProcessStats::read() {
+            Ok(stats) => stats,
+            Err(_) => return,
+        };
+
+        for ((_, suffix, _, _), &value) in FAMILIES.iter().zip(stats.values().iter()) {
+            visit(suffix, &[], value.into());
+        }
+    }
+
+    fn text_size_hint(&self) -> usize {
+        FAMILIES
+            .iter()
+            .map(|(name, _, help, metric_type)| {
+                // The headers plus a single sample line with room for any value
+                "# HELP  \n# TYPE  \n".len() + 3 * name.len() + help.len() + metric_type.len() + 26
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct ProcessStats {
+    cpu_seconds: f64,
+    resident_memory_bytes: f64,
+    virtual_memory_bytes: f64,
+    open_fds: f64,
+    max_fds: f64,
+    threads: f64,
+    start_time_seconds: f64,
+}
+
+impl ProcessStats {
+    fn read() -> io::Result<Self> {
+        let mut stats = Self::default();
+
+        // The command name in the second field is wrapped in parentheses and may contain spaces,
+        // so only the fields after the closing parenthesis are split on whitespace
+        let stat = fs::read_to_string("/proc/self/stat")?;
+        let fields: Vec<&str> = stat
+            .rsplit_once(')')
+            .map(|(_, rest)| rest.split_whitespace().collect())
+            .ok_or_else(|| invalid_data("malformed /proc/self/stat"))?;
+
+        // `fields[0]` is the third field of the stat file (the process state)
+        let field = |idx: usize| -> io::Result<f64> {
+            fields
+                .get(idx - 3)
+                .and_then(|field| field.parse().ok())
+                .ok_or_else(|| invalid_data("malformed /proc/self/stat"))
+        };
+
+        stats.cpu_seconds = (field(14)? + field(15)?) / CLOCK_TICKS_PER_SECOND;
+        stats.start_time_seconds = boot_time()? + field(22)? / CLOCK_TICKS_PER_SECOND;
+
+        let status = fs::read_to_string("/proc/self/status")?;
+        for line in status.lines() {
+            let mut parts = line.split_whitespace();
+            let key = parts.next();
+            let value: f64 = parts.next().and_then(|v| v.parse().ok()).unwrap_or(0.0);
+
+            match key {
+                Some("VmRSS:") => stats.resident_memory_bytes = value * 1024.0,
+                Some("VmSize:") => stats.virtual_memory_bytes = value * 1024.0,
+                Some("Threads:") => stats.threads = value,
+                _ => {}
+            }
+        }
+
+        stats.open_fds = fs::read_dir("/proc/self/fd")?.count() as f64;
+
+        let limits = fs::read_to_string("/proc/self/limits")?;
+        stats.max_fds = limits
+            .lines()
+            .find(|line| line.starts_with("Max open files"))
+            .and_then(|line| line.split_whitespace().nth(3))
+            .and_then(|soft| soft.parse().ok())
+            .unwrap_or(f64::INFINITY);
+
+        Ok(stats)
+    }
+
+    /// Every stat in the same order as [`FAMILIES`]
+    fn values(&self) -> [f64; 7] {
+        [
+            self.cpu_seconds,
+            self.resident_memory_bytes,
+            self.virtual_memory_bytes,
+            self.open_fds,
+            self.max_fds,
+            self.threads,
+            self.start_time_seconds,
+        ]
+    }
+}
+
+/// Reads the system boot time in seconds since the unix epoch from `/proc/stat`
+fn boot_time() -> io::Result<f64> {
+    fs::read_to_string("/proc/stat")?
+        .lines()
+        .find(|line| line.starts_with("btime"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|btime| btime.parse().ok())
+        .ok_or_else(|| invalid_data("missing btime in /proc/stat"))
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::RegistryBuilder;
+
+    #[test]
+    fn emits_process_metrics() {
+        let registry = RegistryBuilder::new()
+            .register(Box::new(ProcessCollector::new()))
+            .build()
+            .unwrap();
+        let output = registry.collect_to_string().unwrap();
+
+        for name in &[
+            "process_cpu_seconds_total",
+            "process_resident_memory_bytes",
+            "process_virtual_memory_bytes",
+            "process_open_fds",
+            "process_max_fds",
+            "process_threads",
+            "process_start_time_seconds",
+        ] {
+            assert!(output.contains(&format!("# TYPE {} ", name)), "{}", output);
+            assert!(output.contains(&format!("\n{} ", name)), "{}", output);
+        }
+
+        assert!(output.contains("# TYPE process_cpu_seconds_total counter\n"));
+        assert!(!output.contains("# TYPE process untyped"));
+        assert!(registry.text_size_hint() >= output.len());
+
+        // Every family is a single series named the same as in the text format
+        let series: Vec<String> = registry
+            .series()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            series,
+            FAMILIES
+                .iter()
+                .map(|(name, _, _, _)| name.to_string())
+                .collect::<Vec<_>>(),
+        );
+        for (name, suffix, _, _) in &FAMILIES {
+            assert_eq!(format!("process{}", suffix), *name);
+        }
+        assert!(registry
+            .snapshot()
+            .get("process_threads", &[])
+            .is_some_and(|threads| threads.as_f64() >= 1.0));
+    }
+}
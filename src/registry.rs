@@ -1,23 +1,56 @@
 use crate::{
+    atomics::{AtomicF64, FormatOptions, MetricValue},
+    callback::CallbackGauge,
+    encoder::counter_family,
     error::{PromError, PromErrorKind, Result},
-    label::{valid_metric_name, Label},
+    gauge::Gauge,
+    histogram::{Histogram, HistogramBuilder, DEFAULT_BUCKETS},
+    label::{escape_label_value, valid_metric_name, Label},
+    snapshot::RegistrySnapshot,
+    validate::validate_exposition,
 };
-use std::{borrow::Cow, fmt};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock, RwLockReadGuard, Weak,
+    },
+    time::{Instant, SystemTime},
+};
+
+/// The counter the registry reports the scrape errors of failed collectors in
+const SCRAPE_ERRORS: &str = "collector_scrape_errors_total";
+
+/// Whether `name` is taken by the registry's [`SCRAPE_ERRORS`] counter, which shares its
+/// OpenMetrics family with a metric named without the `_total` suffix
+fn is_scrape_errors(name: &str) -> bool {
+    counter_family(name) == counter_family(SCRAPE_ERRORS)
+}
 
 pub struct RegistryBuilder {
     inputs: Option<Vec<Box<dyn Collectable + Send + Sync>>>,
+    self_instrument: bool,
+    float_precision: Option<usize>,
 }
 
 impl RegistryBuilder {
     pub fn new() -> Self {
-        Self { inputs: None }
+        Self {
+            inputs: None,
+            self_instrument: false,
+            float_precision: None,
+        }
     }
 
+    /// Registers every collector in `inputs`, keeping any that were already registered
     pub fn register_all(
         mut self,
         inputs: impl Into<Vec<Box<dyn Collectable + Send + Sync>>>,
     ) -> Self {
-        self.inputs = Some(inputs.into());
+        self.inputs
+            .get_or_insert_with(Vec::new)
+            .extend(inputs.into());
         self
     }
 
@@ -31,6 +64,106 @@ impl RegistryBuilder {
         self
     }
 
+    /// Registers every collector in `inputs`, keeping any that were already registered
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{Collectable, Counter, RegistryBuilder};
+    /// use once_cell::sync::Lazy;
+    ///
+    /// static REQUESTS: Lazy<Counter> = Lazy::new(|| Counter::new("requests", "Total requests").unwrap());
+    /// static ERRORS: Lazy<Counter> = Lazy::new(|| Counter::new("errors", "Total errors").unwrap());
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_iter(vec![&*REQUESTS, &*ERRORS].into_iter().map(|counter| {
+    ///         Box::new(counter) as Box<dyn Collectable + Send + Sync>
+    ///     }))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(registry.collect().len(), 2);
+    /// ```
+    pub fn register_iter(
+        mut self,
+        inputs: impl IntoIterator<Item = Box<dyn Collectable + Send + Sync>>,
+    ) -> Self {
+        self.inputs.get_or_insert_with(Vec::new).extend(inputs);
+        self
+    }
+
+    /// Registers a [`CallbackGauge`] that calls `callback` every time the registry is collected
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::RegistryBuilder;
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if `name` doesn't follow the [prometheus metric name specification]
+    ///
+    /// [`CallbackGauge`]: crate::CallbackGauge
+    /// [`PromError`]: crate::PromError
+    /// [prometheus metric name specification]: https://prometheus.io/docs/concepts/data_model/#metric-names-and-labels
+    pub fn register_gauge_fn<F>(
+        self,
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        callback: F,
+    ) -> Result<Self>
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        let gauge = CallbackGauge::new(name, help, callback)?;
+        Ok(self.register(Box::new(gauge)))
+    }
+
+    /// Makes the registry report metrics about itself, a `registry_collect_duration_seconds`
+    /// histogram and a `registry_last_collect_timestamp` gauge which are updated every time
+    /// the registry is collected into a string and emitted after the registered metrics
+    pub fn self_instrument(mut self) -> Self {
+        self.self_instrument = true;
+        self
+    }
+
+    /// Write float sample values with `precision` significant digits when the registry is
+    /// encoded, turning values like `0.30000000000000004` into `0.3`. Values are written exactly
+    /// when it's `None`, which is the default. `NaN`, `+Inf` and `-Inf`, label values like
+    /// histogram buckets and exemplar values are never rounded
+    ///
+    /// The precision is passed to every collector through the [`FormatOptions`] of the
+    /// registry's encoding methods and of the families gathered by [`Registry::collect`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::RegistryBuilder;
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("ratio", "A messy ratio", || 0.1 + 0.2)
+    ///     .unwrap()
+    ///     .float_precision(Some(6))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(registry.collect_to_string().unwrap().ends_with("ratio 0.3\n"));
+    /// ```
+    ///
+    /// [`FormatOptions`]: crate::FormatOptions
+    /// [`Registry::collect`]: crate::Registry#method.collect
+    pub fn float_precision(mut self, precision: Option<usize>) -> Self {
+        self.float_precision = precision;
+        self
+    }
+
     pub fn build(self) -> Result<Registry> {
         let raw_inputs = self.inputs.ok_or_else(|| {
             PromError::new(
@@ -58,14 +191,114 @@ impl RegistryBuilder {
                     format!("{} was registered twice", input.descriptor().name()),
                     PromErrorKind::DuplicatedCollector,
                 ));
-            } else {
-                inputs.push(input);
             }
+
+            // Metrics in the same family must share a type, otherwise the output has conflicting `# TYPE` lines
+            if let Some(coll) = inputs.iter().find(|coll| {
+                coll.descriptor().name() == input.descriptor().name()
+                    && coll.metric_type() != input.metric_type()
+            }) {
+                return Err(PromError::new(
+                    format!(
+                        "{} was registered as both a {} and a {}",
+                        input.descriptor().name(),
+                        coll.metric_type(),
+                        input.metric_type(),
+                    ),
+                    PromErrorKind::DuplicatedCollector,
+                ));
+            }
+
+            // Histograms in the same family must share their buckets, otherwise the family's
+            // `_bucket` samples have inconsistent `le` labels
+            if input.metric_type() == "histogram" {
+                if let Some(coll) = inputs.iter().find(|coll| {
+                    coll.descriptor().name() == input.descriptor().name()
+                        && bucket_bounds(&***coll) != bucket_bounds(&*input)
+                }) {
+                    return Err(PromError::new(
+                        format!(
+                            "{} was registered with the buckets {:?} and {:?}",
+                            input.descriptor().name(),
+                            bucket_bounds(&**coll),
+                            bucket_bounds(&*input),
+                        ),
+                        PromErrorKind::DuplicatedCollector,
+                    ));
+                }
+            }
+
+            inputs.push(input);
         }
 
+        // The scrape errors counter is reported whether or not the registry instruments itself
+        if let Some(input) = inputs
+            .iter()
+            .find(|input| is_scrape_errors(input.descriptor().name()))
+        {
+            return Err(PromError::new(
+                format!(
+                    "{} is reserved for the registry's own metrics",
+                    input.descriptor().name()
+                ),
+                PromErrorKind::DuplicatedCollector,
+            ));
+        }
+
+        let self_metrics = if self.self_instrument {
+            let self_metrics = SelfMetrics::new()?;
+
+            if let Some(input) = inputs.iter().find(|input| {
+                let name = input.descriptor().name();
+                name == self_metrics.collect_duration.name()
+                    || name == self_metrics.last_collect.name()
+            }) {
+                return Err(PromError::new(
+                    format!(
+                        "{} is reserved for the registry's own metrics",
+                        input.descriptor().name()
+                    ),
+                    PromErrorKind::DuplicatedCollector,
+                ));
+            }
+
+            Some(self_metrics)
+        } else {
+            None
+        };
+
         inputs.sort_unstable_by(|a, b| a.descriptor().name().cmp(b.descriptor().name()));
 
-        Ok(Registry { inputs })
+        Ok(Registry {
+            inputs,
+            self_metrics,
+            scoped: Arc::new(ScopedCollectors::default()),
+            last_changed: Mutex::new(RegistrySnapshot::default()),
+            options: FormatOptions::new().with_float_precision(self.float_precision),
+        })
+    }
+}
+
+/// The `le` label values of a histogram's buckets, taken from [`Collectable::current_values`]
+///
+/// [`Collectable::current_values`]: crate::Collectable#method.current_values
+fn bucket_bounds(collector: &dyn Collectable) -> Vec<String> {
+    collector
+        .current_values()
+        .into_iter()
+        .filter(|(suffix, _, _)| *suffix == "_bucket")
+        .filter_map(|(_, labels, _)| {
+            labels
+                .into_iter()
+                .find(|label| label.name() == "le")
+                .map(|label| label.value.into_owned())
+        })
+        .collect()
+}
+
+impl Default for RegistryBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -87,22 +320,500 @@ impl fmt::Debug for RegistryBuilder {
 
 pub struct Registry {
     inputs: Vec<Box<dyn Collectable + Send + Sync>>,
+    self_metrics: Option<SelfMetrics>,
+    /// Collectors registered at runtime with [`Registry::register_scoped`], shared with their
+    /// [`RegistrationGuard`]s
+    ///
+    /// [`Registry::register_scoped`]: crate::Registry#method.register_scoped
+    /// [`RegistrationGuard`]: crate::RegistrationGuard
+    scoped: Arc<ScopedCollectors>,
+    /// The snapshot taken by the last call to [`Registry::collect_changed`]
+    ///
+    /// [`Registry::collect_changed`]: crate::Registry#method.collect_changed
+    last_changed: Mutex<RegistrySnapshot>,
+    /// How sample values are written, see [`RegistryBuilder::float_precision`]
+    ///
+    /// [`RegistryBuilder::float_precision`]: crate::RegistryBuilder#method.float_precision
+    pub(crate) options: FormatOptions,
+}
+
+#[derive(Default)]
+struct ScopedCollectors {
+    next_id: AtomicU64,
+    collectors: RwLock<Vec<(u64, Box<dyn Collectable + Send + Sync>)>>,
+}
+
+impl ScopedCollectors {
+    fn read(&self) -> RwLockReadGuard<'_, Vec<(u64, Box<dyn Collectable + Send + Sync>)>> {
+        self.collectors
+            .read()
+            .expect("a registry's scoped collectors lock was poisoned")
+    }
+}
+
+/// Unregisters a collector registered with [`Registry::register_scoped`] when it's dropped
+///
+/// The guard only holds a weak reference to the registry, so it doesn't keep the registry alive
+/// and does nothing if the registry was dropped first
+///
+/// [`Registry::register_scoped`]: crate::Registry#method.register_scoped
+#[must_use = "the collector is unregistered as soon as the guard is dropped"]
+pub struct RegistrationGuard {
+    scoped: Weak<ScopedCollectors>,
+    id: u64,
+}
+
+impl Drop for RegistrationGuard {
+    fn drop(&mut self) {
+        if let Some(scoped) = self.scoped.upgrade() {
+            scoped
+                .collectors
+                .write()
+                .expect("a registry's scoped collectors lock was poisoned")
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+impl fmt::Debug for RegistrationGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistrationGuard")
+            .field("id", &self.id)
+            .finish()
+    }
 }
 
 impl Registry {
-    pub fn collect<'a>(&'a self) -> Vec<Metric<'a>> {
+    /// Builds a `Registry` directly from a collection of collectors, see [`RegistryBuilder::build`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] under the same conditions as [`RegistryBuilder::build`]
+    ///
+    /// [`RegistryBuilder::build`]: crate::RegistryBuilder#method.build
+    /// [`PromError`]: crate::PromError
+    pub fn from_collectors<I>(collectors: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = Box<dyn Collectable + Send + Sync>>,
+    {
+        RegistryBuilder::new().register_iter(collectors).build()
+    }
+
+    /// Register a collector for as long as the returned [`RegistrationGuard`] is alive, meant for
+    /// request or plugin scoped metrics that should disappear along with their owner. Scoped
+    /// collectors are included by the `collect_to_string` methods, [`Registry::snapshot`] and
+    /// [`Registry::text_size_hint`], but not by [`Registry::collect`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{Counter, RegistryBuilder};
+    /// use once_cell::sync::Lazy;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// static PLUGIN_LOADS: Lazy<Counter<AtomicU64>> =
+    ///     Lazy::new(|| Counter::new("plugin_loads", "Plugin loads").unwrap());
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("up", "Whether the app is up", || 1.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let guard = registry.register_scoped(Box::new(&*PLUGIN_LOADS)).unwrap();
+    /// assert!(registry.collect_to_string().unwrap().contains("plugin_loads 0"));
+    ///
+    /// drop(guard);
+    /// assert!(!registry.collect_to_string().unwrap().contains("plugin_loads"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if any registered collector, or one of the registry's own
+    /// metrics, has the same name as `collector`
+    ///
+    /// [`RegistrationGuard`]: crate::RegistrationGuard
+    /// [`Registry::snapshot`]: crate::Registry#method.snapshot
+    /// [`Registry::text_size_hint`]: crate::Registry#method.text_size_hint
+    /// [`Registry::collect`]: crate::Registry#method.collect
+    /// [`PromError`]: crate::PromError
+    pub fn register_scoped(
+        &self,
+        collector: Box<dyn Collectable + Send + Sync>,
+    ) -> Result<RegistrationGuard> {
+        let name = collector.descriptor().name();
+
+        let mut collectors = self
+            .scoped
+            .collectors
+            .write()
+            .expect("a registry's scoped collectors lock was poisoned");
+
+        // Scoped collectors are encoded after every other collector, so sharing a family with
+        // another collector would split the family's samples apart
+        let mut registered = self
+            .inputs
+            .iter()
+            .chain(collectors.iter().map(|(_, coll)| coll));
+        let self_metric = is_scrape_errors(name)
+            || self.self_metrics.as_ref().is_some_and(|self_metrics| {
+                name == self_metrics.collect_duration.name()
+                    || name == self_metrics.last_collect.name()
+            });
+        if self_metric || registered.any(|coll| coll.descriptor().name() == name) {
+            return Err(PromError::new(
+                format!("{} was registered twice", name),
+                PromErrorKind::DuplicatedCollector,
+            ));
+        }
+
+        let id = self.scoped.next_id.fetch_add(1, Ordering::Relaxed);
+        collectors.push((id, collector));
+
+        Ok(RegistrationGuard {
+            scoped: Arc::downgrade(&self.scoped),
+            id,
+        })
+    }
+
+    /// Gather every collector registered with the [`RegistryBuilder`] for an [`Encoder`]
+    ///
+    /// Collectors registered with [`Registry::register_scoped`] aren't included, since they can
+    /// be unregistered while the gathered families borrow them. [`Registry::collect_to_string`]
+    /// encodes them along with everything else
+    ///
+    /// [`RegistryBuilder`]: crate::RegistryBuilder
+    /// [`Encoder`]: crate::Encoder
+    /// [`Registry::register_scoped`]: crate::Registry#method.register_scoped
+    /// [`Registry::collect_to_string`]: crate::Registry#method.collect_to_string
+    pub fn collect(&self) -> Vec<Metric<'_>> {
         let mut metrics = Vec::with_capacity(self.inputs.len());
         for input in self.inputs.iter() {
-            metrics.push(Metric::new(&**input, input.descriptor()));
+            metrics.push(Metric::new(&**input, input.descriptor(), self.options));
         }
 
         metrics
     }
 
+    /// Capture the current value of every registered collector's samples, see
+    /// [`Collectable::current_values`]. The registry's own metrics aren't included
+    ///
+    /// [`Collectable::current_values`]: crate::Collectable#method.current_values
+    pub fn snapshot(&self) -> RegistrySnapshot {
+        let scoped = self.scoped.read();
+
+        RegistrySnapshot::new(
+            self.inputs
+                .iter()
+                .chain(scoped.iter().map(|(_, input)| input))
+                .flat_map(|input| {
+                    let name = input.descriptor().name();
+
+                    input
+                        .current_values()
+                        .into_iter()
+                        .map(move |(suffix, labels, value)| {
+                            (format!("{}{}", name, suffix), labels, value)
+                        })
+                })
+                .collect(),
+        )
+    }
+
+    /// Encode the current values of every registered collector into a compact binary format,
+    /// shorthand for `registry.snapshot().to_bytes()`. The bytes can be decoded and aggregated
+    /// with [`RegistrySnapshot::from_bytes`] and [`RegistrySnapshot::merge`]
+    ///
+    /// [`RegistrySnapshot::from_bytes`]: crate::RegistrySnapshot#method.from_bytes
+    /// [`RegistrySnapshot::merge`]: crate::RegistrySnapshot#method.merge
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.snapshot().to_bytes()
+    }
+
+    /// List the name and labels of every series the registered collectors currently expose,
+    /// one entry per sample line, for auditing cardinality. Histograms contribute their
+    /// `_sum`, `_count` and every `_bucket` series and a group contributes every child's
+    /// series. The registry's own metrics aren't included
+    ///
+    /// Series are listed with [`Collectable::for_each_sample`], so custom collectors that don't
+    /// implement it don't contribute any
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{Counter, Label, RegistryBuilder};
+    /// use once_cell::sync::Lazy;
+    ///
+    /// static REQUESTS: Lazy<Counter> = Lazy::new(|| {
+    ///     Counter::new("requests", "Total requests")
+    ///         .unwrap()
+    ///         .with_labels(vec![Label::new("method", "GET").unwrap()])
+    /// });
+    ///
+    /// let registry = RegistryBuilder::new().register(Box::new(&*REQUESTS)).build().unwrap();
+    ///
+    /// let series = registry.series();
+    /// assert_eq!(series.len(), 1);
+    /// assert_eq!(series[0].0, "requests");
+    /// assert_eq!(series[0].1, vec![Label::new("method", "GET").unwrap()]);
+    /// ```
+    ///
+    /// [`Collectable::for_each_sample`]: crate::Collectable#method.for_each_sample
+    pub fn series(&self) -> Vec<(String, Vec<Label>)> {
+        let scoped = self.scoped.read();
+
+        let mut series = Vec::new();
+        for input in self
+            .inputs
+            .iter()
+            .chain(scoped.iter().map(|(_, input)| input))
+        {
+            let name = input.descriptor().name();
+            input.for_each_sample(&mut |suffix, labels, _| {
+                series.push((format!("{}{}", name, suffix), labels.to_vec()));
+            });
+        }
+
+        series
+    }
+
+    /// Get a copy of the [`Descriptor`] of every registered collector, including scoped ones,
+    /// for inspecting what's registered without parsing the encoded output. The registry's own
+    /// metrics aren't included
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::RegistryBuilder;
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let descriptors = registry.descriptors();
+    /// assert_eq!(descriptors[0].name(), "answer");
+    /// assert_eq!(descriptors[0].help(), "The answer to everything");
+    /// ```
+    ///
+    /// [`Descriptor`]: crate::Descriptor
+    pub fn descriptors(&self) -> Vec<Descriptor> {
+        self.inputs
+            .iter()
+            .chain(self.scoped.read().iter().map(|(_, input)| input))
+            .map(|input| input.descriptor().clone())
+            .collect()
+    }
+
+    /// Estimate the length of [`Registry::collect_to_string`]'s output, meant for sizing
+    /// buffers up front. The estimate errs on the side of being too large but isn't exact
+    ///
+    /// [`Registry::collect_to_string`]: crate::Registry#method.collect_to_string
+    pub fn text_size_hint(&self) -> usize {
+        let mut hint: usize = self
+            .inputs
+            .iter()
+            .chain(self.scoped.read().iter().map(|(_, input)| input))
+            .map(|input| input.text_size_hint())
+            .sum();
+        if let Some(self_metrics) = self.self_metrics.as_ref() {
+            hint += (&self_metrics.collect_duration).text_size_hint();
+            hint += (&self_metrics.last_collect).text_size_hint();
+        }
+
+        hint
+    }
+
     pub fn collect_to_string(&self) -> Result<String> {
+        let start = Instant::now();
+
         let mut buf = String::new();
         for input in self.inputs.iter() {
-            input.encode_text(&mut buf)?;
+            input.encode_text(&mut buf, &self.options)?;
+        }
+        for (_, input) in self.scoped.read().iter() {
+            input.encode_text(&mut buf, &self.options)?;
+        }
+
+        self.encode_scrape_errors(&mut buf)?;
+        if let Some(self_metrics) = self.self_metrics.as_ref() {
+            self_metrics.record_and_encode(start, &mut buf, &self.options)?;
+        }
+
+        Ok(buf)
+    }
+
+    /// Render the registry like [`Registry::collect_to_string`] and check that the output follows
+    /// the exposition format, meant for catching instrumentation mistakes in tests and CI.
+    /// Checks that every family has at most one `# HELP` and `# TYPE` line, that no series is
+    /// repeated and that every histogram's buckets are cumulative and end with `+Inf`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::RegistryBuilder;
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// registry.assert_valid().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] describing the first problem found, or any error from encoding
+    ///
+    /// [`Registry::collect_to_string`]: crate::Registry#method.collect_to_string
+    /// [`PromError`]: crate::PromError
+    pub fn assert_valid(&self) -> Result<()> {
+        validate_exposition(&self.collect_to_string()?)
+    }
+
+    /// Encodes only the families with a sample that changed since the last call to
+    /// `collect_changed`, meant for push based pipelines where bandwidth is limited. Every family
+    /// is encoded by the first call. Changes are found by comparing [`Registry::snapshot`]s, and
+    /// the registry's own metrics and scrape errors aren't included
+    ///
+    /// Collectors that don't report their samples through [`Collectable::for_each_sample`], like
+    /// custom collectors that only implement the text format, can't be compared and are encoded by
+    /// every call
+    ///
+    /// This is unsuitable for normal pull based scraping, Prometheus marks the series of a family
+    /// that's missing from a scrape as stale
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{Counter, RegistryBuilder};
+    /// use once_cell::sync::Lazy;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// static REQUESTS: Lazy<Counter<AtomicU64>> =
+    ///     Lazy::new(|| Counter::new("requests", "Total requests").unwrap());
+    ///
+    /// let registry = RegistryBuilder::new().register(Box::new(&*REQUESTS)).build().unwrap();
+    /// assert!(registry.collect_changed().unwrap().contains("requests 0"));
+    /// assert_eq!(registry.collect_changed().unwrap(), "");
+    ///
+    /// REQUESTS.inc();
+    /// assert!(registry.collect_changed().unwrap().contains("requests 1"));
+    /// ```
+    ///
+    /// [`Registry::snapshot`]: crate::Registry#method.snapshot
+    /// [`Collectable::for_each_sample`]: crate::Collectable#method.for_each_sample
+    pub fn collect_changed(&self) -> Result<String> {
+        let mut last_changed = self
+            .last_changed
+            .lock()
+            .expect("a registry's last changed snapshot lock was poisoned");
+
+        let mut buf = String::new();
+        let mut samples = Vec::new();
+        let scoped = self.scoped.read();
+        for input in self
+            .inputs
+            .iter()
+            .chain(scoped.iter().map(|(_, input)| input))
+        {
+            let name = input.descriptor().name();
+            let values: Vec<_> = input
+                .current_values()
+                .into_iter()
+                .map(|(suffix, labels, value)| (format!("{}{}", name, suffix), labels, value))
+                .collect();
+
+            let changed = values.is_empty()
+                || values.iter().any(|(sample_name, labels, value)| {
+                    !last_changed
+                        .get(sample_name, labels)
+                        .is_some_and(|earlier| earlier.bitwise_eq(*value))
+                });
+            if changed {
+                input.encode_text(&mut buf, &self.options)?;
+            }
+
+            samples.extend(values);
+        }
+
+        *last_changed = RegistrySnapshot::new(samples);
+
+        Ok(buf)
+    }
+
+    /// Encodes every collector like [`Registry::collect_to_string`], but a collector that fails
+    /// is left out of the output instead of failing the whole collection. The errors of every
+    /// failed collector are returned alongside the output
+    ///
+    /// [`Registry::collect_to_string`]: crate::Registry#method.collect_to_string
+    pub fn collect_to_string_lenient(&self) -> (String, Vec<PromError>) {
+        let start = Instant::now();
+
+        let mut buf = String::new();
+        let mut errors = Vec::new();
+
+        // Every collector is encoded into its own buffer so a failure can't leave half of its output behind
+        let mut collector = String::new();
+        // The scoped collectors lock is released before `encode_scrape_errors` takes it again
+        for input in self
+            .inputs
+            .iter()
+            .chain(self.scoped.read().iter().map(|(_, input)| input))
+        {
+            collector.clear();
+
+            match input.encode_text(&mut collector, &self.options) {
+                Ok(()) => buf.push_str(&collector),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        if let Err(err) = self.encode_scrape_errors(&mut buf) {
+            errors.push(err);
+        }
+        if let Some(self_metrics) = self.self_metrics.as_ref() {
+            if let Err(err) = self_metrics.record_and_encode(start, &mut buf, &self.options) {
+                errors.push(err);
+            }
+        }
+
+        (buf, errors)
+    }
+
+    /// Encodes every collector in parallel, each into its own buffer, and concatenates the
+    /// results. The output is identical to [`Registry::collect_to_string`]
+    ///
+    /// Useful when the registry contains expensive collectors like [`CallbackGauge`]s that query a database
+    ///
+    /// [`Registry::collect_to_string`]: crate::Registry#method.collect_to_string
+    /// [`CallbackGauge`]: crate::CallbackGauge
+    #[cfg(feature = "rayon")]
+    pub fn collect_to_string_parallel(&self) -> Result<String> {
+        use rayon::prelude::*;
+
+        let start = Instant::now();
+        let buffers = self
+            .inputs
+            .par_iter()
+            .map(|input| {
+                let mut buf = String::new();
+                input.encode_text(&mut buf, &self.options)?;
+
+                Ok(buf)
+            })
+            .collect::<Result<Vec<String>>>()?;
+
+        let mut buf = buffers.concat();
+        for (_, input) in self.scoped.read().iter() {
+            input.encode_text(&mut buf, &self.options)?;
+        }
+
+        self.encode_scrape_errors(&mut buf)?;
+        if let Some(self_metrics) = self.self_metrics.as_ref() {
+            self_metrics.record_and_encode(start, &mut buf, &self.options)?;
         }
 
         Ok(buf)
@@ -112,6 +823,38 @@ impl Registry {
     pub fn init_registered(&self) {
         self.collect();
     }
+
+    /// Encodes [`SCRAPE_ERRORS`] for every collector that has failed, see
+    /// [`Collectable::scrape_errors`]. Nothing is written while no collector has failed
+    ///
+    /// [`Collectable::scrape_errors`]: crate::Collectable#method.scrape_errors
+    fn encode_scrape_errors(&self, buf: &mut String) -> Result<()> {
+        let scoped = self.scoped.read();
+        let mut failed = self
+            .inputs
+            .iter()
+            .chain(scoped.iter().map(|(_, input)| input))
+            .map(|input| (input.descriptor().name(), input.scrape_errors()))
+            .filter(|&(_, errors)| errors != 0)
+            .peekable();
+
+        if failed.peek().is_some() {
+            writeln!(
+                buf,
+                "# HELP {} The number of times collecting a collector failed",
+                SCRAPE_ERRORS,
+            )?;
+            writeln!(buf, "# TYPE {} counter", SCRAPE_ERRORS)?;
+
+            for (name, errors) in failed {
+                write!(buf, "{}{{collector=", SCRAPE_ERRORS)?;
+                escape_label_value(buf, name);
+                writeln!(buf, "}} {}", errors)?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl fmt::Debug for Registry {
@@ -125,30 +868,113 @@ impl fmt::Debug for Registry {
                     .map(|coll| (coll.descriptor().name(), coll.descriptor().help()))
                     .collect::<Vec<_>>(),
             )
+            .field(
+                "scoped",
+                &self
+                    .scoped
+                    .read()
+                    .iter()
+                    .map(|(_, coll)| (coll.descriptor().name(), coll.descriptor().help()))
+                    .collect::<Vec<_>>(),
+            )
+            .field("self_metrics", &self.self_metrics)
             .finish()
     }
 }
 
+/// The metrics a [`Registry`] reports about itself, see [`RegistryBuilder::self_instrument`]
+///
+/// [`Registry`]: crate::Registry
+/// [`RegistryBuilder::self_instrument`]: crate::RegistryBuilder#method.self_instrument
+#[derive(Debug)]
+struct SelfMetrics {
+    collect_duration: Histogram<AtomicF64>,
+    last_collect: Gauge<AtomicF64>,
+}
+
+impl SelfMetrics {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            collect_duration: HistogramBuilder::new()
+                .name("registry_collect_duration_seconds")
+                .help("The time it took to collect the registry in seconds")
+                .with_buckets(DEFAULT_BUCKETS.to_vec())
+                .build()?,
+            last_collect: Gauge::new(
+                "registry_last_collect_timestamp",
+                "The unix timestamp of the registry's last collection in seconds",
+            )?,
+        })
+    }
+
+    /// Records a collection that started at `start` and encodes the updated metrics into `buf`
+    fn record_and_encode(
+        &self,
+        start: Instant,
+        buf: &mut String,
+        options: &FormatOptions,
+    ) -> Result<()> {
+        self.collect_duration.observe(start.elapsed().as_secs_f64());
+        self.last_collect.set(
+            SystemTime::UNIX_EPOCH
+                .elapsed()
+                .expect("Impossible to fail, `UNIX_EPOCH` will never be sooner than the current system time")
+                .as_secs_f64(),
+        );
+
+        (&self.collect_duration).encode_text(buf, options)?;
+        (&self.last_collect).encode_text(buf, options)
+    }
+}
+
 #[derive(Clone)]
 pub struct Metric<'a> {
     name: &'a str,
     help: &'a str,
     labels: &'a [Label],
     value: &'a dyn Collectable,
+    /// The options of the registry the family was gathered from
+    options: FormatOptions,
 }
 
 impl<'a> Metric<'a> {
-    fn new(value: &'a dyn Collectable, descriptor: &'a Descriptor) -> Self {
+    fn new(value: &'a dyn Collectable, descriptor: &'a Descriptor, options: FormatOptions) -> Self {
         Self {
             name: descriptor.name(),
             help: descriptor.help(),
             labels: descriptor.labels(),
             value,
+            options,
         }
     }
 
+    /// Encodes the family in the Prometheus text format with the options of the registry it
+    /// was gathered from
     pub fn encode_text(&self, buf: &mut String) -> Result<()> {
-        self.value.encode_text(buf)
+        self.value.encode_text(buf, &self.options)
+    }
+
+    /// The collector this family was gathered from, meant for encoders that write their own headers
+    pub(crate) fn collectable(&self) -> &'a dyn Collectable {
+        self.value
+    }
+
+    /// How the family's sample values are written, see [`RegistryBuilder::float_precision`]
+    ///
+    /// [`RegistryBuilder::float_precision`]: crate::RegistryBuilder#method.float_precision
+    pub fn options(&self) -> &FormatOptions {
+        &self.options
+    }
+}
+
+/// Renders the metric's family in the Prometheus text format
+impl fmt::Display for Metric<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = String::new();
+        // `fmt::Error` can't carry any information, so the `PromError` is dropped
+        self.encode_text(&mut buf).map_err(|_| fmt::Error)?;
+
+        f.write_str(&buf)
     }
 }
 
@@ -162,22 +988,197 @@ impl fmt::Debug for Metric<'_> {
     }
 }
 
+/// A source of metrics that can be registered into a [`Registry`]
+///
+/// Implementors only need to emit their samples, the `# HELP` and `# TYPE` headers are
+/// written by the default [`Collectable::encode_text`] using [`Collectable::metric_type`]
+///
+/// [`Registry`]: crate::Registry
+/// [`Collectable::encode_text`]: crate::Collectable#method.encode_text
+/// [`Collectable::metric_type`]: crate::Collectable#tymethod.metric_type
 pub trait Collectable {
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()>;
+    /// Encodes the collector into the Prometheus text format, writing the header lines
+    /// followed by the collector's samples
+    ///
+    /// ```text
+    /// # HELP {{ name }} {{ help }}
+    /// # TYPE {{ name }} {{ metric_type }}
+    /// {{ samples }}
+    /// ```
+    fn encode_text(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        let descriptor = self.descriptor();
+        writeln!(
+            buf,
+            "# HELP {} {}",
+            descriptor.name(),
+            descriptor.help_escaped()
+        )?;
+        writeln!(buf, "# TYPE {} {}", descriptor.name(), self.metric_type())?;
+
+        self.encode_samples(buf, options)
+    }
+
+    /// Encodes the collector's samples without the `# HELP` and `# TYPE` headers, writing
+    /// sample values with [`FormatOptions::write_value`]
+    ///
+    /// [`FormatOptions::write_value`]: crate::FormatOptions#method.write_value
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()>;
+
+    /// The metric type written to the `# TYPE` line, e.g. `counter`, `gauge` or `histogram`
+    fn metric_type(&self) -> &'static str;
+
+    /// Encodes the collector's samples for the [`OpenMetricsEncoder`], defaults to
+    /// [`Collectable::encode_samples`]. Meant for samples only OpenMetrics has, like a
+    /// counter's `_created` timestamp
+    ///
+    /// OpenMetrics names a counter's family without its `_total` suffix and its samples
+    /// `<family>_total`, so counters have to override this to write their samples under that name
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    /// [`Collectable::encode_samples`]: crate::Collectable#tymethod.encode_samples
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.encode_samples(buf, options)
+    }
+
+    /// The metric type written to the `# TYPE` line by the [`OpenMetricsEncoder`], defaults to
+    /// [`Collectable::metric_type`]. Meant for OpenMetrics types the text format doesn't have,
+    /// like `info`
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    /// [`Collectable::metric_type`]: crate::Collectable#tymethod.metric_type
+    fn openmetrics_type(&self) -> &'static str {
+        self.metric_type()
+    }
+
     fn descriptor(&self) -> &Descriptor;
+
+    /// The collector's current samples, each with its name suffix and full set of labels, collected
+    /// from [`Collectable::for_each_sample`]
+    ///
+    /// [`Collectable::for_each_sample`]: crate::Collectable#method.for_each_sample
+    fn current_values(&self) -> Vec<(&'static str, Vec<Label>, MetricValue)> {
+        let mut values = Vec::new();
+        self.for_each_sample(&mut |suffix, labels, value| {
+            values.push((suffix, labels.to_vec(), value));
+        });
+
+        values
+    }
+
+    /// Call `visit` with the name suffix, labels and value of each of the collector's samples
+    /// without collecting them first. The suffix is empty for plain samples, histograms report
+    /// their `_sum` and `_count` followed by their cumulative `_bucket`s, which have an `le` label
+    /// after all the others
+    ///
+    /// Collectors that don't override this only contribute to the text format and report no values
+    fn for_each_sample(&self, _visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {}
+
+    /// The number of times collecting this collector has failed, like a [`CallbackGauge`]
+    /// timing out. Reported by the registry in `collector_scrape_errors_total`
+    ///
+    /// [`CallbackGauge`]: crate::CallbackGauge
+    fn scrape_errors(&self) -> u64 {
+        0
+    }
+
+    /// An estimate of how many bytes [`Collectable::encode_text`] will write, based on the
+    /// collector's descriptor and the samples visited by [`Collectable::for_each_sample`]
+    ///
+    /// [`Collectable::encode_text`]: crate::Collectable#method.encode_text
+    /// [`Collectable::for_each_sample`]: crate::Collectable#method.for_each_sample
+    fn text_size_hint(&self) -> usize {
+        estimate_text_size(self.descriptor(), self.metric_type(), |visit| {
+            self.for_each_sample(visit)
+        })
+    }
+}
+
+/// Estimates the encoded length of a collector from its descriptor, type and the samples passed
+/// to the visitor given to `for_each_sample`, without collecting them. Label values are counted
+/// with their escaping, see [`Collectable::text_size_hint`]
+///
+/// [`Collectable::text_size_hint`]: crate::Collectable#method.text_size_hint
+pub(crate) fn estimate_text_size(
+    descriptor: &Descriptor,
+    metric_type: &str,
+    for_each_sample: impl FnOnce(&mut dyn FnMut(&'static str, &[Label], MetricValue)),
+) -> usize {
+    /// Enough room for any formatted sample value, like `-1.7976931348623157e308`
+    const VALUE_WIDTH: usize = 24;
+    /// Enough room for any sample name suffix, like `_bucket`
+    const SUFFIX_WIDTH: usize = 8;
+
+    let name = descriptor.name().len();
+    let headers = "# HELP  \n# TYPE  \n".len()
+        + 2 * name
+        + descriptor.help_escaped().len()
+        + metric_type.len();
+
+    let labels_width = |labels: &[Label]| -> usize {
+        // `{}` plus a `name="value",` for each label, where backslashes, double quotes and
+        // newlines in the value are escaped with a backslash
+        2 + labels
+            .iter()
+            .map(|label| {
+                let escapes = label.value().matches(['\\', '"', '\n']).count();
+                label.name().len() + label.value().len() + escapes + 4
+            })
+            .sum::<usize>()
+    };
+    let line = |labels: &[Label]| name + SUFFIX_WIDTH + labels_width(labels) + VALUE_WIDTH + 2;
+
+    let mut samples = 0;
+    let mut visited = false;
+    for_each_sample(&mut |_, labels, _| {
+        samples += line(labels);
+        visited = true;
+    });
+    if !visited {
+        samples += line(descriptor.labels());
+    }
+
+    headers + samples
 }
 
 impl<T> Collectable for T
 where
     T: AsRef<dyn Collectable>,
 {
-    fn encode_text<'a>(&'a self, buf: &mut String) -> Result<()> {
-        self.as_ref().encode_text(buf)
+    fn encode_text(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.as_ref().encode_text(buf, options)
+    }
+
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.as_ref().encode_samples(buf, options)
+    }
+
+    fn metric_type(&self) -> &'static str {
+        self.as_ref().metric_type()
+    }
+
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        self.as_ref().encode_openmetrics_samples(buf, options)
+    }
+
+    fn openmetrics_type(&self) -> &'static str {
+        self.as_ref().openmetrics_type()
     }
 
     fn descriptor(&self) -> &Descriptor {
         self.as_ref().descriptor()
     }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        self.as_ref().for_each_sample(visit)
+    }
+
+    fn scrape_errors(&self) -> u64 {
+        self.as_ref().scrape_errors()
+    }
+
+    fn text_size_hint(&self) -> usize {
+        self.as_ref().text_size_hint()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -185,10 +1186,22 @@ pub struct Descriptor {
     name: Cow<'static, str>,
     help: Cow<'static, str>,
     pub(crate) labels: Vec<Label>,
+    /// The OpenMetrics unit of the metric, like `seconds` or `bytes`
+    unit: Option<Cow<'static, str>>,
+    /// Whether the help is written as-is instead of being escaped, for help that's already escaped
+    pub(crate) raw_help: bool,
 }
 
 impl Descriptor {
-    pub(crate) fn new(
+    /// Create a new `Descriptor`. `help` is stored as given, backslashes and newlines within it are
+    /// only escaped when it's encoded
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if `name` doesn't conform to the regex `[a-zA-Z_:][a-zA-Z0-9_:]*`
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn new(
         name: impl Into<Cow<'static, str>>,
         help: impl AsRef<str>,
         labels: impl Into<Vec<Label>>,
@@ -204,26 +1217,112 @@ impl Descriptor {
 
         Ok(Self {
             name,
-            help: help
-                .as_ref()
-                .replace("\\", "\\\\")
-                .replace("\n", "\\n")
-                .into(),
+            help: Cow::Owned(help.as_ref().to_owned()),
             labels: labels.into(),
+            unit: None,
+            raw_help: false,
         })
     }
 
+    /// Create a `Descriptor` without labels in a const context, meant for metrics stored in
+    /// plain `static`s
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` doesn't conform to the regex `[a-zA-Z_:][a-zA-Z0-9_:]*`, which is a
+    /// compile error when evaluated in a const context
+    pub(crate) const fn from_static(name: &'static str, help: &'static str) -> Self {
+        assert!(
+            valid_metric_name(name),
+            "Metric name contains invalid characters"
+        );
+
+        Self {
+            name: Cow::Borrowed(name),
+            help: Cow::Borrowed(help),
+            labels: Vec::new(),
+            unit: None,
+            raw_help: false,
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// A copy of this descriptor named `{{ name }}{{ suffix }}`, keeping its help, labels and unit.
+    /// Suffixes made of `[a-zA-Z0-9_:]` keep the name valid
+    pub(crate) fn with_name_suffix(&self, suffix: &str) -> Self {
+        Self {
+            name: Cow::Owned(format!("{}{}", self.name, suffix)),
+            ..self.clone()
+        }
+    }
+
+    /// The help text as it was given, without any escaping
     pub fn help(&self) -> &str {
         &self.help
     }
 
+    /// The help text with backslashes and newlines escaped, as written to the `# HELP` line.
+    /// Raw help is returned unchanged
+    pub(crate) fn help_escaped(&self) -> Cow<'_, str> {
+        if !self.raw_help && self.help.contains(['\\', '\n']) {
+            Cow::Owned(self.help.replace('\\', "\\\\").replace('\n', "\\n"))
+        } else {
+            Cow::Borrowed(&self.help)
+        }
+    }
+
     pub fn labels(&self) -> &[Label] {
         &self.labels
     }
+
+    /// The unit of the metric, emitted as a `# UNIT` line by the [`OpenMetricsEncoder`]
+    ///
+    /// [`OpenMetricsEncoder`]: crate::OpenMetricsEncoder
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    /// Set the unit of the metric. A counter's name is checked without its `_total` suffix, since
+    /// OpenMetrics leaves it out of the family name
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the unit is empty, contains characters other than
+    /// `[a-zA-Z0-9_]` or if the metric's name doesn't end with `_{unit}` as OpenMetrics requires
+    ///
+    /// [`PromError`]: crate::PromError
+    pub(crate) fn set_unit(&mut self, unit: Cow<'static, str>, counter: bool) -> Result<()> {
+        if unit.is_empty() || !unit.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(PromError::new(
+                "Unit contains invalid characters",
+                PromErrorKind::InvalidUnit,
+            ));
+        }
+
+        let family = if counter {
+            counter_family(&self.name)
+        } else {
+            &self.name
+        };
+        let suffixed = family
+            .strip_suffix(unit.as_ref())
+            .is_some_and(|rest| rest.ends_with('_'));
+        if !suffixed {
+            return Err(PromError::new(
+                format!(
+                    "The metric name {} must end with its unit, `_{}`",
+                    self.name, unit
+                ),
+                PromErrorKind::InvalidUnit,
+            ));
+        }
+
+        self.unit = Some(unit);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -231,10 +1330,12 @@ mod tests {
     use super::*;
     use crate::{
         counter::Counter,
+        encoder::{Encoder, OpenMetricsEncoder, TextEncoder},
         gauge::Gauge,
         histogram::{Histogram, HistogramBuilder, DEFAULT_BUCKETS},
     };
     use once_cell::sync::Lazy;
+    use std::sync::atomic::AtomicI64;
 
     #[test]
     fn normal_use() {
@@ -266,4 +1367,1037 @@ mod tests {
 
         println!("{}", REGISTRY.collect_to_string().unwrap());
     }
+
+    #[test]
+    fn custom_collector() {
+        struct Uptime {
+            descriptor: Descriptor,
+            started: u64,
+        }
+
+        impl Collectable for Uptime {
+            fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+                // Computed at encode time instead of being stored in an atomic
+                writeln!(buf, "{} {}", self.descriptor.name(), 100 - self.started)?;
+                Ok(())
+            }
+
+            fn metric_type(&self) -> &'static str {
+                "gauge"
+            }
+
+            fn descriptor(&self) -> &Descriptor {
+                &self.descriptor
+            }
+        }
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(Uptime {
+                descriptor: Descriptor::new("uptime", "The uptime", Vec::new()).unwrap(),
+                started: 58,
+            }))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP uptime The uptime\n# TYPE uptime gauge\nuptime 42\n",
+        );
+    }
+
+    #[test]
+    fn lenient_collection() {
+        struct Failing {
+            descriptor: Descriptor,
+        }
+
+        impl Collectable for Failing {
+            fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+                writeln!(buf, "{} 1", self.descriptor.name())?;
+                Err(PromError::new(
+                    "The database is down",
+                    PromErrorKind::FormattingError,
+                ))
+            }
+
+            fn metric_type(&self) -> &'static str {
+                "gauge"
+            }
+
+            fn descriptor(&self) -> &Descriptor {
+                &self.descriptor
+            }
+        }
+
+        let registry = RegistryBuilder::new()
+            .register_gauge_fn("healthy_a", "Healthy", || 1.0)
+            .unwrap()
+            .register(Box::new(Failing {
+                descriptor: Descriptor::new("failing", "Fails", Vec::new()).unwrap(),
+            }))
+            .register_gauge_fn("healthy_b", "Also healthy", || 2.0)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert!(registry.collect_to_string().is_err());
+
+        let (output, errors) = registry.collect_to_string_lenient();
+        assert_eq!(
+            output,
+            "# HELP healthy_a Healthy\n\
+             # TYPE healthy_a gauge\n\
+             healthy_a 1.0\n\
+             # HELP healthy_b Also healthy\n\
+             # TYPE healthy_b gauge\n\
+             healthy_b 2.0\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message(), "The database is down");
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_collection() {
+        let mut builder = RegistryBuilder::new();
+        for i in 0..32 {
+            builder = builder
+                .register_gauge_fn(format!("gauge_{}", i), "A callback gauge", move || i as f64)
+                .unwrap();
+        }
+        let registry = builder.build().unwrap();
+
+        assert_eq!(
+            registry.collect_to_string_parallel().unwrap(),
+            registry.collect_to_string().unwrap(),
+        );
+    }
+
+    #[test]
+    fn self_instrumented() {
+        let registry = RegistryBuilder::new()
+            .register_gauge_fn("some_gauge", "A gauge", || 1.0)
+            .unwrap()
+            .self_instrument()
+            .build()
+            .unwrap();
+
+        let first = registry.collect_to_string().unwrap();
+        assert!(first.contains("# TYPE registry_collect_duration_seconds histogram\n"));
+        assert!(first.contains("registry_collect_duration_seconds_count 1\n"));
+        assert!(first.contains("# TYPE registry_last_collect_timestamp gauge\n"));
+        assert!(first.contains("some_gauge 1.0\n"));
+
+        let second = registry.collect_to_string().unwrap();
+        assert!(second.contains("registry_collect_duration_seconds_count 2\n"));
+
+        let plain = RegistryBuilder::new()
+            .register_gauge_fn("some_gauge", "A gauge", || 1.0)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(!plain.collect_to_string().unwrap().contains("registry_"));
+
+        let reserved = RegistryBuilder::new()
+            .register_gauge_fn("registry_last_collect_timestamp", "Taken", || 1.0)
+            .unwrap()
+            .self_instrument()
+            .build()
+            .unwrap_err();
+        assert_eq!(reserved.kind(), PromErrorKind::DuplicatedCollector);
+
+        // The scrape errors counter is reserved even without self instrumentation
+        for name in &["collector_scrape_errors_total", "collector_scrape_errors"] {
+            let scrape_errors = RegistryBuilder::new()
+                .register_gauge_fn(*name, "Taken", || 1.0)
+                .unwrap()
+                .build()
+                .unwrap_err();
+            assert_eq!(scrape_errors.kind(), PromErrorKind::DuplicatedCollector);
+        }
+    }
+
+    #[test]
+    fn display_metric() {
+        static COUNTER: Lazy<Counter> =
+            Lazy::new(|| Counter::new("displayed_counter", "Counts displays").unwrap());
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .build()
+            .unwrap();
+        COUNTER.inc_by(3);
+
+        let metrics = registry.collect();
+        assert_eq!(
+            metrics[0].to_string(),
+            "# HELP displayed_counter Counts displays\n\
+             # TYPE displayed_counter counter\n\
+             displayed_counter 3\n",
+        );
+    }
+
+    #[test]
+    fn from_collectors() {
+        static COUNTER: Lazy<Counter> =
+            Lazy::new(|| Counter::new("collected_counter", "Counts things").unwrap());
+        static GAUGE: Lazy<Gauge> =
+            Lazy::new(|| Gauge::new("collected_gauge", "Gauges things").unwrap());
+
+        let collectors: Vec<Box<dyn Collectable + Send + Sync>> =
+            vec![Box::new(&*COUNTER), Box::new(&*GAUGE)];
+        let registry = Registry::from_collectors(collectors).unwrap();
+        assert_eq!(registry.collect().len(), 2);
+
+        let duplicated: Vec<Box<dyn Collectable + Send + Sync>> =
+            vec![Box::new(&*COUNTER), Box::new(&*COUNTER)];
+        assert_eq!(
+            Registry::from_collectors(duplicated).unwrap_err().kind(),
+            PromErrorKind::DuplicatedCollector,
+        );
+    }
+
+    #[test]
+    fn mixed_metric_types() {
+        static COUNTER: Lazy<Counter> =
+            Lazy::new(|| Counter::new("requests", "Counts requests").unwrap());
+        static GAUGE: Lazy<Gauge> = Lazy::new(|| {
+            Gauge::new("requests", "Gauges requests")
+                .unwrap()
+                .with_labels(vec![Label::new("kind", "gauge").unwrap()])
+        });
+
+        let err = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .register(Box::new(&*GAUGE))
+            .build()
+            .unwrap_err();
+
+        assert_eq!(err.kind(), PromErrorKind::DuplicatedCollector);
+        assert_eq!(
+            err.message(),
+            "requests was registered as both a counter and a gauge"
+        );
+    }
+
+    #[test]
+    fn register_gauge_fns() {
+        let registry = RegistryBuilder::new()
+            .register_gauge_fn("free_space", "Free disk space", || 1024.0)
+            .unwrap()
+            .register_gauge_fn("queue_length", "Length of the queue", || 3.0)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP free_space Free disk space\n\
+             # TYPE free_space gauge\n\
+             free_space 1024.0\n\
+             # HELP queue_length Length of the queue\n\
+             # TYPE queue_length gauge\n\
+             queue_length 3.0\n",
+        );
+
+        let err = RegistryBuilder::new()
+            .register_gauge_fn("not a name", "Invalid", || 0.0)
+            .unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidMetricName);
+    }
+
+    #[test]
+    fn current_values() {
+        static COUNTER: Lazy<Counter> = Lazy::new(|| {
+            Counter::new("values_counter", "Counts things")
+                .unwrap()
+                .with_labels(vec![Label::new("kind", "uint").unwrap()])
+        });
+        static GAUGE: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("values_gauge", "Gauges things").unwrap());
+        static HISTOGRAM: Lazy<Histogram<AtomicF64>> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("values_histogram")
+                .help("Hists grams")
+                .with_buckets(vec![1.0])
+                .build()
+                .unwrap()
+        });
+
+        COUNTER.inc_by(3);
+        GAUGE.set(-2);
+        HISTOGRAM.observe(0.5);
+        HISTOGRAM.observe(1.5);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .register(Box::new(&*GAUGE))
+            .register(Box::new(&*HISTOGRAM))
+            .register_gauge_fn("values_callback", "Calls back", || 0.25)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let values: Vec<_> = registry
+            .inputs
+            .iter()
+            .map(|input| (input.descriptor().name(), input.current_values()))
+            .collect();
+
+        // `le` is reserved so it can't be created through `Label::new`
+        let le = |value: &'static str| Label {
+            name: Cow::Borrowed("le"),
+            value: Cow::Borrowed(value),
+        };
+        assert_eq!(
+            values,
+            vec![
+                (
+                    "values_callback",
+                    vec![("", vec![], MetricValue::F64(0.25))]
+                ),
+                (
+                    "values_counter",
+                    vec![(
+                        "",
+                        vec![Label::new("kind", "uint").unwrap()],
+                        MetricValue::U64(3)
+                    )]
+                ),
+                ("values_gauge", vec![("", vec![], MetricValue::I64(-2))]),
+                (
+                    "values_histogram",
+                    vec![
+                        ("_sum", vec![], MetricValue::F64(2.0)),
+                        ("_count", vec![], MetricValue::U64(2)),
+                        ("_bucket", vec![le("1.0")], MetricValue::U64(1)),
+                        ("_bucket", vec![le("+Inf")], MetricValue::U64(2)),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_size_hint() {
+        static COUNTER: Lazy<Counter> = Lazy::new(|| {
+            Counter::new("hinted_counter", "Counts things")
+                .unwrap()
+                .with_labels(vec![Label::new("kind", "uint").unwrap()])
+        });
+        static GAUGE: Lazy<Gauge<AtomicF64>> =
+            Lazy::new(|| Gauge::new("hinted_gauge", "Gauges things").unwrap());
+        // Every character of the label value is escaped, doubling its length
+        static ESCAPED: Lazy<Gauge> = Lazy::new(|| {
+            Gauge::new("hinted_escaped", "Gauges\\escapes")
+                .unwrap()
+                .with_labels(vec![Label::new("path", "\\\"\n".repeat(32)).unwrap()])
+        });
+        static HISTOGRAM: Lazy<Histogram<AtomicF64>> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("hinted_histogram")
+                .help("Hists grams")
+                .with_buckets(DEFAULT_BUCKETS.to_vec())
+                .with_labels(vec![Label::new("endpoint", "/index.html").unwrap()])
+                .build()
+                .unwrap()
+        });
+
+        COUNTER.inc_by(u64::MAX);
+        GAUGE.set(f64::MIN);
+        HISTOGRAM.observe(f64::MAX);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .register(Box::new(&*GAUGE))
+            .register(Box::new(&*ESCAPED))
+            .register(Box::new(&*HISTOGRAM))
+            .register_gauge_fn("hinted_callback", "Calls back", || f64::MIN_POSITIVE)
+            .unwrap()
+            .self_instrument()
+            .build()
+            .unwrap();
+
+        let hint = registry.text_size_hint();
+        let output = registry.collect_to_string().unwrap();
+        assert!(hint >= output.len(), "{} < {}", hint, output.len());
+
+        let escaped = (&*ESCAPED).text_size_hint();
+        let mut encoded = String::new();
+        (&*ESCAPED)
+            .encode_text(&mut encoded, &FormatOptions::new())
+            .unwrap();
+        assert!(escaped >= encoded.len(), "{} < {}", escaped, encoded.len());
+    }
+
+    #[test]
+    fn snapshot_bytes_round_trip() {
+        static COUNTER: Lazy<Counter> = Lazy::new(|| {
+            Counter::new("bytes_counter", "Counts things")
+                .unwrap()
+                .with_labels(vec![Label::new("kind", "a \"quoted\" value").unwrap()])
+        });
+        static GAUGE: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("bytes_gauge", "Gauges things").unwrap());
+        static HISTOGRAM: Lazy<Histogram<AtomicF64>> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("bytes_histogram")
+                .help("Hists grams")
+                .with_buckets(vec![1.0])
+                .build()
+                .unwrap()
+        });
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .register(Box::new(&*GAUGE))
+            .register(Box::new(&*HISTOGRAM))
+            .register_gauge_fn("bytes_float", "Floats things", || 0.5)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        COUNTER.inc_by(3);
+        GAUGE.set(-7);
+        HISTOGRAM.observe(0.5);
+
+        let bytes = registry.to_bytes();
+        assert_eq!(&bytes[..5], b"PRSS\x01");
+        assert_eq!(
+            RegistrySnapshot::from_bytes(&bytes).unwrap(),
+            registry.snapshot()
+        );
+
+        // Truncated, trailing and unversioned bytes are rejected
+        for invalid in [&bytes[..bytes.len() - 1], b"PRSS\x02\0\0\0\0", b"nope"] {
+            assert_eq!(
+                RegistrySnapshot::from_bytes(invalid).unwrap_err().kind(),
+                PromErrorKind::InvalidSnapshot,
+            );
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(RegistrySnapshot::from_bytes(&trailing).is_err());
+    }
+
+    #[test]
+    fn snapshot_bytes_aggregation() {
+        static FIRST: Lazy<Counter> =
+            Lazy::new(|| Counter::new("worker_requests", "Requests served").unwrap());
+        static SECOND: Lazy<Counter> =
+            Lazy::new(|| Counter::new("worker_requests", "Requests served").unwrap());
+        static ONLY_SECOND: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("worker_queue", "Queued requests").unwrap());
+        fn latency() -> Histogram<AtomicF64> {
+            HistogramBuilder::new()
+                .name("worker_latency")
+                .help("Request latency")
+                .with_buckets(vec![1.0])
+                .build()
+                .unwrap()
+        }
+        static FIRST_LATENCY: Lazy<Histogram<AtomicF64>> = Lazy::new(latency);
+        static SECOND_LATENCY: Lazy<Histogram<AtomicF64>> = Lazy::new(latency);
+
+        let first = RegistryBuilder::new()
+            .register(Box::new(&*FIRST))
+            .register(Box::new(&*FIRST_LATENCY))
+            .build()
+            .unwrap();
+        let second = RegistryBuilder::new()
+            .register(Box::new(&*SECOND))
+            .register(Box::new(&*ONLY_SECOND))
+            .register(Box::new(&*SECOND_LATENCY))
+            .build()
+            .unwrap();
+
+        FIRST.inc_by(4);
+        SECOND.inc_by(6);
+        ONLY_SECOND.set(2);
+        FIRST_LATENCY.observe(0.5);
+        SECOND_LATENCY.observe(0.25);
+        SECOND_LATENCY.observe(2.0);
+
+        let mut total = RegistrySnapshot::from_bytes(&first.to_bytes()).unwrap();
+        total.merge(&RegistrySnapshot::from_bytes(&second.to_bytes()).unwrap());
+
+        assert_eq!(total.len(), 6);
+        assert_eq!(
+            total.get("worker_requests", &[]),
+            Some(MetricValue::U64(10))
+        );
+        assert_eq!(total.get("worker_queue", &[]), Some(MetricValue::I64(2)));
+
+        // Histograms are aggregated along with their sum and count
+        let le = |value: &'static str| Label {
+            name: Cow::Borrowed("le"),
+            value: Cow::Borrowed(value),
+        };
+        assert_eq!(
+            total.get("worker_latency_sum", &[]),
+            Some(MetricValue::F64(2.75))
+        );
+        assert_eq!(
+            total.get("worker_latency_count", &[]),
+            Some(MetricValue::U64(3))
+        );
+        assert_eq!(
+            total.get("worker_latency_bucket", &[le("1.0")]),
+            Some(MetricValue::U64(2))
+        );
+        assert_eq!(
+            total.get("worker_latency_bucket", &[le("+Inf")]),
+            Some(MetricValue::U64(3))
+        );
+    }
+
+    #[test]
+    fn snapshot_diff() {
+        static COUNTER: Lazy<Counter> =
+            Lazy::new(|| Counter::new("snapshot_counter", "Counts things").unwrap());
+        static GAUGE: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("snapshot_gauge", "Gauges things").unwrap());
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*COUNTER))
+            .register(Box::new(&*GAUGE))
+            .register_gauge_fn("snapshot_stale", "Always stale", || f64::NAN)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        GAUGE.set(7);
+        let before = registry.snapshot();
+        assert_eq!(before.len(), 3);
+        assert_eq!(before.get("snapshot_gauge", &[]), Some(MetricValue::I64(7)));
+
+        COUNTER.inc_by(3);
+        let after = registry.snapshot();
+
+        // The unchanged `NaN` isn't reported
+        let diff = after.diff(&before);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].name(), "snapshot_counter");
+        assert!(diff[0].labels().is_empty());
+        assert_eq!(diff[0].earlier(), Some(MetricValue::U64(0)));
+        assert_eq!(diff[0].current(), MetricValue::U64(3));
+        assert_eq!(diff[0].change(), 3.0);
+
+        assert!(after.diff(&after).is_empty());
+        assert_eq!(after.diff(&RegistrySnapshot::default()).len(), 3);
+    }
+
+    #[test]
+    fn register_all_extends() {
+        static A: Lazy<Counter> = Lazy::new(|| Counter::new("a", "The a").unwrap());
+        static B: Lazy<Counter> = Lazy::new(|| Counter::new("b", "The b").unwrap());
+        static C: Lazy<Counter> = Lazy::new(|| Counter::new("c", "The c").unwrap());
+
+        let rest: Vec<Box<dyn Collectable + Send + Sync>> = vec![Box::new(&*B), Box::new(&*C)];
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*A))
+            .register_all(rest)
+            .build()
+            .unwrap();
+
+        let output = registry.collect_to_string().unwrap();
+        assert_eq!(registry.collect().len(), 3);
+        for name in &["a", "b", "c"] {
+            assert!(output.contains(&format!("\n{} 0\n", name)), "{}", output);
+        }
+    }
+
+    #[test]
+    fn register_iter() {
+        static FIRST: Lazy<Counter> = Lazy::new(|| Counter::new("first", "The first").unwrap());
+        static SECOND: Lazy<Counter> = Lazy::new(|| Counter::new("second", "The second").unwrap());
+        static THIRD: Lazy<Counter> = Lazy::new(|| Counter::new("third", "The third").unwrap());
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*FIRST))
+            .register_iter(
+                vec![&*SECOND, &*THIRD]
+                    .into_iter()
+                    .map(|counter| Box::new(counter) as Box<dyn Collectable + Send + Sync>),
+            )
+            .build()
+            .unwrap();
+
+        let output = registry.collect_to_string().unwrap();
+        assert_eq!(registry.collect().len(), 3);
+        for name in &["first", "second", "third"] {
+            assert!(output.contains(&format!("\n{} 0\n", name)), "{}", output);
+        }
+    }
+
+    #[test]
+    fn raw_help() {
+        static ESCAPED: Lazy<Counter> =
+            Lazy::new(|| Counter::new("escaped_help", "Counts C:\\paths\nacross lines").unwrap());
+
+        assert_eq!(ESCAPED.help(), "Counts C:\\paths\nacross lines");
+        assert_eq!(
+            (&*ESCAPED).descriptor().help_escaped(),
+            "Counts C:\\\\paths\\nacross lines"
+        );
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*ESCAPED))
+            .build()
+            .unwrap();
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP escaped_help Counts C:\\\\paths\\nacross lines\n\
+             # TYPE escaped_help counter\n\
+             escaped_help 0\n",
+        );
+    }
+
+    #[test]
+    fn raw_help_passthrough() {
+        // Help migrated from another system that already escaped it
+        static ESCAPED: Lazy<Counter> =
+            Lazy::new(|| Counter::new("escaped_twice", "Reads C:\\data\\nfiles").unwrap());
+        static RAW: Lazy<Counter> = Lazy::new(|| {
+            Counter::new("raw_help", "Reads C:\\data\\nfiles")
+                .unwrap()
+                .with_raw_help()
+        });
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*ESCAPED))
+            .register(Box::new(&*RAW))
+            .build()
+            .unwrap();
+
+        let output = registry.collect_to_string().unwrap();
+        assert!(output.contains("# HELP escaped_twice Reads C:\\\\data\\\\nfiles\n"));
+        assert!(output.contains("# HELP raw_help Reads C:\\data\\nfiles\n"));
+        assert_eq!(RAW.help(), ESCAPED.help());
+    }
+
+    #[test]
+    fn scoped_registration() {
+        static STATIC: Lazy<Counter> =
+            Lazy::new(|| Counter::new("static_requests", "Static requests").unwrap());
+        static SCOPED: Lazy<Counter> =
+            Lazy::new(|| Counter::new("scoped_requests", "Scoped requests").unwrap());
+        static RESERVED: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("registry_last_collect_timestamp", "Reserved").unwrap());
+        static SCRAPE_ERROR_COUNTER: Lazy<Counter> =
+            Lazy::new(|| Counter::new("collector_scrape_errors_total", "Reserved").unwrap());
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*STATIC))
+            .self_instrument()
+            .build()
+            .unwrap();
+
+        let guard = registry.register_scoped(Box::new(&*SCOPED)).unwrap();
+        SCOPED.inc();
+
+        crate::testutil::assert_metric_value(&registry, "scoped_requests", &[], 1u64);
+
+        // Names can't be shared with any other collector
+        let err = registry.register_scoped(Box::new(&*SCOPED)).unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::DuplicatedCollector);
+        assert!(registry.register_scoped(Box::new(&*STATIC)).is_err());
+        assert!(registry.register_scoped(Box::new(&*RESERVED)).is_err());
+        assert!(registry
+            .register_scoped(Box::new(&*SCRAPE_ERROR_COUNTER))
+            .is_err());
+
+        drop(guard);
+        let unregistered = registry.collect_to_string().unwrap();
+        assert!(
+            !unregistered.contains("scoped_requests"),
+            "{}",
+            unregistered
+        );
+        assert!(
+            unregistered.contains("\nstatic_requests 0\n"),
+            "{}",
+            unregistered
+        );
+
+        // The name is free again once the guard is dropped, and guards outliving the registry are harmless
+        let reregistered = registry.register_scoped(Box::new(&*SCOPED)).unwrap();
+        drop(registry);
+        drop(reregistered);
+    }
+
+    #[test]
+    fn mismatched_histogram_buckets() {
+        static GET: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("request_latency")
+                .help("Request latency")
+                .with_buckets(vec![0.1, 1.0])
+                .label(Label::new("method", "GET").unwrap())
+                .build()
+                .unwrap()
+        });
+        static POST: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("request_latency")
+                .help("Request latency")
+                .with_buckets(vec![0.1, 1.0])
+                .label(Label::new("method", "POST").unwrap())
+                .build()
+                .unwrap()
+        });
+        static PUT: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("request_latency")
+                .help("Request latency")
+                .with_buckets(vec![0.5, 5.0])
+                .label(Label::new("method", "PUT").unwrap())
+                .build()
+                .unwrap()
+        });
+
+        // The same buckets with different labels are fine
+        assert!(RegistryBuilder::new()
+            .register(Box::new(&*GET))
+            .register(Box::new(&*POST))
+            .build()
+            .is_ok());
+
+        let err = RegistryBuilder::new()
+            .register(Box::new(&*GET))
+            .register(Box::new(&*PUT))
+            .build()
+            .unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::DuplicatedCollector);
+        assert_eq!(
+            err.message(),
+            r#"request_latency was registered with the buckets ["0.1", "1.0", "+Inf"] and ["0.5", "5.0", "+Inf"]"#,
+        );
+    }
+
+    #[test]
+    fn collect_changed() {
+        static SENT: Lazy<Counter<AtomicU64>> =
+            Lazy::new(|| Counter::new("changed_sent", "Messages sent").unwrap());
+        static QUEUED: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("changed_queued", "Messages queued").unwrap());
+        static LATENCY: Lazy<Histogram<AtomicU64>> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("changed_latency")
+                .help("Send latency")
+                .with_buckets(vec![10])
+                .build()
+                .unwrap()
+        });
+
+        /// Only implements the text format, so its values can't be compared
+        struct Opaque(Descriptor);
+
+        impl Collectable for Opaque {
+            fn encode_text(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+                writeln!(buf, "# TYPE changed_opaque gauge")?;
+                self.encode_samples(buf, options)
+            }
+
+            fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+                writeln!(buf, "changed_opaque 1")?;
+                Ok(())
+            }
+
+            fn metric_type(&self) -> &'static str {
+                "gauge"
+            }
+
+            fn descriptor(&self) -> &Descriptor {
+                &self.0
+            }
+        }
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*SENT))
+            .register(Box::new(&*QUEUED))
+            .register(Box::new(&*LATENCY))
+            .register(Box::new(Opaque(
+                Descriptor::new("changed_opaque", "Opaque", Vec::new()).unwrap(),
+            )))
+            .register_gauge_fn("changed_stale", "Always stale", || f64::NAN)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        // Everything is new the first time around
+        assert_eq!(
+            registry.collect_changed().unwrap(),
+            "# HELP changed_latency Send latency\n\
+             # TYPE changed_latency histogram\n\
+             changed_latency_sum 0\n\
+             changed_latency_count 0\n\
+             changed_latency_bucket{le=\"10\"} 0\n\
+             changed_latency_bucket{le=\"+Inf\"} 0\n\
+             # TYPE changed_opaque gauge\n\
+             changed_opaque 1\n\
+             # HELP changed_queued Messages queued\n\
+             # TYPE changed_queued gauge\n\
+             changed_queued 0\n\
+             # HELP changed_sent Messages sent\n\
+             # TYPE changed_sent counter\n\
+             changed_sent 0\n\
+             # HELP changed_stale Always stale\n\
+             # TYPE changed_stale gauge\n\
+             changed_stale NaN\n",
+        );
+
+        // An unchanged `NaN` isn't repeated, the opaque collector always is
+        SENT.inc();
+        LATENCY.observe(20);
+        assert_eq!(
+            registry.collect_changed().unwrap(),
+            "# HELP changed_latency Send latency\n\
+             # TYPE changed_latency histogram\n\
+             changed_latency_sum 20\n\
+             changed_latency_count 1\n\
+             changed_latency_bucket{le=\"10\"} 0\n\
+             changed_latency_bucket{le=\"+Inf\"} 1\n\
+             # TYPE changed_opaque gauge\n\
+             changed_opaque 1\n\
+             # HELP changed_sent Messages sent\n\
+             # TYPE changed_sent counter\n\
+             changed_sent 1\n",
+        );
+        assert_eq!(
+            registry.collect_changed().unwrap(),
+            "# TYPE changed_opaque gauge\n\
+             changed_opaque 1\n",
+        );
+
+        // Regular collection isn't affected
+        assert!(registry
+            .collect_to_string()
+            .unwrap()
+            .contains("changed_queued 0"));
+    }
+
+    #[test]
+    fn descriptors() {
+        static REQUESTS: Lazy<Counter<AtomicU64>> = Lazy::new(|| {
+            Counter::new("described_requests", "Total requests")
+                .unwrap()
+                .with_labels(vec![Label::new("method", "GET").unwrap()])
+        });
+        static QUEUED: Lazy<Gauge<AtomicI64>> =
+            Lazy::new(|| Gauge::new("described_queued", "Queued requests").unwrap());
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*REQUESTS))
+            .register(Box::new(&*QUEUED))
+            .self_instrument()
+            .build()
+            .unwrap();
+
+        let descriptors = registry.descriptors();
+        assert_eq!(
+            descriptors
+                .iter()
+                .map(|descriptor| (descriptor.name(), descriptor.labels()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("described_queued", QUEUED.labels()),
+                ("described_requests", REQUESTS.labels()),
+            ],
+        );
+        assert_eq!(descriptors[1].help(), "Total requests");
+    }
+
+    #[test]
+    fn assert_valid() {
+        static REQUESTS: Lazy<Counter> = Lazy::new(|| {
+            Counter::new("valid_requests", "Total requests")
+                .unwrap()
+                .with_labels(vec![Label::new("method", "GET").unwrap()])
+        });
+        static LATENCY: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("valid_latency")
+                .help("Request latency")
+                .with_buckets(DEFAULT_BUCKETS.to_vec())
+                .build()
+                .unwrap()
+        });
+
+        /// Writes its headers twice, like a hand-written collector with a bug
+        struct DoubledHeaders(Descriptor);
+
+        impl Collectable for DoubledHeaders {
+            fn encode_text(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+                writeln!(buf, "# HELP doubled Doubled")?;
+                writeln!(buf, "# HELP doubled Doubled")?;
+                writeln!(buf, "# TYPE doubled gauge")?;
+                self.encode_samples(buf, options)
+            }
+
+            fn encode_samples(&self, buf: &mut String, _options: &FormatOptions) -> Result<()> {
+                writeln!(buf, "doubled 1")?;
+                Ok(())
+            }
+
+            fn metric_type(&self) -> &'static str {
+                "gauge"
+            }
+
+            fn descriptor(&self) -> &Descriptor {
+                &self.0
+            }
+        }
+
+        LATENCY.observe(0.3);
+        REQUESTS.inc();
+
+        let valid = RegistryBuilder::new()
+            .register(Box::new(&*REQUESTS))
+            .register(Box::new(&*LATENCY))
+            .self_instrument()
+            .build()
+            .unwrap();
+        valid.assert_valid().unwrap();
+
+        let invalid = RegistryBuilder::new()
+            .register(Box::new(DoubledHeaders(
+                Descriptor::new("doubled", "Doubled", Vec::new()).unwrap(),
+            )))
+            .build()
+            .unwrap();
+        assert_eq!(
+            invalid.assert_valid().unwrap_err().kind(),
+            PromErrorKind::InvalidExposition,
+        );
+    }
+
+    #[test]
+    fn float_precision() {
+        static MESSY: Lazy<Gauge<AtomicF64>> =
+            Lazy::new(|| Gauge::new("messy_ratio", "A messy ratio").unwrap());
+        static LATENCY: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("precise_latency")
+                .help("Request latency")
+                .with_buckets(vec![0.123456789])
+                .build()
+                .unwrap()
+        });
+
+        MESSY.set(0.1 + 0.2);
+        LATENCY.observe(2.0 / 3.0);
+
+        let build = |precision| {
+            RegistryBuilder::new()
+                .register(Box::new(&*MESSY))
+                .register(Box::new(&*LATENCY))
+                .register_gauge_fn("precise_nan", "Not a number", || f64::NAN)
+                .unwrap()
+                .float_precision(precision)
+                .build()
+                .unwrap()
+        };
+
+        let exact = build(None).collect_to_string().unwrap();
+        assert!(
+            exact.contains("\nmessy_ratio 0.30000000000000004\n"),
+            "{}",
+            exact
+        );
+        assert!(exact.contains("\nprecise_latency_sum 0.6666666666666666\n"));
+
+        let rounded = build(Some(3)).collect_to_string().unwrap();
+        assert!(rounded.contains("\nmessy_ratio 0.3\n"), "{}", rounded);
+        assert!(rounded.contains("\nprecise_latency_sum 0.667\n"));
+        assert!(rounded.contains("\nprecise_nan NaN\n"));
+        // Bucket boundaries are labels and stay exact
+        assert!(rounded.contains("le=\"0.123456789\""));
+
+        // Families gathered for an encoder carry the registry's precision
+        let registry = build(Some(3));
+        let mut text = Vec::new();
+        TextEncoder::new()
+            .encode(&registry.collect(), &mut text)
+            .unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains("\nmessy_ratio 0.3\n"), "{}", text);
+        assert!(text.contains("\nprecise_latency_sum 0.667\n"));
+
+        let mut openmetrics = Vec::new();
+        OpenMetricsEncoder::new()
+            .encode(&registry.collect(), &mut openmetrics)
+            .unwrap();
+        assert!(String::from_utf8(openmetrics)
+            .unwrap()
+            .contains("\nmessy_ratio 0.3\n"));
+
+        // Labels built while a registry with a precision exists are still exact
+        assert_eq!(Label::quantile(2.0 / 3.0).value(), "0.6666666666666666");
+    }
+
+    #[test]
+    fn series() {
+        use crate::group::{CounterGroup, HistogramGroup};
+
+        static ROUTES: Lazy<CounterGroup<String>> = Lazy::new(|| {
+            CounterGroup::new(
+                "route_requests",
+                "Requests per route",
+                "route",
+                (0..1000).map(|route| format!("/{}", route)),
+            )
+            .unwrap()
+        });
+        static LATENCY: Lazy<HistogramGroup<&'static str>> = Lazy::new(|| {
+            HistogramGroup::new(
+                "route_latency",
+                "Latency per route",
+                "route",
+                vec!["/", "/about"].into_iter(),
+                vec![1u64, 10].into_iter(),
+            )
+            .unwrap()
+        });
+        static SINGLE: Lazy<Histogram> = Lazy::new(|| {
+            HistogramBuilder::new()
+                .name("single_latency")
+                .help("Latency")
+                .with_buckets(vec![0.5])
+                .build()
+                .unwrap()
+        });
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*ROUTES))
+            .register(Box::new(&*LATENCY))
+            .register(Box::new(&*SINGLE))
+            .self_instrument()
+            .build()
+            .unwrap();
+
+        let series = registry.series();
+        // 1000 counters, 2 histograms with a sum, count and 3 buckets each and 1 histogram with
+        // a sum, count and 2 buckets
+        assert_eq!(series.len(), 1000 + 2 * 5 + 4);
+
+        // Every series is a distinct line of the output
+        let output = registry.collect_to_string().unwrap();
+        let samples = output
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with("registry_"))
+            .count();
+        assert_eq!(series.len(), samples);
+
+        assert!(series.contains(&(
+            "route_latency_count".to_owned(),
+            vec![Label::new("route", "/about").unwrap()],
+        )));
+        assert!(series.iter().any(|(name, labels)| {
+            name == "single_latency_bucket"
+                && labels.len() == 1
+                && labels[0].name() == "le"
+                && labels[0].value() == "+Inf"
+        }));
+    }
 }
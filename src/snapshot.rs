@@ -0,0 +1,373 @@
+use crate::{
+    atomics::MetricValue,
+    error::{PromError, PromErrorKind, Result},
+    label::{valid_label_name, valid_metric_name, Label},
+};
+use std::{borrow::Cow, collections::HashMap, convert::TryInto};
+
+/// The magic bytes that start every encoded snapshot
+const SNAPSHOT_MAGIC: &[u8; 4] = b"PRSS";
+
+/// The current version of the binary snapshot format, bumped whenever the layout changes
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// A point in time copy of every sample in a [`Registry`], created with [`Registry::snapshot`]
+///
+/// Meant for tests and delta exporters, two snapshots can be compared with [`RegistrySnapshot::diff`]
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{Counter, MetricValue, RegistryBuilder};
+/// use once_cell::sync::Lazy;
+///
+/// static REQUESTS: Lazy<Counter> = Lazy::new(|| Counter::new("requests", "Total requests").unwrap());
+///
+/// let registry = RegistryBuilder::new().register(Box::new(&*REQUESTS)).build().unwrap();
+///
+/// let before = registry.snapshot();
+/// REQUESTS.inc_by(5);
+/// let after = registry.snapshot();
+///
+/// let diff = after.diff(&before);
+/// assert_eq!(diff.len(), 1);
+/// assert_eq!(diff[0].name(), "requests");
+/// assert_eq!(diff[0].current(), MetricValue::U64(5));
+/// assert_eq!(diff[0].change(), 5.0);
+/// ```
+///
+/// [`Registry`]: crate::Registry
+/// [`Registry::snapshot`]: crate::Registry#method.snapshot
+/// [`RegistrySnapshot::diff`]: crate::RegistrySnapshot#method.diff
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RegistrySnapshot {
+    samples: Vec<(String, Vec<Label>, MetricValue)>,
+    /// The position of each sample in `samples`, keyed by its name and then its labels
+    positions: HashMap<String, HashMap<Vec<Label>, usize>>,
+}
+
+impl RegistrySnapshot {
+    pub(crate) fn new(samples: Vec<(String, Vec<Label>, MetricValue)>) -> Self {
+        let mut positions: HashMap<String, HashMap<Vec<Label>, usize>> = HashMap::new();
+        for (idx, (name, labels, _)) in samples.iter().enumerate() {
+            positions
+                .entry(name.clone())
+                .or_default()
+                .entry(labels.clone())
+                .or_insert(idx);
+        }
+
+        Self { samples, positions }
+    }
+
+    /// Get the value of the sample with the given name and labels
+    pub fn get(&self, name: &str, labels: &[Label]) -> Option<MetricValue> {
+        self.position(name, labels).map(|idx| self.samples[idx].2)
+    }
+
+    fn position(&self, name: &str, labels: &[Label]) -> Option<usize> {
+        self.positions.get(name)?.get(labels).copied()
+    }
+
+    /// Iterate over every sample's name, labels and value in registry order. Names include
+    /// their suffix, like a histogram's `_sum`, `_count` and `_bucket` samples
+    pub fn samples(&self) -> impl Iterator<Item = (&str, &[Label], MetricValue)> + '_ {
+        self.samples
+            .iter()
+            .map(|(name, labels, value)| (name.as_str(), labels.as_slice(), *value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Encode the snapshot into a compact binary format, meant for sending the values of one
+    /// process's registry to another, e.g. from a prefork server's children to their parent.
+    /// The bytes can be decoded with [`RegistrySnapshot::from_bytes`]
+    ///
+    /// The format starts with the magic bytes `PRSS` and a one byte version, followed by the
+    /// number of samples as a `u32`. Each sample is its name, its number of labels as a `u32`,
+    /// each label's name and value, a one byte value tag (`0` for `u64`, `1` for `i64` and `2`
+    /// for `f64`) and the value's eight bytes. Strings are written as their length as a `u32`
+    /// followed by their UTF-8 bytes and every integer is little endian
+    ///
+    /// [`RegistrySnapshot::from_bytes`]: crate::RegistrySnapshot#method.from_bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn write_str(buf: &mut Vec<u8>, string: &str) {
+            buf.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            buf.extend_from_slice(string.as_bytes());
+        }
+
+        let mut buf = Vec::with_capacity(9 + self.samples.len() * 32);
+        buf.extend_from_slice(SNAPSHOT_MAGIC);
+        buf.push(SNAPSHOT_VERSION);
+        buf.extend_from_slice(&(self.samples.len() as u32).to_le_bytes());
+
+        for (name, labels, value) in self.samples.iter() {
+            write_str(&mut buf, name);
+
+            buf.extend_from_slice(&(labels.len() as u32).to_le_bytes());
+            for label in labels {
+                write_str(&mut buf, label.name());
+                write_str(&mut buf, label.value());
+            }
+
+            let (tag, bytes) = match *value {
+                MetricValue::U64(int) => (0, int.to_le_bytes()),
+                MetricValue::I64(int) => (1, int.to_le_bytes()),
+                MetricValue::F64(float) => (2, float.to_bits().to_le_bytes()),
+            };
+            buf.push(tag);
+            buf.extend_from_slice(&bytes);
+        }
+
+        buf
+    }
+
+    /// Decode a snapshot encoded with [`RegistrySnapshot::to_bytes`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{MetricValue, RegistryBuilder, RegistrySnapshot};
+    ///
+    /// let registry = RegistryBuilder::new()
+    ///     .register_gauge_fn("answer", "The answer to everything", || 42.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let bytes = registry.to_bytes();
+    /// let snapshot = RegistrySnapshot::from_bytes(&bytes).unwrap();
+    /// assert_eq!(snapshot.get("answer", &[]), Some(MetricValue::F64(42.0)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the bytes are truncated, were written by an unsupported
+    /// version of the format or contain an invalid metric or label name
+    ///
+    /// [`RegistrySnapshot::to_bytes`]: crate::RegistrySnapshot#method.to_bytes
+    /// [`PromError`]: crate::PromError
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = SnapshotReader { bytes };
+
+        if reader.take(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(PromError::new(
+                "The bytes aren't an encoded registry snapshot",
+                PromErrorKind::InvalidSnapshot,
+            ));
+        }
+
+        let version = reader.take(1)?[0];
+        if version != SNAPSHOT_VERSION {
+            return Err(PromError::new(
+                format!("Unsupported registry snapshot version {}", version),
+                PromErrorKind::InvalidSnapshot,
+            ));
+        }
+
+        let len = reader.u32()? as usize;
+        // The length isn't trusted for preallocation since every sample takes at least 17 bytes
+        let mut samples = Vec::with_capacity(len.min(reader.bytes.len() / 17));
+        for _ in 0..len {
+            let name = reader.string()?;
+            if !valid_metric_name(&name) {
+                return Err(PromError::new(
+                    format!("{:?} isn't a valid metric name", name),
+                    PromErrorKind::InvalidMetricName,
+                ));
+            }
+
+            let label_count = reader.u32()? as usize;
+            let mut labels = Vec::with_capacity(label_count.min(reader.bytes.len() / 8));
+            for _ in 0..label_count {
+                let label_name = reader.string()?;
+                let label_value = reader.string()?;
+
+                // Histogram buckets carry the reserved `le` label, which `Label::new` rejects
+                if label_name != "le" && !valid_label_name(&label_name) {
+                    return Err(PromError::new(
+                        format!("{:?} isn't a valid label name", label_name),
+                        PromErrorKind::InvalidLabelName,
+                    ));
+                }
+                labels.push(Label {
+                    name: Cow::Owned(label_name),
+                    value: Cow::Owned(label_value),
+                });
+            }
+
+            let tag = reader.take(1)?[0];
+            let bits = reader.u64()?;
+            let value = match tag {
+                0 => MetricValue::U64(bits),
+                1 => MetricValue::I64(bits as i64),
+                2 => MetricValue::F64(f64::from_bits(bits)),
+                tag => {
+                    return Err(PromError::new(
+                        format!("Unknown registry snapshot value tag {}", tag),
+                        PromErrorKind::InvalidSnapshot,
+                    ))
+                }
+            };
+
+            samples.push((name, labels, value));
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(PromError::new(
+                "The registry snapshot has trailing bytes",
+                PromErrorKind::InvalidSnapshot,
+            ));
+        }
+
+        Ok(Self::new(samples))
+    }
+
+    /// Add the samples of `other` into this snapshot, for aggregating the registries of
+    /// multiple processes. Samples with the same name and labels are summed, including gauges,
+    /// and samples only `other` has are appended. Integers of the same type saturate, while
+    /// mixing value types sums them as `f64`s
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::{MetricValue, RegistryBuilder, RegistrySnapshot};
+    ///
+    /// let child = RegistryBuilder::new()
+    ///     .register_gauge_fn("connections", "Open connections", || 3.0)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut total = RegistrySnapshot::from_bytes(&child.to_bytes()).unwrap();
+    /// total.merge(&RegistrySnapshot::from_bytes(&child.to_bytes()).unwrap());
+    /// assert_eq!(total.get("connections", &[]), Some(MetricValue::F64(6.0)));
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        for (name, labels, value) in other.samples.iter() {
+            if let Some(idx) = self.position(name, labels) {
+                let current = &mut self.samples[idx].2;
+                *current = match (*current, *value) {
+                    (MetricValue::U64(a), MetricValue::U64(b)) => {
+                        MetricValue::U64(a.saturating_add(b))
+                    }
+                    (MetricValue::I64(a), MetricValue::I64(b)) => {
+                        MetricValue::I64(a.saturating_add(b))
+                    }
+                    (a, b) => MetricValue::F64(a.as_f64() + b.as_f64()),
+                };
+            } else {
+                self.positions
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(labels.clone(), self.samples.len());
+                self.samples.push((name.clone(), labels.clone(), *value));
+            }
+        }
+    }
+
+    /// Get every sample that changed since `earlier` was taken, along with samples that
+    /// didn't exist in `earlier`. Samples are returned in registry order, floats are compared
+    /// by their bits so unchanged `NaN`s aren't reported
+    pub fn diff(&self, earlier: &Self) -> Vec<MetricDelta> {
+        self.samples
+            .iter()
+            .filter_map(|(name, labels, current)| {
+                let previous = earlier.get(name, labels);
+
+                if previous.is_some_and(|previous| previous.bitwise_eq(*current)) {
+                    None
+                } else {
+                    Some(MetricDelta {
+                        name: name.clone(),
+                        labels: labels.clone(),
+                        earlier: previous,
+                        current: *current,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// Reads the fields of an encoded [`RegistrySnapshot`], failing on truncated input
+struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(PromError::new(
+                "The registry snapshot is truncated",
+                PromErrorKind::InvalidSnapshot,
+            ));
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| {
+            PromError::new(
+                "The registry snapshot contains invalid UTF-8",
+                PromErrorKind::InvalidSnapshot,
+            )
+        })
+    }
+}
+
+/// A single sample that changed between two [`RegistrySnapshot`]s
+///
+/// [`RegistrySnapshot`]: crate::RegistrySnapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    name: String,
+    labels: Vec<Label>,
+    earlier: Option<MetricValue>,
+    current: MetricValue,
+}
+
+impl MetricDelta {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// The sample's value in the earlier snapshot, `None` if the sample didn't exist yet
+    pub fn earlier(&self) -> Option<MetricValue> {
+        self.earlier
+    }
+
+    /// The sample's value in the later snapshot
+    pub fn current(&self) -> MetricValue {
+        self.current
+    }
+
+    /// How much the sample changed by, treating missing earlier samples as zero
+    pub fn change(&self) -> f64 {
+        self.current.as_f64() - self.earlier.map_or(0.0, MetricValue::as_f64)
+    }
+}
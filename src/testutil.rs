@@ -0,0 +1,77 @@
+//! Helpers for testing instrumentation, enabled with the `testutil` feature
+
+use crate::{
+    atomics::MetricValue,
+    label::{write_labels, Label},
+    registry::Registry,
+};
+
+/// Assert that the sample of `registry` with the given name and labels currently has the value
+/// `expected`, reading the registry's [`snapshot`] instead of parsing its text output. Labels
+/// have to be given in the same order as the metric's
+///
+/// # Panics
+///
+/// Panics if the sample doesn't exist or has a different value, listing every sample the
+/// registry has
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{testutil::assert_metric_value, Counter, Label, RegistryBuilder};
+/// use once_cell::sync::Lazy;
+///
+/// static REQUESTS: Lazy<Counter> = Lazy::new(|| {
+///     Counter::new("requests", "Total requests")
+///         .unwrap()
+///         .with_labels(vec![Label::new("method", "GET").unwrap()])
+/// });
+///
+/// let registry = RegistryBuilder::new().register(Box::new(&*REQUESTS)).build().unwrap();
+/// REQUESTS.inc_by(3);
+///
+/// assert_metric_value(&registry, "requests", &[Label::new("method", "GET").unwrap()], 3u64);
+/// ```
+///
+/// [`snapshot`]: crate::Registry#method.snapshot
+#[track_caller]
+pub fn assert_metric_value(
+    registry: &Registry,
+    name: &str,
+    labels: &[Label],
+    expected: impl Into<MetricValue>,
+) {
+    let expected = expected.into();
+    let snapshot = registry.snapshot();
+    let actual = snapshot.get(name, labels);
+
+    if actual != Some(expected) {
+        let mut series = String::new();
+        for (sample_name, sample_labels, value) in snapshot.samples() {
+            series.push_str("\n    ");
+            series.push_str(sample_name);
+            if !sample_labels.is_empty() {
+                series.push('{');
+                write_labels(&mut series, sample_labels, false)
+                    .expect("writing to a string can't fail");
+                series.push('}');
+            }
+            series.push_str(&format!(" {:?}", value));
+        }
+
+        let mut wanted = name.to_owned();
+        if !labels.is_empty() {
+            wanted.push('{');
+            write_labels(&mut wanted, labels, false).expect("writing to a string can't fail");
+            wanted.push('}');
+        }
+
+        match actual {
+            Some(actual) => panic!(
+                "expected {} to be {:?}, but it was {:?}. Available series:{}",
+                wanted, expected, actual, series,
+            ),
+            None => panic!("{} doesn't exist. Available series:{}", wanted, series,),
+        }
+    }
+}
@@ -3,7 +3,33 @@ use crate::{
     gauge::Gauge,
     histogram::{Histogram, LocalHistogram},
 };
-use std::time::Instant;
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+/// The unit of time a [`Timer`] records elapsed durations in
+///
+/// [`Timer`]: crate::Timer
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum TimeUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+    Microseconds,
+}
+
+impl TimeUnit {
+    /// Converts `duration` into a whole number of the current unit, truncating any remainder
+    /// and saturating at `u64::MAX`
+    pub fn convert(self, duration: Duration) -> u64 {
+        match self {
+            Self::Seconds => duration.as_secs(),
+            Self::Milliseconds => u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            Self::Microseconds => u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Timer<'a, Target: Observable> {
@@ -26,31 +52,129 @@ impl<'a, Target: Observable> Timer<'a, Target> {
 
 impl<Target: Observable> Drop for Timer<'_, Target> {
     fn drop(&mut self) {
-        self.target.observe(self.start_time.elapsed().as_secs());
+        let elapsed = self.target.time_unit().convert(self.start_time.elapsed());
+        self.target.observe(elapsed);
     }
 }
 
+/// A metric that a [`Timer`] can record elapsed time into. It's implemented for histograms
+/// and gauges and can be implemented for custom metric types to time them as well
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::{Observable, TimeUnit};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// /// Keeps the longest time that was observed
+/// #[derive(Default)]
+/// struct Slowest(AtomicU64);
+///
+/// impl Observable for Slowest {
+///     fn observe(&self, val: u64) {
+///         self.0.fetch_max(val, Ordering::SeqCst);
+///     }
+///
+///     fn time_unit(&self) -> TimeUnit {
+///         TimeUnit::Microseconds
+///     }
+/// }
+///
+/// let slowest = Slowest::default();
+/// let timer = slowest.start_timer();
+/// timer.observe();
+/// ```
+///
+/// [`Timer`]: crate::Timer
 pub trait Observable {
+    /// Record an elapsed time, given in the unit returned by [`Observable::time_unit`]
+    ///
+    /// [`Observable::time_unit`]: crate::Observable#method.time_unit
     fn observe(&self, val: u64);
+
+    /// Start a [`Timer`] that records the time elapsed until it's dropped into `self`
+    ///
+    /// [`Timer`]: crate::Timer
+    fn start_timer(&self) -> Timer<'_, Self>
+    where
+        Self: Sized,
+    {
+        Timer::new(self)
+    }
+
+    /// The unit elapsed time is recorded in by a [`Timer`], defaults to seconds
+    ///
+    /// [`Timer`]: crate::Timer
+    fn time_unit(&self) -> TimeUnit {
+        TimeUnit::Seconds
+    }
 }
 
-impl<'a, Atomic: AtomicNum> Observable for Histogram<Atomic> {
+impl<Atomic: AtomicNum> Observable for Histogram<Atomic> {
     #[inline(always)]
     fn observe(&self, val: u64) {
         self.observe(Num::from_u64(val));
     }
+
+    fn time_unit(&self) -> TimeUnit {
+        self.time_unit()
+    }
 }
 
-impl<'a, Atomic: AtomicNum> Observable for LocalHistogram<'_, Atomic> {
+impl<Atomic: AtomicNum> Observable for LocalHistogram<'_, Atomic> {
     #[inline(always)]
     fn observe(&self, val: u64) {
         self.inner.borrow_mut().observe(Num::from_u64(val));
     }
+
+    fn time_unit(&self) -> TimeUnit {
+        self.inner.borrow().histogram.time_unit
+    }
 }
 
-impl<'a, Atomic: AtomicNum> Observable for Gauge<Atomic> {
+impl<Atomic: AtomicNum> Observable for Gauge<Atomic> {
     #[inline(always)]
     fn observe(&self, val: u64) {
         self.set(Num::from_u64(val));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Mutex, thread};
+
+    /// A user defined metric that keeps every observation
+    #[derive(Debug, Default)]
+    struct Recorder {
+        observed: Mutex<Vec<u64>>,
+    }
+
+    impl Observable for Recorder {
+        fn observe(&self, val: u64) {
+            self.observed.lock().unwrap().push(val);
+        }
+
+        fn time_unit(&self) -> TimeUnit {
+            TimeUnit::Milliseconds
+        }
+    }
+
+    fn time<T: Observable>(target: &T) {
+        let _timer = target.start_timer();
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    #[test]
+    fn user_defined_observable() {
+        let recorder = Recorder::default();
+
+        time(&recorder);
+        Timer::new(&recorder).observe();
+
+        let observed = recorder.observed.lock().unwrap();
+        assert_eq!(observed.len(), 2);
+        assert!(observed[0] >= 20, "{:?}", observed);
+        assert!(observed[1] < 20, "{:?}", observed);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::{PromError, PromErrorKind, Result};
+use std::collections::{HashMap, HashSet};
+
+/// The labels of a sample as sorted `(name, value)` pairs, with label values still escaped
+type LabelSet = Vec<(String, String)>;
+
+/// Checks Prometheus text format output for the mistakes scrapers reject or silently
+/// misreport: repeated `# HELP` or `# TYPE` lines, repeated series and histogram buckets that
+/// aren't cumulative or are missing their `+Inf` bucket
+pub(crate) fn validate_exposition(text: &str) -> Result<()> {
+    let mut helps = HashSet::new();
+    let mut types = HashMap::new();
+    let mut series = HashSet::new();
+    // The `(le, count)` pairs of each histogram series, keyed by the family and its other labels
+    let mut buckets: HashMap<(&str, LabelSet), Vec<(f64, f64)>> = HashMap::new();
+    // The family and labels of each histogram series with a `_sum` or `_count` sample
+    let mut aggregates = HashSet::new();
+
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        if let Some(header) = line.strip_prefix("# ") {
+            let mut parts = header.splitn(3, ' ');
+
+            let (kind, name) = (parts.next(), parts.next());
+            let repeated = match (kind, name) {
+                (Some("HELP"), Some(name)) => !helps.insert(name),
+                (Some("TYPE"), Some(name)) => types
+                    .insert(name, parts.next().unwrap_or_default())
+                    .is_some(),
+                _ => false,
+            };
+
+            if repeated {
+                return Err(invalid(format!(
+                    "{} has more than one # {} line",
+                    name.unwrap_or_default(),
+                    kind.unwrap_or_default()
+                )));
+            }
+
+            continue;
+        }
+
+        let (name, labels, value) = parse_sample(line)?;
+        if !series.insert((name, labels.clone())) {
+            return Err(invalid(format!("The series {:?} is repeated", line)));
+        }
+
+        let histogram = |suffix| {
+            name.strip_suffix(suffix)
+                .filter(|family| types.get(family) == Some(&"histogram"))
+        };
+
+        if let Some(family) = histogram("_bucket") {
+            let (le, other_labels): (Vec<_>, Vec<_>) =
+                labels.into_iter().partition(|(label, _)| label == "le");
+            let le = match le.first().map(|(_, le)| le.parse::<f64>()) {
+                Some(Ok(le)) => le,
+                _ => {
+                    return Err(invalid(format!(
+                        "The histogram bucket {:?} has no valid `le` label",
+                        line
+                    )))
+                }
+            };
+
+            buckets
+                .entry((family, other_labels))
+                .or_default()
+                .push((le, value));
+        } else if let Some(family) = histogram("_sum").or_else(|| histogram("_count")) {
+            aggregates.insert((family, labels));
+        }
+    }
+
+    // A series with a `_sum` or `_count` but no buckets is missing its `+Inf` bucket too
+    for aggregate in aggregates {
+        buckets.entry(aggregate).or_default();
+    }
+
+    for ((family, _), mut counts) in buckets {
+        counts.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        if counts.last().map(|&(le, _)| le) != Some(f64::INFINITY) {
+            return Err(invalid(format!(
+                "The histogram {} has no +Inf bucket",
+                family
+            )));
+        }
+
+        if counts.windows(2).any(|pair| pair[1].1 < pair[0].1) {
+            return Err(invalid(format!(
+                "The buckets of the histogram {} aren't cumulative",
+                family
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a sample line into its name, sorted labels and value
+fn parse_sample(line: &str) -> Result<(&str, LabelSet, f64)> {
+    let name_end = line
+        .find(['{', ' '])
+        .ok_or_else(|| invalid(format!("The sample {:?} has no value", line)))?;
+    let name = &line[..name_end];
+    let mut rest = &line[name_end..];
+
+    let mut labels = Vec::new();
+    if let Some(mut pairs) = rest.strip_prefix('{') {
+        loop {
+            pairs = pairs.trim_start_matches(',');
+            if let Some(after) = pairs.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+
+            let (label, after_name) = pairs
+                .split_once("=\"")
+                .ok_or_else(|| invalid(format!("The sample {:?} has malformed labels", line)))?;
+
+            // Find the closing quote, skipping over escaped characters
+            let mut escaped = false;
+            let value_end = after_name
+                .char_indices()
+                .find(|&(_, c)| {
+                    let closes = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closes
+                })
+                .map(|(idx, _)| idx)
+                .ok_or_else(|| invalid(format!("The sample {:?} has malformed labels", line)))?;
+
+            labels.push((label.to_owned(), after_name[..value_end].to_owned()));
+            pairs = &after_name[value_end + 1..];
+        }
+    }
+    labels.sort();
+
+    let value = rest
+        .split_whitespace()
+        .next()
+        .and_then(|value| match value {
+            "+Inf" => Some(f64::INFINITY),
+            "-Inf" => Some(f64::NEG_INFINITY),
+            value => value.parse().ok(),
+        })
+        .ok_or_else(|| invalid(format!("The sample {:?} has no valid value", line)))?;
+
+    Ok((name, labels, value))
+}
+
+fn invalid(message: String) -> PromError {
+    PromError::new(message, PromErrorKind::InvalidExposition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn violations() {
+        let valid = "# HELP latency Latency\n\
+                     # TYPE latency histogram\n\
+                     latency_sum{path=\"/a,\\\"}\"} 1.5\n\
+                     latency_count{path=\"/a,\\\"}\"} 2\n\
+                     latency_bucket{path=\"/a,\\\"}\",le=\"0.5\"} 1\n\
+                     latency_bucket{path=\"/a,\\\"}\",le=\"+Inf\"} 2\n\
+                     latency_bucket{path=\"/b\",le=\"0.5\"} 0\n\
+                     latency_bucket{path=\"/b\",le=\"+Inf\"} 0\n";
+        assert!(validate_exposition(valid).is_ok());
+
+        let invalid = [
+            // Repeated headers
+            "# HELP a A\n# HELP a A\n# TYPE a gauge\na 1\n",
+            "# HELP a A\n# TYPE a gauge\n# TYPE a gauge\na 1\n",
+            // Repeated label sets, regardless of label order
+            "# TYPE a gauge\na{x=\"1\",y=\"2\"} 1\na{y=\"2\",x=\"1\"} 2\n",
+            // Buckets that aren't cumulative
+            "# TYPE h histogram\nh_bucket{le=\"0.5\"} 3\nh_bucket{le=\"+Inf\"} 2\n",
+            // A missing `+Inf` bucket
+            "# TYPE h histogram\nh_bucket{le=\"0.5\"} 3\nh_bucket{le=\"1.0\"} 3\n",
+            "# TYPE h histogram\nh_sum 1.5\nh_count 2\n",
+            "# TYPE h histogram\nh_count{a=\"1\"} 2\nh_bucket{a=\"2\",le=\"+Inf\"} 2\n",
+            // Malformed samples
+            "# TYPE a gauge\na{x=\"1} 1\n",
+            "# TYPE a gauge\na\n",
+        ];
+
+        for text in invalid.iter() {
+            let err = validate_exposition(text).unwrap_err();
+            assert_eq!(err.kind(), PromErrorKind::InvalidExposition, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    atomics::{AtomicNum, FormatOptions, MetricValue},
+    counter::Counter,
+    error::{PromError, PromErrorKind, Result},
+    label::{valid_label_name, Label},
+    registry::{Collectable, Descriptor},
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{atomic::AtomicU64, Arc, RwLock},
+};
+
+/// A family of [`Counter`]s sharing a name and help which are partitioned by the values of a
+/// fixed set of `N` label names, children are created the first time their label values are used
+///
+/// The number of label values is checked at compile time, so giving the wrong number of values
+/// is a type error rather than a runtime one
+///
+/// ```rust,compile_fail
+/// use prometheus_rs::CounterVec;
+/// use std::sync::atomic::AtomicU64;
+///
+/// let requests: CounterVec<2, AtomicU64> =
+///     CounterVec::new("requests", "Total requests", ["method", "status"]).unwrap();
+///
+/// requests.with_label_values(["GET"]).inc();
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use prometheus_rs::CounterVec;
+/// use std::sync::atomic::AtomicU64;
+///
+/// let requests: CounterVec<2, AtomicU64> =
+///     CounterVec::new("requests", "Total requests", ["method", "status"]).unwrap();
+///
+/// requests.with_label_values(["GET", "200"]).inc();
+/// assert_eq!(requests.with_label_values(["GET", "200"]).get(), 1);
+/// ```
+///
+/// [`Counter`]: crate::Counter
+#[derive(Debug)]
+pub struct CounterVec<const N: usize, Atomic: AtomicNum = AtomicU64> {
+    descriptor: Descriptor,
+    label_names: [&'static str; N],
+    children: RwLock<HashMap<[String; N], Arc<Counter<Atomic>>>>,
+}
+
+impl<const N: usize, Atomic: AtomicNum> CounterVec<N, Atomic> {
+    /// Create a new `CounterVec` whose children are labeled with `label_names`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PromError`] if the name isn't a valid metric name or if any of the label
+    /// names are invalid
+    ///
+    /// [`PromError`]: crate::PromError
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        help: impl AsRef<str>,
+        label_names: [&'static str; N],
+    ) -> Result<Self> {
+        if !label_names.iter().all(|label| valid_label_name(label)) {
+            return Err(PromError::new(
+                "Label name contains invalid characters",
+                PromErrorKind::InvalidLabelName,
+            ));
+        }
+
+        Ok(Self {
+            descriptor: Descriptor::new(name, help, Vec::new())?,
+            label_names,
+            children: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Get the child counter with the given label values, creating it if it doesn't exist yet.
+    /// The values are given in the same order as the vec's label names
+    pub fn with_label_values(&self, values: [&str; N]) -> Arc<Counter<Atomic>> {
+        let key = values.map(String::from);
+
+        if let Some(child) = self
+            .children
+            .read()
+            .expect("a counter vec's lock was poisoned")
+            .get(&key)
+        {
+            return child.clone();
+        }
+
+        // Another thread may have created the child between releasing the read lock and
+        // acquiring the write lock
+        let mut children = self
+            .children
+            .write()
+            .expect("a counter vec's lock was poisoned");
+        if let Some(child) = children.get(&key) {
+            return child.clone();
+        }
+
+        let child = Arc::new(self.new_child(&key));
+        children.insert(key, child.clone());
+
+        child
+    }
+
+    /// Create a zeroed child for every set of label values so that they're exported before
+    /// they're first incremented, letting `rate()` work from the first scrape. Children that
+    /// already exist are left untouched
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use prometheus_rs::CounterVec;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// let requests: CounterVec<1, AtomicU64> =
+    ///     CounterVec::new("requests", "Total requests", ["method"]).unwrap();
+    /// requests.init_label_values(&[["GET"], ["POST"]]);
+    ///
+    /// assert_eq!(requests.len(), 2);
+    /// ```
+    pub fn init_label_values(&self, label_values: &[[&str; N]]) {
+        for &values in label_values {
+            self.with_label_values(values);
+        }
+    }
+
+    /// Remove the child with the given label values so that it's no longer exported and
+    /// Prometheus marks the series as absent, returning `true` if it existed
+    ///
+    /// Handles to the removed child stay usable, but their updates are no longer exported. Using
+    /// the same label values again creates a new, zeroed child
+    pub fn remove(&self, values: [&str; N]) -> bool {
+        let key = values.map(String::from);
+
+        self.children
+            .write()
+            .expect("a counter vec's lock was poisoned")
+            .remove(&key)
+            .is_some()
+    }
+
+    /// The number of children the vec currently holds
+    pub fn len(&self) -> usize {
+        self.children
+            .read()
+            .expect("a counter vec's lock was poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn name(&self) -> &str {
+        self.descriptor.name()
+    }
+
+    pub fn help(&self) -> &str {
+        self.descriptor.help()
+    }
+
+    pub fn label_names(&self) -> &[&'static str; N] {
+        &self.label_names
+    }
+
+    fn new_child(&self, values: &[String; N]) -> Counter<Atomic> {
+        let labels: Vec<Label> = self
+            .label_names
+            .iter()
+            .zip(values)
+            .map(|(&name, value)| Label {
+                name: Cow::Borrowed(name),
+                value: Cow::Owned(value.clone()),
+            })
+            .collect();
+
+        Counter::new(self.name().to_owned(), self.help())
+            .expect("the vec's name was already validated")
+            .with_labels(labels)
+    }
+
+    /// Every child of the vec sorted by its label values, so that encoding doesn't depend on
+    /// the `HashMap`'s iteration order
+    fn sorted(&self) -> Vec<Arc<Counter<Atomic>>> {
+        let children = self
+            .children
+            .read()
+            .expect("a counter vec's lock was poisoned");
+
+        let mut sorted: Vec<_> = children.iter().collect();
+        sorted.sort_unstable_by_key(|&(values, _)| values);
+
+        sorted.into_iter().map(|(_, child)| child.clone()).collect()
+    }
+}
+
+impl<const N: usize, Atomic: AtomicNum> Collectable for &CounterVec<N, Atomic> {
+    fn encode_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        for child in self.sorted() {
+            (&*child).encode_samples(buf, options)?;
+        }
+
+        Ok(())
+    }
+
+    fn encode_openmetrics_samples(&self, buf: &mut String, options: &FormatOptions) -> Result<()> {
+        for child in self.sorted() {
+            (&*child).encode_openmetrics_samples(buf, options)?;
+        }
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> &'static str {
+        "counter"
+    }
+
+    fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    fn for_each_sample(&self, visit: &mut dyn FnMut(&'static str, &[Label], MetricValue)) {
+        for child in self.sorted().iter() {
+            (&**child).for_each_sample(visit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_label_values() {
+        let requests: CounterVec<2, AtomicU64> =
+            CounterVec::new("requests", "Total requests", ["method", "status"]).unwrap();
+
+        requests.init_label_values(&[["GET", "200"], ["POST", "500"]]);
+        assert_eq!(requests.len(), 2);
+
+        let mut buf = String::new();
+        (&requests)
+            .encode_text(&mut buf, &FormatOptions::new())
+            .unwrap();
+        assert_eq!(
+            buf,
+            "# HELP requests Total requests\n\
+             # TYPE requests counter\n\
+             requests{method=\"GET\",status=\"200\"} 0\n\
+             requests{method=\"POST\",status=\"500\"} 0\n",
+        );
+
+        // Initializing an existing child doesn't reset it
+        requests.with_label_values(["GET", "200"]).inc();
+        requests.init_label_values(&[["GET", "200"]]);
+        assert_eq!(requests.with_label_values(["GET", "200"]).get(), 1);
+    }
+
+    #[test]
+    fn label_arity() {
+        let requests: CounterVec<3, AtomicU64> =
+            CounterVec::new("requests", "Total requests", ["method", "status", "path"]).unwrap();
+        assert_eq!(requests.label_names(), &["method", "status", "path"]);
+
+        let child = requests.with_label_values(["GET", "200", "/"]);
+        child.inc_by(2);
+        assert_eq!(
+            child.labels(),
+            Label::from_pairs(vec![("method", "GET"), ("status", "200"), ("path", "/")])
+                .unwrap()
+                .as_slice(),
+        );
+
+        assert!(requests.remove(["GET", "200", "/"]));
+        assert!(requests.is_empty());
+
+        let err =
+            CounterVec::<1, AtomicU64>::new("requests", "Total requests", ["le"]).unwrap_err();
+        assert_eq!(err.kind(), PromErrorKind::InvalidLabelName);
+    }
+
+    #[test]
+    fn remove() {
+        use crate::registry::RegistryBuilder;
+        use once_cell::sync::Lazy;
+
+        static CUSTOMERS: Lazy<CounterVec<1, AtomicU64>> = Lazy::new(|| {
+            CounterVec::new("customer_requests", "Requests per customer", ["customer"]).unwrap()
+        });
+
+        CUSTOMERS.with_label_values(["acme"]).inc();
+        CUSTOMERS.with_label_values(["initech"]).inc_by(5);
+
+        let registry = RegistryBuilder::new()
+            .register(Box::new(&*CUSTOMERS))
+            .build()
+            .unwrap();
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP customer_requests Requests per customer\n\
+             # TYPE customer_requests counter\n\
+             customer_requests{customer=\"acme\"} 1\n\
+             customer_requests{customer=\"initech\"} 5\n",
+        );
+
+        assert!(CUSTOMERS.remove(["initech"]));
+        assert!(!CUSTOMERS.remove(["initech"]));
+        assert_eq!(
+            registry.collect_to_string().unwrap(),
+            "# HELP customer_requests Requests per customer\n\
+             # TYPE customer_requests counter\n\
+             customer_requests{customer=\"acme\"} 1\n",
+        );
+
+        // The series starts over from zero when it's used again
+        assert_eq!(CUSTOMERS.with_label_values(["initech"]).get(), 0);
+    }
+}